@@ -0,0 +1,86 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+#![feature(test)]
+
+extern crate rsx_stylesheet;
+extern crate test;
+
+use rsx_stylesheet::types::{InlineRules, StyleDeclarations, StyleRule, StyleSelector, Stylesheet};
+use test::{black_box, Bencher};
+
+// Roughly the size of a design system's stylesheet.
+const RULES: usize = 2000;
+
+fn rules() -> Vec<StyleRule> {
+    (0..RULES)
+        .map(|i| StyleRule {
+            selectors: vec![
+                StyleSelector::from(format!(".component-{} > .element-{}", i, i)),
+                StyleSelector::from(format!("#id-{}", i))
+            ].into_iter()
+                .collect(),
+            declarations: StyleDeclarations::default()
+        })
+        .collect()
+}
+
+fn stylesheet() -> Stylesheet {
+    Stylesheet::from(rules().into_iter().collect::<InlineRules>())
+}
+
+fn selectors() -> Vec<String> {
+    (0..RULES).map(|i| format!("#id-{}", i)).collect()
+}
+
+// What `Stylesheet::index_of` used to do.
+fn linear_index_of(rules: &[StyleRule], selector: &str) -> Option<usize> {
+    rules
+        .iter()
+        .position(|rule| rule.selectors.iter().any(|s| s.as_ref() == selector))
+}
+
+#[bench]
+fn bench_lookup_linear(b: &mut Bencher) {
+    let rules = rules();
+    let selectors = selectors();
+
+    b.iter(|| {
+        for selector in &selectors {
+            black_box(linear_index_of(&rules, selector));
+        }
+    });
+}
+
+#[bench]
+fn bench_lookup_indexed(b: &mut Bencher) {
+    let stylesheet = stylesheet();
+    let selectors = selectors();
+
+    b.iter(|| {
+        for selector in &selectors {
+            black_box(stylesheet.index_of(selector));
+        }
+    });
+}
+
+#[bench]
+fn bench_take(b: &mut Bencher) {
+    let stylesheet = stylesheet();
+    let selectors = selectors();
+
+    b.iter(|| {
+        let mut stylesheet = stylesheet.clone();
+        for selector in &selectors {
+            black_box(stylesheet.take(selector));
+        }
+    });
+}
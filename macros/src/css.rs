@@ -0,0 +1,225 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use rsx_stylesheet::servo_css_parser::cssparser::{
+    AtRuleParser,
+    AtRuleType,
+    CowRcStr,
+    DeclarationListParser,
+    DeclarationParser,
+    ParseError,
+    Parser,
+    ParserInput,
+    QualifiedRuleParser,
+    RuleListParser,
+    SourceLocation
+};
+use rsx_stylesheet::servo_css_parser::parse as servo_parse;
+use rsx_stylesheet::servo_css_parser::style::properties::PropertyId;
+use rsx_stylesheet::servo_css_parser::style::stylesheets::CssRule;
+use rsx_stylesheet::servo_css_parser::types::{MediaList, Origin, QuirksMode, ServoStylesheet, Url};
+
+#[derive(Debug)]
+pub struct CssError {
+    pub offset: usize,
+    pub message: String
+}
+
+impl CssError {
+    fn new<T: Into<String>>(offset: usize, message: T) -> Self {
+        CssError {
+            offset,
+            message: message.into()
+        }
+    }
+
+    // The 1-based line and column of the error in the given source, counted
+    // in characters. Offsets past the end, or within a character, point at
+    // the character they fall in.
+    pub fn location(&self, css: &str) -> (usize, usize) {
+        let mut line = 1;
+        let mut column = 1;
+        for (i, c) in css.char_indices() {
+            if i + c.len_utf8() > self.offset {
+                break;
+            }
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+}
+
+// A top-level style rule, as found by cssparser. Declarations that aren't
+// well formed are kept as the text cssparser skipped, in source order.
+struct Rule<'i> {
+    location: SourceLocation,
+    selectors: &'i str,
+    declarations: Vec<Result<Declaration<'i>, &'i str>>
+}
+
+struct Declaration<'i> {
+    name: String,
+    value: &'i str
+}
+
+fn parse(css: &str) -> ServoStylesheet {
+    let url = Url::parse("about:blank").unwrap();
+    let origin = Origin::Author;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+    servo_parse(css, url, origin, qm, media)
+}
+
+// Servo recovers from invalid CSS by silently dropping whatever it can't
+// parse, so check that every selector and declaration found by walking the
+// rules with cssparser survives parsing. Rules are matched with Servo's by
+// their source location. Returns Servo's stylesheet when nothing was dropped.
+pub fn check(css: &str) -> Result<ServoStylesheet, CssError> {
+    let stylesheet = parse(css);
+    {
+        let guard = stylesheet.shared_lock.read();
+        let servo_rules = &stylesheet.contents.rules.read_with(&guard).0;
+
+        let mut input = ParserInput::new(css);
+        let mut parser = Parser::new(&mut input);
+        for rule in RuleListParser::new_for_stylesheet(&mut parser, RulesParser) {
+            let rule = match rule {
+                Ok(Some(rule)) => rule,
+                // At-rules aren't supported yet.
+                Ok(None) => continue,
+                Err((_, text)) => return Err(CssError::new(offset_of(css, text), "expected a `{ ... }` block after the selector"))
+            };
+            if rule.selectors.is_empty() {
+                return Err(CssError::new(offset_of(css, rule.selectors), "expected a selector before `{`"));
+            }
+
+            let servo_rule = servo_rules
+                .iter()
+                .filter_map(|servo_rule| match servo_rule {
+                    &CssRule::Style(ref style) => Some(style.read_with(&guard)),
+                    _ => None
+                })
+                .find(|style| style.source_location.line == rule.location.line && style.source_location.column == rule.location.column);
+            let block = match servo_rule {
+                Some(style) => style.block.read_with(&guard),
+                None => {
+                    let message = format!("invalid selector `{}`", rule.selectors);
+                    return Err(CssError::new(offset_of(css, rule.selectors), message));
+                }
+            };
+
+            // A declaration overridden by a later one for the same property
+            // isn't told apart from a dropped one, since Servo keeps neither.
+            for declaration in rule.declarations {
+                let declaration = match declaration {
+                    Ok(declaration) => declaration,
+                    Err(text) => {
+                        let message = format!("invalid declaration `{}`", text.trim());
+                        return Err(CssError::new(offset_of(css, text), message));
+                    }
+                };
+                let id = match PropertyId::parse(&declaration.name) {
+                    Ok(id) => id,
+                    Err(_) => {
+                        let message = format!("unknown property `{}`", declaration.name);
+                        return Err(CssError::new(offset_of(css, declaration.value), message));
+                    }
+                };
+                if !block.declarations().iter().any(|servo| servo.id().is_or_is_longhand_of(&id)) {
+                    let message = format!("invalid value `{}` for `{}`", declaration.value.trim(), declaration.name);
+                    return Err(CssError::new(offset_of(css, declaration.value), message));
+                }
+            }
+        }
+    }
+    Ok(stylesheet)
+}
+
+// Slices handed out by cssparser borrow from the source, so their offset in
+// it is where they start.
+fn offset_of(css: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - css.as_ptr() as usize
+}
+
+// Collects the top-level style rules, skipping at-rules.
+struct RulesParser;
+
+impl<'i> QualifiedRuleParser<'i> for RulesParser {
+    type Prelude = (SourceLocation, &'i str);
+    type QualifiedRule = Option<Rule<'i>>;
+    type Error = ();
+
+    fn parse_prelude<'t>(&mut self, input: &mut Parser<'i, 't>) -> Result<Self::Prelude, ParseError<'i, ()>> {
+        let location = input.current_source_location();
+        let start = input.position();
+        while input.next().is_ok() {}
+        Ok((location, input.slice_from(start).trim()))
+    }
+
+    fn parse_block<'t>(&mut self, prelude: Self::Prelude, input: &mut Parser<'i, 't>) -> Result<Self::QualifiedRule, ParseError<'i, ()>> {
+        let (location, selectors) = prelude;
+        let declarations = DeclarationListParser::new(input, DeclarationsParser)
+            .map(|declaration| declaration.map_err(|(_, text)| text))
+            .collect();
+        Ok(Some(Rule { location, selectors, declarations }))
+    }
+}
+
+impl<'i> AtRuleParser<'i> for RulesParser {
+    type PreludeNoBlock = ();
+    type PreludeBlock = ();
+    type AtRule = Option<Rule<'i>>;
+    type Error = ();
+
+    fn parse_prelude<'t>(&mut self, _: CowRcStr<'i>, input: &mut Parser<'i, 't>) -> Result<AtRuleType<(), ()>, ParseError<'i, ()>> {
+        while input.next().is_ok() {}
+        Ok(AtRuleType::WithBlock(()))
+    }
+
+    fn rule_without_block(&mut self, _: ()) -> Self::AtRule {
+        None
+    }
+
+    fn parse_block<'t>(&mut self, _: (), input: &mut Parser<'i, 't>) -> Result<Self::AtRule, ParseError<'i, ()>> {
+        while input.next().is_ok() {}
+        Ok(None)
+    }
+}
+
+// Collects the declarations of a style rule. At-rules aren't allowed in
+// there, and are reported as malformed declarations.
+struct DeclarationsParser;
+
+impl<'i> DeclarationParser<'i> for DeclarationsParser {
+    type Declaration = Declaration<'i>;
+    type Error = ();
+
+    fn parse_value<'t>(&mut self, name: CowRcStr<'i>, input: &mut Parser<'i, 't>) -> Result<Self::Declaration, ParseError<'i, ()>> {
+        let start = input.position();
+        while input.next().is_ok() {}
+        Ok(Declaration {
+            name: name.to_string(),
+            value: input.slice_from(start)
+        })
+    }
+}
+
+impl<'i> AtRuleParser<'i> for DeclarationsParser {
+    type PreludeNoBlock = ();
+    type PreludeBlock = ();
+    type AtRule = Declaration<'i>;
+    type Error = ();
+}
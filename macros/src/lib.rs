@@ -0,0 +1,314 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+#![feature(proc_macro)]
+#![feature(slice_patterns)]
+
+extern crate proc_macro;
+#[macro_use]
+extern crate quote;
+extern crate rsx_stylesheet;
+extern crate self_tokenize_trait;
+
+mod css;
+mod properties;
+mod suggest;
+mod tokens;
+mod values;
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
+
+use proc_macro::{Delimiter, Spacing, Span, Term, TokenNode, TokenStream, TokenTree};
+use rsx_stylesheet::types::Stylesheet;
+use self_tokenize_trait::{ToCustomTokens, Tokens};
+
+use properties::ValueKind;
+use suggest::closest;
+use tokens::{hyphenated, span_of, tokens, Token, TokenKind};
+
+// Same as `style!`, but unknown properties and invalid values are reported
+// as compile errors pointing at the offending tokens.
+#[proc_macro]
+pub fn checked_style(input: TokenStream) -> TokenStream {
+    let tokens = tokens(input.clone());
+    let mut rest = &tokens[..];
+    let mut valid = true;
+
+    if let [ref hash, ref include, ref path, ..] = *rest {
+        if hash.is_punct('#') && include.is_ident("include") {
+            valid &= check_include(path);
+            rest = &rest[3..];
+            if rest.first().map(|token| token.is_punct(';')).unwrap_or(false) {
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    while !rest.is_empty() {
+        let (name, len) = match hyphenated(rest) {
+            Some(name) => name,
+            None => return error(rest[0].span, "expected a property name")
+        };
+        let name_tokens = &rest[..len];
+        rest = &rest[len..];
+
+        match rest.first() {
+            Some(colon) if colon.is_punct(':') => rest = &rest[1..],
+            _ => return error(span_of(name_tokens), "expected `:` after the property name")
+        }
+
+        let value = match rest.first() {
+            Some(&Token { kind: TokenKind::Group(Delimiter::Brace, ref value), .. }) => value,
+            Some(token) => return error(token.span, "expected the value in braces, like `{ 10 px }`"),
+            None => return error(span_of(name_tokens), "expected a value after the property name")
+        };
+        rest = &rest[1..];
+
+        valid &= check_declaration(&name, name_tokens, value, true);
+
+        match rest.first() {
+            Some(semicolon) if semicolon.is_punct(';') => rest = &rest[1..],
+            Some(token) => return error(token.span, "expected `;` after the declaration"),
+            None => {}
+        }
+    }
+
+    if valid {
+        expand("style", Delimiter::Brace, input)
+    } else {
+        invalid()
+    }
+}
+
+// Same as `declaration!`, but unknown properties and invalid values are
+// reported as compile errors pointing at the offending tokens.
+#[proc_macro]
+pub fn checked_declaration(input: TokenStream) -> TokenStream {
+    let tokens = tokens(input.clone());
+
+    let (name, len) = match hyphenated(&tokens) {
+        Some(name) => name,
+        None => return error(span_of(&tokens), "expected a property name")
+    };
+    let name_tokens = &tokens[..len];
+
+    match tokens.get(len) {
+        Some(colon) if colon.is_punct(':') => {}
+        _ => return error(span_of(name_tokens), "expected `:` after the property name")
+    }
+
+    if check_declaration(&name, name_tokens, &tokens[len + 1..], false) {
+        expand("declaration", Delimiter::Parenthesis, input)
+    } else {
+        invalid()
+    }
+}
+
+fn check_include(path: &Token) -> bool {
+    match path.kind {
+        TokenKind::Group(Delimiter::Parenthesis, ref path) if path.len() == 1 && path[0].is_string() => {
+            if let TokenKind::Literal(ref literal) = path[0].kind {
+                if literal == "\"defaults.css\"" {
+                    return true;
+                }
+            }
+            path[0].span.error("unknown include").help("only `\"defaults.css\"` can be included").emit();
+            false
+        }
+        _ => {
+            path.span.error("expected a quoted path, like `(\"defaults.css\")`").emit();
+            false
+        }
+    }
+}
+
+// Parses a CSS string literal at compile time into a tokenized `Stylesheet`,
+// so that no parsing happens at runtime. CSS that Servo would drop is
+// reported as a compile error instead. The expansion refers to the types in
+// `rsx_stylesheet::types`.
+#[proc_macro]
+pub fn css(input: TokenStream) -> TokenStream {
+    match css_literal(input) {
+        Ok((css, span)) => stylesheet(&css, span, None, false),
+        Err(err) => err
+    }
+}
+
+// Same as `css!`, but reads the CSS from a file. Relative paths are resolved
+// against the directory containing the crate's `Cargo.toml`.
+#[proc_macro]
+pub fn include_css(input: TokenStream) -> TokenStream {
+    match css_file(input) {
+        Ok((css, path, span)) => stylesheet(&css, span, Some(&path), false),
+        Err(err) => err
+    }
+}
+
+// Same as `css!`, but with class names made local to the stylesheet, as with
+// CSS modules. Expands to a `(Stylesheet, ClassNames)` tuple, the latter
+// mapping each class name written in the CSS to the one in the stylesheet.
+#[proc_macro]
+pub fn css_module(input: TokenStream) -> TokenStream {
+    match css_literal(input) {
+        Ok((css, span)) => stylesheet(&css, span, None, true),
+        Err(err) => err
+    }
+}
+
+// Same as `css_module!`, but reads the CSS from a file, like `include_css!`.
+#[proc_macro]
+pub fn include_css_module(input: TokenStream) -> TokenStream {
+    match css_file(input) {
+        Ok((css, path, span)) => stylesheet(&css, span, Some(&path), true),
+        Err(err) => err
+    }
+}
+
+fn css_literal(input: TokenStream) -> Result<(String, Span), TokenStream> {
+    let tokens = tokens(input);
+    match tokens.as_slice() {
+        &[ref literal] => match literal.string_value() {
+            Some(css) => Ok((css, span_of(&tokens))),
+            None => Err(error(literal.span, "expected a CSS string literal"))
+        },
+        _ => Err(error(span_of(&tokens), "expected a CSS string literal"))
+    }
+}
+
+fn css_file(input: TokenStream) -> Result<(String, PathBuf, Span), TokenStream> {
+    let tokens = tokens(input);
+    let path = match tokens.as_slice() {
+        &[ref literal] => match literal.string_value() {
+            Some(path) => path,
+            None => return Err(error(literal.span, "expected a quoted path, like `\"style.css\"`"))
+        },
+        _ => return Err(error(span_of(&tokens), "expected a quoted path, like `\"style.css\"`"))
+    };
+
+    let path = match env::var("CARGO_MANIFEST_DIR") {
+        Ok(dir) => PathBuf::from(dir).join(path),
+        Err(_) => PathBuf::from(path)
+    };
+    let mut css = String::new();
+    if let Err(err) = File::open(&path).and_then(|mut file| file.read_to_string(&mut css)) {
+        return Err(error(span_of(&tokens), &format!("couldn't read `{}`: {}", path.display(), err)));
+    }
+    Ok((css, path, span_of(&tokens)))
+}
+
+fn stylesheet(css: &str, span: Span, path: Option<&Path>, module: bool) -> TokenStream {
+    let servo_stylesheet = match css::check(css) {
+        Ok(servo_stylesheet) => servo_stylesheet,
+        Err(err) => {
+            let (line, column) = err.location(css);
+            let message = match path {
+                Some(path) => format!("{} at {}:{}:{}", err.message, path.display(), line, column),
+                None => format!("{} at line {}, column {}", err.message, line, column)
+            };
+            return error(span, &message);
+        }
+    };
+
+    let mut tokens = Tokens::new();
+    if module {
+        let (stylesheet, class_names) = Stylesheet::from_servo_with_class_names(servo_stylesheet);
+        let mut stylesheet_tokens = Tokens::new();
+        let mut class_names_tokens = Tokens::new();
+        stylesheet.to_custom_tokens(&mut stylesheet_tokens);
+        class_names.to_custom_tokens(&mut class_names_tokens);
+        tokens.append(quote! { (#stylesheet_tokens, #class_names_tokens) });
+    } else {
+        Stylesheet::from(servo_stylesheet).to_custom_tokens(&mut tokens);
+    }
+
+    // Including the file makes the crate rebuild whenever it changes.
+    let dependency = match path {
+        Some(path) => {
+            let path = path.display().to_string();
+            quote! { let _ = include_str!(#path); }
+        }
+        None => quote! {}
+    };
+    let expansion = quote! {
+        {
+            use rsx_stylesheet::types::*;
+            #dependency
+            #tokens
+        }
+    };
+    expansion.parse().unwrap()
+}
+
+// Reports an unknown property or an invalid value, returning whether the
+// declaration is valid.
+fn check_declaration(name: &str, name_tokens: &[Token], value: &[Token], shorthands: bool) -> bool {
+    let kind = match properties::find(name) {
+        Some(kind) => kind,
+        None => {
+            let message = match closest(name, &properties::names()) {
+                Some(suggestion) => format!("unknown property `{}`, did you mean `{}`?", name, suggestion),
+                None => format!("unknown property `{}`", name)
+            };
+            span_of(name_tokens).error(message).emit();
+            return false;
+        }
+    };
+
+    // Shorthands with more than one value are only expanded by `style!`, and
+    // `declaration!` keeps the single value layout shorthands.
+    let kind = match kind {
+        ValueKind::Sides if !shorthands => ValueKind::Unit,
+        ValueKind::Flex if !shorthands => ValueKind::Number,
+        ValueKind::Border | ValueKind::Font if !shorthands => {
+            span_of(name_tokens).error(format!("`{}` is a shorthand and can only be used in `style!`", name)).emit();
+            return false;
+        }
+        kind => kind
+    };
+
+    match values::check(kind, value) {
+        Ok(()) => true,
+        Err(invalid) => {
+            let span = if value.is_empty() { span_of(name_tokens) } else { invalid.span };
+            let diagnostic = span.error(format!("invalid value for `{}`", name));
+            match invalid.help {
+                Some(help) => diagnostic.help(help).emit(),
+                None => diagnostic.emit()
+            }
+            false
+        }
+    }
+}
+
+// Hands the tokens over to the declarative macro with the given name.
+fn expand(name: &str, delimiter: Delimiter, input: TokenStream) -> TokenStream {
+    let span = Span::call_site();
+    TokenStream::from_iter(vec![
+        TokenTree { span, kind: TokenNode::Term(Term::intern(name)) },
+        TokenTree { span, kind: TokenNode::Op('!', Spacing::Alone) },
+        TokenTree { span, kind: TokenNode::Group(delimiter, input) }
+    ])
+}
+
+fn error(span: Span, message: &str) -> TokenStream {
+    span.error(message).emit();
+    invalid()
+}
+
+// Stands in for the expansion once errors have been reported, so that they
+// aren't followed by confusing errors from the declarative macros.
+fn invalid() -> TokenStream {
+    "panic!()".parse().unwrap()
+}
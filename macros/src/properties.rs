@@ -0,0 +1,141 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use rsx_stylesheet::keywords::{
+    ALIGN,
+    BORDER_STYLE,
+    CURSOR,
+    DISPLAY,
+    FLEX_DIRECTION,
+    FLEX_WRAP,
+    FONT_CAPS,
+    FONT_STRETCH,
+    FONT_STYLE,
+    JUSTIFY,
+    OVERFLOW,
+    POSITION,
+    VISIBILITY
+};
+
+// The grammar accepted by the `value!` macro for each property. Keywords come
+// from the tables the stylesheet serializes them with.
+#[derive(Debug, Copy, Clone)]
+pub enum ValueKind {
+    // One of the given keywords.
+    Keyword(&'static [&'static str]),
+    // One of the given keywords, or an expression converting into the value.
+    KeywordOrExpr(&'static [&'static str]),
+    // A length in pixels or percents, `auto` or `undefined`.
+    Unit,
+    // A single number.
+    Number,
+    Color,
+    FontSize,
+    FontWeight,
+    FontFamily,
+    BoxShadow,
+    TextShadow,
+
+    // Shorthands expanded by `style!`
+    Sides,
+    Flex,
+    Border,
+    Font
+}
+
+// Every property understood by `declaration!` and `style!`.
+pub const PROPERTIES: &[(&str, ValueKind)] = &[
+    // Layout styles
+    ("align-content", ValueKind::Keyword(ALIGN)),
+    ("align-items", ValueKind::Keyword(ALIGN)),
+    ("align-self", ValueKind::Keyword(ALIGN)),
+    ("aspect-ratio", ValueKind::Number),
+    ("border-bottom-width", ValueKind::Number),
+    ("border-end-width", ValueKind::Number),
+    ("border-left-width", ValueKind::Number),
+    ("border-right-width", ValueKind::Number),
+    ("border-start-width", ValueKind::Number),
+    ("border-top-width", ValueKind::Number),
+    ("border-width", ValueKind::Number),
+    ("bottom", ValueKind::Unit),
+    ("display", ValueKind::Keyword(DISPLAY)),
+    ("end", ValueKind::Unit),
+    ("flex", ValueKind::Flex),
+    ("flex-basis", ValueKind::Unit),
+    ("flex-direction", ValueKind::Keyword(FLEX_DIRECTION)),
+    ("flex-grow", ValueKind::Number),
+    ("flex-shrink", ValueKind::Number),
+    ("flex-wrap", ValueKind::Keyword(FLEX_WRAP)),
+    ("height", ValueKind::Unit),
+    ("justify-content", ValueKind::Keyword(JUSTIFY)),
+    ("left", ValueKind::Unit),
+    ("margin", ValueKind::Sides),
+    ("margin-bottom", ValueKind::Unit),
+    ("margin-end", ValueKind::Unit),
+    ("margin-horizontal", ValueKind::Unit),
+    ("margin-left", ValueKind::Unit),
+    ("margin-right", ValueKind::Unit),
+    ("margin-start", ValueKind::Unit),
+    ("margin-top", ValueKind::Unit),
+    ("margin-vertical", ValueKind::Unit),
+    ("max-height", ValueKind::Unit),
+    ("max-width", ValueKind::Unit),
+    ("min-height", ValueKind::Unit),
+    ("min-width", ValueKind::Unit),
+    ("overflow", ValueKind::Keyword(OVERFLOW)),
+    ("padding", ValueKind::Sides),
+    ("padding-bottom", ValueKind::Unit),
+    ("padding-end", ValueKind::Unit),
+    ("padding-horizontal", ValueKind::Unit),
+    ("padding-left", ValueKind::Unit),
+    ("padding-right", ValueKind::Unit),
+    ("padding-start", ValueKind::Unit),
+    ("padding-top", ValueKind::Unit),
+    ("padding-vertical", ValueKind::Unit),
+    ("position", ValueKind::Keyword(POSITION)),
+    ("right", ValueKind::Unit),
+    ("start", ValueKind::Unit),
+    ("top", ValueKind::Unit),
+    ("width", ValueKind::Unit),
+    // Theme styles
+    ("cursor", ValueKind::KeywordOrExpr(CURSOR)),
+    ("color", ValueKind::Color),
+    ("background-color", ValueKind::Color),
+    ("opacity", ValueKind::Number),
+    ("border-top-color", ValueKind::Color),
+    ("border-left-color", ValueKind::Color),
+    ("border-bottom-color", ValueKind::Color),
+    ("border-right-color", ValueKind::Color),
+    ("border-top-style", ValueKind::KeywordOrExpr(BORDER_STYLE)),
+    ("border-bottom-style", ValueKind::KeywordOrExpr(BORDER_STYLE)),
+    ("border-left-style", ValueKind::KeywordOrExpr(BORDER_STYLE)),
+    ("border-right-style", ValueKind::KeywordOrExpr(BORDER_STYLE)),
+    ("box-shadow", ValueKind::BoxShadow),
+    ("text-shadow", ValueKind::TextShadow),
+    ("font-family", ValueKind::FontFamily),
+    ("font-style", ValueKind::KeywordOrExpr(FONT_STYLE)),
+    ("font-caps", ValueKind::KeywordOrExpr(FONT_CAPS)),
+    ("font-weight", ValueKind::FontWeight),
+    ("font-size", ValueKind::FontSize),
+    ("font-stretch", ValueKind::KeywordOrExpr(FONT_STRETCH)),
+    ("visibility", ValueKind::KeywordOrExpr(VISIBILITY)),
+    // Shorthands
+    ("border", ValueKind::Border),
+    ("font", ValueKind::Font)
+];
+
+pub fn find(name: &str) -> Option<ValueKind> {
+    PROPERTIES.iter().find(|&&(property, _)| property == name).map(|&(_, kind)| kind)
+}
+
+pub fn names() -> Vec<&'static str> {
+    PROPERTIES.iter().map(|&(property, _)| property).collect()
+}
@@ -0,0 +1,163 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use proc_macro::{Delimiter, Span, TokenNode, TokenStream};
+
+// A simplified view over the tokens given to the macros, which is easier to
+// match on than the compiler's token trees.
+#[derive(Debug, Clone)]
+pub enum TokenKind {
+    Ident(String),
+    Literal(String),
+    Punct(char),
+    Group(Delimiter, Vec<Token>)
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span
+}
+
+impl Token {
+    pub fn is_ident(&self, name: &str) -> bool {
+        match self.kind {
+            TokenKind::Ident(ref ident) => ident == name,
+            _ => false
+        }
+    }
+
+    pub fn is_punct(&self, punct: char) -> bool {
+        match self.kind {
+            TokenKind::Punct(c) => c == punct,
+            _ => false
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        match self.kind {
+            TokenKind::Literal(ref literal) => literal.starts_with(|c: char| c.is_digit(10)),
+            _ => false
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        match self.kind {
+            TokenKind::Literal(ref literal) => literal.starts_with('"') || literal.starts_with("r\"") || literal.starts_with("r#"),
+            _ => false
+        }
+    }
+
+    // The contents of a string literal, with escapes resolved.
+    pub fn string_value(&self) -> Option<String> {
+        match self.kind {
+            TokenKind::Literal(ref literal) if literal.starts_with('r') => {
+                let hashes = literal[1..].chars().take_while(|&c| c == '#').count();
+                Some(literal[hashes + 2..literal.len() - hashes - 1].to_owned())
+            }
+            TokenKind::Literal(ref literal) if literal.starts_with('"') => Some(unescape(&literal[1..literal.len() - 1])),
+            _ => None
+        }
+    }
+
+    // Whether this token can stand for a single value, like a number or a
+    // variable holding one.
+    pub fn is_value(&self) -> bool {
+        match self.kind {
+            TokenKind::Ident(_) | TokenKind::Literal(_) => true,
+            TokenKind::Group(Delimiter::Parenthesis, _) | TokenKind::Group(Delimiter::Brace, _) => true,
+            _ => false
+        }
+    }
+}
+
+pub fn tokens(stream: TokenStream) -> Vec<Token> {
+    stream
+        .into_iter()
+        .map(|tree| {
+            let kind = match tree.kind {
+                TokenNode::Term(term) => TokenKind::Ident(term.as_str().to_owned()),
+                TokenNode::Literal(literal) => TokenKind::Literal(literal.to_string()),
+                TokenNode::Op(c, _) => TokenKind::Punct(c),
+                TokenNode::Group(delimiter, stream) => TokenKind::Group(delimiter, tokens(stream))
+            };
+            Token { kind, span: tree.span }
+        })
+        .collect()
+}
+
+// The span covering all of the given tokens.
+pub fn span_of(tokens: &[Token]) -> Span {
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => first.span.join(last.span).unwrap_or(first.span),
+        _ => Span::call_site()
+    }
+}
+
+// Reads a hyphenated name such as `justify-content` or `xx-small`, returning
+// it along with the number of tokens it spans.
+pub fn hyphenated(tokens: &[Token]) -> Option<(String, usize)> {
+    let mut name = match tokens.first() {
+        Some(&Token { kind: TokenKind::Ident(ref ident), .. }) => ident.clone(),
+        _ => return None
+    };
+    let mut len = 1;
+    while len + 1 < tokens.len() && tokens[len].is_punct('-') {
+        match tokens[len + 1].kind {
+            TokenKind::Ident(ref ident) | TokenKind::Literal(ref ident) => {
+                name.push('-');
+                name.push_str(ident);
+                len += 2;
+            }
+            _ => break
+        }
+    }
+    Some((name, len))
+}
+
+// Splits tokens on top-level commas.
+pub fn split_commas(tokens: &[Token]) -> Vec<&[Token]> {
+    tokens.split(|token| token.is_punct(',')).collect()
+}
+
+fn unescape(string: &str) -> String {
+    let mut result = String::with_capacity(string.len());
+    let mut chars = string.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('0') => result.push('\0'),
+            Some('x') => {
+                let code: String = chars.by_ref().take(2).collect();
+                result.extend(u8::from_str_radix(&code, 16).ok().map(char::from));
+            }
+            Some('u') => {
+                let code: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                result.extend(u32::from_str_radix(&code, 16).ok().and_then(::std::char::from_u32));
+            }
+            // Line continuations skip the newline and any leading whitespace.
+            Some('\n') => {
+                while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+                    chars.next();
+                }
+            }
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    result
+}
@@ -0,0 +1,327 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use proc_macro::{Delimiter, Span};
+
+use rsx_stylesheet::keywords::{BORDER_STYLE, FONT_CAPS, FONT_SIZE_KEYWORD, FONT_STRETCH, FONT_STYLE, FONT_WEIGHT, GENERIC_FONT_NAME, RELATIVE_FONT_SIZE};
+
+use properties::ValueKind;
+use suggest::closest;
+use tokens::{hyphenated, span_of, split_commas, Token, TokenKind};
+
+pub struct InvalidValue {
+    pub span: Span,
+    pub help: Option<String>
+}
+
+impl InvalidValue {
+    fn new(tokens: &[Token]) -> Self {
+        InvalidValue { span: span_of(tokens), help: None }
+    }
+
+    fn help<T>(mut self, help: T) -> Self
+    where
+        T: Into<String>
+    {
+        self.help = Some(help.into());
+        self
+    }
+}
+
+type CheckResult = Result<(), InvalidValue>;
+
+pub fn check(kind: ValueKind, tokens: &[Token]) -> CheckResult {
+    if tokens.is_empty() {
+        return Err(InvalidValue::new(tokens).help("expected a value"));
+    }
+
+    let result = match kind {
+        ValueKind::Keyword(keywords) => check_keyword(keywords, tokens, false),
+        ValueKind::KeywordOrExpr(keywords) => check_keyword(keywords, tokens, true),
+        ValueKind::Unit => check_unit(tokens),
+        ValueKind::Number => check_number(tokens),
+        ValueKind::Color => check_color(tokens),
+        ValueKind::FontSize => check_font_size(tokens),
+        ValueKind::FontWeight => check_font_weight(tokens),
+        ValueKind::FontFamily => check_font_family(tokens),
+        ValueKind::BoxShadow => check_shadows(tokens, true),
+        ValueKind::TextShadow => check_shadows(tokens, false),
+        ValueKind::Sides => check_sides(tokens),
+        ValueKind::Flex => check_flex(tokens),
+        ValueKind::Border => check_border(tokens),
+        ValueKind::Font => check_font(tokens)
+    };
+
+    // Empty list items have no tokens to point at.
+    result.map_err(|mut invalid| {
+        if invalid.span == Span::call_site() {
+            invalid.span = span_of(tokens);
+        }
+        invalid
+    })
+}
+
+fn one_of(keywords: &[&str]) -> String {
+    let keywords: Vec<_> = keywords.iter().map(|keyword| format!("`{}`", keyword)).collect();
+    format!("one of {}", keywords.join(", "))
+}
+
+fn expected_one_of(keywords: &[&str]) -> String {
+    format!("expected {}", one_of(keywords))
+}
+
+// Keywords are written as hyphenated identifiers. Anything else is passed on
+// as an expression when the property allows it, so variables need to be
+// wrapped in parentheses to be told apart from keywords.
+fn check_keyword(keywords: &[&str], tokens: &[Token], allow_expr: bool) -> CheckResult {
+    match hyphenated(tokens) {
+        Some((ref name, len)) if len == tokens.len() => {
+            if keywords.contains(&name.as_str()) {
+                Ok(())
+            } else {
+                let error = InvalidValue::new(tokens);
+                Err(match closest(name, keywords) {
+                    Some(keyword) => error.help(format!("did you mean `{}`?", keyword)),
+                    None => error.help(expected_one_of(keywords))
+                })
+            }
+        }
+        _ if allow_expr => Ok(()),
+        _ => Err(InvalidValue::new(tokens).help(expected_one_of(keywords)))
+    }
+}
+
+// Matches a length, returning the number of tokens it spans.
+fn unit_len(tokens: &[Token]) -> Option<usize> {
+    match tokens {
+        &[ref auto, ..] if auto.is_ident("auto") || auto.is_ident("undefined") => Some(1),
+        &[ref value, ref unit, ..] if value.is_value() && (unit.is_ident("px") || unit.is_punct('%')) => Some(2),
+        _ => None
+    }
+}
+
+fn check_unit(tokens: &[Token]) -> CheckResult {
+    match unit_len(tokens) {
+        Some(len) if len == tokens.len() => Ok(()),
+        _ => Err(InvalidValue::new(tokens).help("expected a length like `10 px` or `50 %`, `auto` or `undefined`"))
+    }
+}
+
+fn check_number(tokens: &[Token]) -> CheckResult {
+    match tokens {
+        &[ref value] if value.is_value() && !value.is_string() => Ok(()),
+        _ => Err(InvalidValue::new(tokens).help("expected a single number"))
+    }
+}
+
+// Matches a color, returning the number of tokens it spans.
+fn color_len(tokens: &[Token]) -> Option<usize> {
+    match tokens {
+        &[Token { kind: TokenKind::Ident(ref name), .. }, Token { kind: TokenKind::Group(Delimiter::Parenthesis, _), .. }, ..]
+            if name == "rgb" || name == "rgba" =>
+        {
+            Some(2)
+        }
+        &[ref value, ..] if value.is_value() && !value.is_number() && !value.is_string() => Some(1),
+        _ => None
+    }
+}
+
+fn check_color(tokens: &[Token]) -> CheckResult {
+    match color_len(tokens) {
+        Some(len) if len == tokens.len() => Ok(()),
+        // Any other expression converting into a color.
+        _ if !tokens[0].is_number() && !tokens[0].is_string() => Ok(()),
+        _ => Err(InvalidValue::new(tokens).help("expected a color like `rgb(255, 0, 0)`, `currentColor` or a named color"))
+    }
+}
+
+fn is_font_size_keyword(name: &str) -> bool {
+    FONT_SIZE_KEYWORD.contains(&name) || RELATIVE_FONT_SIZE.contains(&name)
+}
+
+fn font_size_len(tokens: &[Token]) -> Option<usize> {
+    match hyphenated(tokens) {
+        Some((ref name, len)) if is_font_size_keyword(name) => Some(len),
+        _ if tokens[0].is_ident("auto") || tokens[0].is_ident("undefined") => None,
+        _ => unit_len(tokens)
+    }
+}
+
+fn check_font_size(tokens: &[Token]) -> CheckResult {
+    match font_size_len(tokens) {
+        Some(len) if len == tokens.len() => Ok(()),
+        _ => {
+            let keywords: Vec<_> = FONT_SIZE_KEYWORD.iter().chain(RELATIVE_FONT_SIZE).cloned().collect();
+            Err(InvalidValue::new(tokens).help(format!("expected a length like `12 px`, or {}", one_of(&keywords))))
+        }
+    }
+}
+
+fn check_font_weight(tokens: &[Token]) -> CheckResult {
+    match tokens {
+        &[ref weight] if weight.is_number() => Ok(()),
+        _ => check_keyword(FONT_WEIGHT, tokens, true)
+    }
+}
+
+fn check_font_name(tokens: &[Token]) -> CheckResult {
+    let is_unquoted_name = tokens.iter().all(|token| match token.kind {
+        TokenKind::Ident(_) => true,
+        _ => false
+    });
+
+    match hyphenated(tokens) {
+        _ if tokens.is_empty() => Err(InvalidValue::new(tokens).help("expected a font name")),
+        _ if is_unquoted_name => Ok(()),
+        _ if tokens.len() == 1 && tokens[0].is_string() => Ok(()),
+        Some((ref name, len)) if len == tokens.len() && GENERIC_FONT_NAME.contains(&name.as_str()) => Ok(()),
+        _ => Err(InvalidValue::new(tokens).help(format!("expected a quoted font name, or {}", one_of(GENERIC_FONT_NAME))))
+    }
+}
+
+fn check_font_family(tokens: &[Token]) -> CheckResult {
+    split_commas(tokens).into_iter().map(check_font_name).collect()
+}
+
+fn check_shadow(tokens: &[Token], is_box_shadow: bool) -> CheckResult {
+    let mut rest = tokens;
+    if is_box_shadow && rest.first().map(|token| token.is_ident("inset")).unwrap_or(false) {
+        rest = &rest[1..];
+    }
+
+    // The color can either come first or last.
+    let mut color = false;
+    if let Some(len) = color_len(rest) {
+        if !rest.get(1).map(|unit| unit.is_ident("px")).unwrap_or(false) {
+            rest = &rest[len..];
+            color = true;
+        }
+    }
+
+    let mut lengths = 0;
+    while rest.len() >= 2 && rest[0].is_value() && rest[1].is_ident("px") {
+        rest = &rest[2..];
+        lengths += 1;
+    }
+
+    if !color && color_len(rest) == Some(rest.len()) {
+        rest = &[];
+    }
+
+    let max_lengths = if is_box_shadow { 4 } else { 3 };
+    if rest.is_empty() && lengths >= 2 && lengths <= max_lengths {
+        Ok(())
+    } else if is_box_shadow {
+        Err(InvalidValue::new(tokens).help("expected an optional `inset`, two to four lengths in pixels and an optional color"))
+    } else {
+        Err(InvalidValue::new(tokens).help("expected two or three lengths in pixels and an optional color"))
+    }
+}
+
+fn check_shadows(tokens: &[Token], is_box_shadow: bool) -> CheckResult {
+    if tokens.len() == 1 && tokens[0].is_ident("none") {
+        return Ok(());
+    }
+    split_commas(tokens).into_iter().map(|shadow| check_shadow(shadow, is_box_shadow)).collect()
+}
+
+fn check_sides(tokens: &[Token]) -> CheckResult {
+    let mut rest = tokens;
+    let mut sides = 0;
+    while let Some(len) = unit_len(rest) {
+        rest = &rest[len..];
+        sides += 1;
+    }
+
+    if rest.is_empty() && sides >= 1 && sides <= 4 {
+        Ok(())
+    } else {
+        Err(InvalidValue::new(tokens).help("expected one to four lengths like `10 px` or `50 %`, or `auto`"))
+    }
+}
+
+fn check_flex(tokens: &[Token]) -> CheckResult {
+    if tokens.len() == 1 && (tokens[0].is_ident("none") || tokens[0].is_ident("auto")) {
+        return Ok(());
+    }
+
+    // Up to two factors, followed by an optional basis.
+    let mut rest = tokens;
+    let mut factors = 0;
+    while factors < 2 && !rest.is_empty() && rest[0].is_value() && !rest[0].is_ident("auto") && unit_len(rest) != Some(2) {
+        rest = &rest[1..];
+        factors += 1;
+    }
+
+    match unit_len(rest) {
+        _ if rest.is_empty() => Ok(()),
+        Some(len) if len == rest.len() && !rest[0].is_ident("undefined") => Ok(()),
+        _ => Err(InvalidValue::new(tokens).help("expected `none`, `auto`, or a grow factor, shrink factor and basis"))
+    }
+}
+
+fn check_border(tokens: &[Token]) -> CheckResult {
+    let error = || InvalidValue::new(tokens).help("expected a width in pixels, a border style and a color, in this order");
+
+    let mut rest = tokens;
+    if let &[ref value, ref unit, ..] = rest {
+        if value.is_value() && unit.is_ident("px") {
+            rest = &rest[2..];
+        }
+    }
+    if rest.is_empty() {
+        return Ok(());
+    }
+
+    match rest[0].kind {
+        TokenKind::Ident(ref style) if BORDER_STYLE.contains(&style.as_str()) => {}
+        TokenKind::Ident(ref style) => {
+            return Err(InvalidValue::new(&rest[..1]).help(match closest(style, BORDER_STYLE) {
+                Some(keyword) => format!("did you mean `{}`?", keyword),
+                None => expected_one_of(BORDER_STYLE)
+            }))
+        }
+        _ => return Err(error())
+    }
+
+    match &rest[1..] {
+        color if color.is_empty() => Ok(()),
+        color => check_color(color)
+    }
+}
+
+fn check_font(tokens: &[Token]) -> CheckResult {
+    let error = || InvalidValue::new(tokens).help("expected optional style, caps, weight and stretch keywords, a size and a family");
+
+    let mut rest = tokens;
+    loop {
+        match hyphenated(rest) {
+            Some((ref name, len)) if name == "normal"
+                || FONT_STYLE.contains(&name.as_str())
+                || FONT_CAPS.contains(&name.as_str())
+                || FONT_WEIGHT.contains(&name.as_str())
+                || FONT_STRETCH.contains(&name.as_str()) =>
+            {
+                rest = &rest[len..]
+            }
+            _ => match rest {
+                &[ref weight, ref next, ..] if weight.is_number() && !next.is_ident("px") && !next.is_punct('%') => rest = &rest[1..],
+                _ => break
+            }
+        }
+    }
+
+    match font_size_len(rest) {
+        Some(len) if len < rest.len() => check_font_family(&rest[len..]),
+        _ => Err(error())
+    }
+}
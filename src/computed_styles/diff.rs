@@ -0,0 +1,221 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use std::f32;
+
+use computed_styles::types::ComputedStyles;
+use styles::types::{Content, PropertyId};
+
+// The work needed to render an element again after its styles changed,
+// ordered from the cheapest to the most expensive. Each level implies the
+// ones before it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub enum StyleDamage {
+    None,
+    Repaint,
+    Restack,
+    Relayout
+}
+
+impl Default for StyleDamage {
+    fn default() -> Self {
+        StyleDamage::None
+    }
+}
+
+impl StyleDamage {
+    pub fn of(id: PropertyId) -> StyleDamage {
+        match id {
+            // Doesn't change how the element looks.
+            PropertyId::Cursor => StyleDamage::None,
+
+            // Changes the compositing of the element and its descendants.
+            PropertyId::Opacity => StyleDamage::Restack,
+
+            // Changes the metrics of the text.
+            PropertyId::FontFamily
+            | PropertyId::FontStyle
+            | PropertyId::FontCaps
+            | PropertyId::FontWeight
+            | PropertyId::FontSize
+            | PropertyId::FontStretch => StyleDamage::Relayout,
+
+            PropertyId::Color
+            | PropertyId::BackgroundColor
+            | PropertyId::BorderTopColor
+            | PropertyId::BorderLeftColor
+            | PropertyId::BorderBottomColor
+            | PropertyId::BorderRightColor
+            | PropertyId::BorderTopStyle
+            | PropertyId::BorderLeftStyle
+            | PropertyId::BorderBottomStyle
+            | PropertyId::BorderRightStyle
+            | PropertyId::BoxShadow
+            | PropertyId::TextShadow
+            | PropertyId::Visibility => StyleDamage::Repaint,
+
+            // Changes the text generated for `::before` and `::after`, here
+            // or in following elements, which has to be measured again.
+            PropertyId::Content | PropertyId::Quotes | PropertyId::CounterIncrement | PropertyId::CounterReset => StyleDamage::Relayout,
+
+            // Layout styles, handed over to the layout engine.
+            PropertyId::AlignContent
+            | PropertyId::AlignItems
+            | PropertyId::AlignSelf
+            | PropertyId::BorderTopWidth
+            | PropertyId::BorderLeftWidth
+            | PropertyId::BorderBottomWidth
+            | PropertyId::BorderRightWidth
+            | PropertyId::Top
+            | PropertyId::Left
+            | PropertyId::Bottom
+            | PropertyId::Right
+            | PropertyId::Display
+            | PropertyId::FlexBasis
+            | PropertyId::FlexDirection
+            | PropertyId::FlexGrow
+            | PropertyId::FlexShrink
+            | PropertyId::FlexWrap
+            | PropertyId::Height
+            | PropertyId::Width
+            | PropertyId::MaxHeight
+            | PropertyId::MaxWidth
+            | PropertyId::MinHeight
+            | PropertyId::MinWidth
+            | PropertyId::Overflow
+            | PropertyId::JustifyContent
+            | PropertyId::MarginTop
+            | PropertyId::MarginLeft
+            | PropertyId::MarginBottom
+            | PropertyId::MarginRight
+            | PropertyId::PaddingTop
+            | PropertyId::PaddingLeft
+            | PropertyId::PaddingBottom
+            | PropertyId::PaddingRight
+            | PropertyId::Position => StyleDamage::Relayout
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StyleChange {
+    pub damage: StyleDamage,
+    pub properties: Vec<PropertyId>
+}
+
+impl StyleChange {
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    pub fn contains(&self, id: PropertyId) -> bool {
+        self.properties.contains(&id)
+    }
+
+    fn push(&mut self, id: PropertyId) {
+        self.damage = self.damage.max(StyleDamage::of(id));
+        self.properties.push(id);
+    }
+}
+
+impl ComputedStyles {
+    // Lists the properties whose computed values differ between both styles,
+    // and the work needed to render the element again with `other`.
+    pub fn diff(&self, other: &ComputedStyles) -> StyleChange {
+        let mut change = StyleChange::default();
+        let (a, b) = (self, other);
+        let (ia, ib) = (&self.inherited, &other.inherited);
+        let (la, lb) = (&self.layout, &other.layout);
+
+        // Generated text also depends on the counters and quotes in scope.
+        let generated_text_changed = match a.content {
+            Content::Items(_) => a.counters != b.counters || a.quote_depth != b.quote_depth,
+            Content::Normal | Content::None => false
+        };
+
+        {
+            let mut check = |id: PropertyId, changed: bool| {
+                if changed {
+                    change.push(id);
+                }
+            };
+
+            // Theme styles
+            check(PropertyId::Cursor, ia.cursor != ib.cursor);
+            check(PropertyId::Color, ia.color != ib.color);
+            check(PropertyId::BackgroundColor, a.background_color != b.background_color);
+            check(PropertyId::Opacity, differs(a.effective_opacity(), b.effective_opacity()));
+            check(PropertyId::BorderTopColor, a.border_top_color != b.border_top_color);
+            check(PropertyId::BorderLeftColor, a.border_left_color != b.border_left_color);
+            check(PropertyId::BorderBottomColor, a.border_bottom_color != b.border_bottom_color);
+            check(PropertyId::BorderRightColor, a.border_right_color != b.border_right_color);
+            check(PropertyId::BorderTopStyle, a.border_top_style != b.border_top_style);
+            check(PropertyId::BorderLeftStyle, a.border_left_style != b.border_left_style);
+            check(PropertyId::BorderBottomStyle, a.border_bottom_style != b.border_bottom_style);
+            check(PropertyId::BorderRightStyle, a.border_right_style != b.border_right_style);
+            check(PropertyId::BoxShadow, a.box_shadows != b.box_shadows);
+            check(PropertyId::TextShadow, ia.text_shadows != ib.text_shadows);
+            check(PropertyId::FontFamily, ia.font_names != ib.font_names);
+            check(PropertyId::FontStyle, ia.font_style != ib.font_style);
+            check(PropertyId::FontCaps, ia.font_caps != ib.font_caps);
+            check(PropertyId::FontWeight, ia.font_weight != ib.font_weight);
+            check(PropertyId::FontSize, ia.font_size != ib.font_size);
+            check(PropertyId::FontStretch, ia.font_stretch != ib.font_stretch);
+            check(PropertyId::Visibility, ia.visibility != ib.visibility);
+            check(PropertyId::Quotes, ia.quotes != ib.quotes);
+            check(PropertyId::CounterIncrement, a.counter_increments != b.counter_increments);
+            check(PropertyId::CounterReset, a.counter_resets != b.counter_resets);
+            check(PropertyId::Content, a.content != b.content || generated_text_changed);
+
+            // Layout styles
+            check(PropertyId::BorderTopWidth, a.border_top_width != b.border_top_width);
+            check(PropertyId::BorderLeftWidth, a.border_left_width != b.border_left_width);
+            check(PropertyId::BorderBottomWidth, a.border_bottom_width != b.border_bottom_width);
+            check(PropertyId::BorderRightWidth, a.border_right_width != b.border_right_width);
+            check(PropertyId::Display, la.display != lb.display);
+            check(PropertyId::Position, la.position != lb.position);
+            check(PropertyId::Overflow, la.overflow != lb.overflow);
+            check(PropertyId::Width, la.width != lb.width);
+            check(PropertyId::Height, la.height != lb.height);
+            check(PropertyId::MinWidth, la.min_width != lb.min_width);
+            check(PropertyId::MinHeight, la.min_height != lb.min_height);
+            check(PropertyId::MaxWidth, la.max_width != lb.max_width);
+            check(PropertyId::MaxHeight, la.max_height != lb.max_height);
+            check(PropertyId::Top, la.top != lb.top);
+            check(PropertyId::Left, la.left != lb.left);
+            check(PropertyId::Bottom, la.bottom != lb.bottom);
+            check(PropertyId::Right, la.right != lb.right);
+            check(PropertyId::MarginTop, la.margin_top != lb.margin_top);
+            check(PropertyId::MarginLeft, la.margin_left != lb.margin_left);
+            check(PropertyId::MarginBottom, la.margin_bottom != lb.margin_bottom);
+            check(PropertyId::MarginRight, la.margin_right != lb.margin_right);
+            check(PropertyId::PaddingTop, la.padding_top != lb.padding_top);
+            check(PropertyId::PaddingLeft, la.padding_left != lb.padding_left);
+            check(PropertyId::PaddingBottom, la.padding_bottom != lb.padding_bottom);
+            check(PropertyId::PaddingRight, la.padding_right != lb.padding_right);
+            check(PropertyId::FlexDirection, la.flex_direction != lb.flex_direction);
+            check(PropertyId::FlexWrap, la.flex_wrap != lb.flex_wrap);
+            check(PropertyId::FlexGrow, differs(la.flex_grow, lb.flex_grow));
+            check(PropertyId::FlexShrink, differs(la.flex_shrink, lb.flex_shrink));
+            check(PropertyId::FlexBasis, la.flex_basis != lb.flex_basis);
+            check(PropertyId::JustifyContent, la.justify_content != lb.justify_content);
+            check(PropertyId::AlignItems, la.align_items != lb.align_items);
+            check(PropertyId::AlignSelf, la.align_self != lb.align_self);
+            check(PropertyId::AlignContent, la.align_content != lb.align_content);
+        }
+
+        change
+    }
+}
+
+fn differs(a: f32, b: f32) -> bool {
+    (a - b).abs() > f32::EPSILON
+}
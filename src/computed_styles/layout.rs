@@ -0,0 +1,264 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use styles::types::{Align, Display, FlexDirection, FlexStyle, Justify, Overflow, PositionType, PropertyId, StyleUnit, Wrap};
+
+// The layout styles an element was given, as the layout engine sees them.
+// Logical `start` and `end` sides are resolved as left and right, since
+// only left-to-right layouts are supported.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ComputedLayoutStyles {
+    // Box
+    pub display: Display,
+    pub position: PositionType,
+    pub overflow: Overflow,
+
+    // Dimensions
+    pub width: StyleUnit,
+    pub height: StyleUnit,
+    pub min_width: StyleUnit,
+    pub min_height: StyleUnit,
+    pub max_width: StyleUnit,
+    pub max_height: StyleUnit,
+
+    // Offsets
+    pub top: StyleUnit,
+    pub right: StyleUnit,
+    pub bottom: StyleUnit,
+    pub left: StyleUnit,
+
+    // Margins
+    pub margin_top: StyleUnit,
+    pub margin_right: StyleUnit,
+    pub margin_bottom: StyleUnit,
+    pub margin_left: StyleUnit,
+
+    // Paddings
+    pub padding_top: StyleUnit,
+    pub padding_right: StyleUnit,
+    pub padding_bottom: StyleUnit,
+    pub padding_left: StyleUnit,
+
+    // Flexbox
+    pub flex_direction: FlexDirection,
+    pub flex_wrap: Wrap,
+    pub flex_grow: f32,
+    pub flex_shrink: f32,
+    pub flex_basis: StyleUnit,
+    pub justify_content: Justify,
+    pub align_items: Align,
+    pub align_self: Align,
+    pub align_content: Align
+}
+
+// Matches the Yoga defaults.
+impl Default for ComputedLayoutStyles {
+    fn default() -> Self {
+        ComputedLayoutStyles {
+            display: Display::Flex,
+            position: PositionType::Relative,
+            overflow: Overflow::Visible,
+
+            width: StyleUnit::Auto,
+            height: StyleUnit::Auto,
+            min_width: StyleUnit::UndefinedValue,
+            min_height: StyleUnit::UndefinedValue,
+            max_width: StyleUnit::UndefinedValue,
+            max_height: StyleUnit::UndefinedValue,
+
+            top: StyleUnit::UndefinedValue,
+            right: StyleUnit::UndefinedValue,
+            bottom: StyleUnit::UndefinedValue,
+            left: StyleUnit::UndefinedValue,
+
+            margin_top: StyleUnit::UndefinedValue,
+            margin_right: StyleUnit::UndefinedValue,
+            margin_bottom: StyleUnit::UndefinedValue,
+            margin_left: StyleUnit::UndefinedValue,
+
+            padding_top: StyleUnit::UndefinedValue,
+            padding_right: StyleUnit::UndefinedValue,
+            padding_bottom: StyleUnit::UndefinedValue,
+            padding_left: StyleUnit::UndefinedValue,
+
+            flex_direction: FlexDirection::Column,
+            flex_wrap: Wrap::NoWrap,
+            flex_grow: 0.0,
+            flex_shrink: 0.0,
+            flex_basis: StyleUnit::Auto,
+            justify_content: Justify::FlexStart,
+            align_items: Align::Stretch,
+            align_self: Align::Auto,
+            align_content: Align::FlexStart
+        }
+    }
+}
+
+impl ComputedLayoutStyles {
+    pub fn apply_style(&mut self, style: &FlexStyle) {
+        use self::FlexStyle::*;
+
+        match style {
+            // Box
+            &Display(display) => self.display = display,
+            &Position(position) => self.position = position,
+            &Overflow(overflow) => self.overflow = overflow,
+
+            // Dimensions
+            &Width(width) => self.width = width,
+            &Height(height) => self.height = height,
+            &MinWidth(min_width) => self.min_width = min_width,
+            &MinHeight(min_height) => self.min_height = min_height,
+            &MaxWidth(max_width) => self.max_width = max_width,
+            &MaxHeight(max_height) => self.max_height = max_height,
+
+            // Offsets
+            &Top(top) => self.top = top,
+            &Right(right) | &End(right) => self.right = right,
+            &Bottom(bottom) => self.bottom = bottom,
+            &Left(left) | &Start(left) => self.left = left,
+
+            // Margins
+            &Margin(margin) => {
+                self.margin_top = margin;
+                self.margin_right = margin;
+                self.margin_bottom = margin;
+                self.margin_left = margin;
+            }
+            &MarginVertical(margin) => {
+                self.margin_top = margin;
+                self.margin_bottom = margin;
+            }
+            &MarginHorizontal(margin) => {
+                self.margin_right = margin;
+                self.margin_left = margin;
+            }
+            &MarginTop(margin_top) => self.margin_top = margin_top,
+            &MarginRight(margin_right) | &MarginEnd(margin_right) => self.margin_right = margin_right,
+            &MarginBottom(margin_bottom) => self.margin_bottom = margin_bottom,
+            &MarginLeft(margin_left) | &MarginStart(margin_left) => self.margin_left = margin_left,
+
+            // Paddings
+            &Padding(padding) => {
+                self.padding_top = padding;
+                self.padding_right = padding;
+                self.padding_bottom = padding;
+                self.padding_left = padding;
+            }
+            &PaddingVertical(padding) => {
+                self.padding_top = padding;
+                self.padding_bottom = padding;
+            }
+            &PaddingHorizontal(padding) => {
+                self.padding_right = padding;
+                self.padding_left = padding;
+            }
+            &PaddingTop(padding_top) => self.padding_top = padding_top,
+            &PaddingRight(padding_right) | &PaddingEnd(padding_right) => self.padding_right = padding_right,
+            &PaddingBottom(padding_bottom) => self.padding_bottom = padding_bottom,
+            &PaddingLeft(padding_left) | &PaddingStart(padding_left) => self.padding_left = padding_left,
+
+            // Flexbox
+            &FlexDirection(flex_direction) => self.flex_direction = flex_direction,
+            &FlexWrap(flex_wrap) => self.flex_wrap = flex_wrap,
+            &FlexGrow(flex_grow) => self.flex_grow = flex_grow.into_inner(),
+            &FlexShrink(flex_shrink) => self.flex_shrink = flex_shrink.into_inner(),
+            &FlexBasis(flex_basis) => self.flex_basis = flex_basis,
+            &Flex(flex) => {
+                // Yoga's `flex` is a single number rather than the CSS shorthand.
+                let flex = flex.into_inner();
+                self.flex_grow = flex.max(0.0);
+                self.flex_shrink = (-flex).max(0.0);
+            }
+            &JustifyContent(justify_content) => self.justify_content = justify_content,
+            &AlignItems(align_items) => self.align_items = align_items,
+            &AlignSelf(align_self) => self.align_self = align_self,
+            &AlignContent(align_content) => self.align_content = align_content,
+
+            // Border widths are part of the theme styles.
+            _ => {}
+        }
+    }
+
+    pub fn copy_property(&mut self, id: PropertyId, other: &ComputedLayoutStyles) {
+        match id {
+            PropertyId::Display => self.display = other.display,
+            PropertyId::Position => self.position = other.position,
+            PropertyId::Overflow => self.overflow = other.overflow,
+            PropertyId::Width => self.width = other.width,
+            PropertyId::Height => self.height = other.height,
+            PropertyId::MinWidth => self.min_width = other.min_width,
+            PropertyId::MinHeight => self.min_height = other.min_height,
+            PropertyId::MaxWidth => self.max_width = other.max_width,
+            PropertyId::MaxHeight => self.max_height = other.max_height,
+            PropertyId::Top => self.top = other.top,
+            PropertyId::Right => self.right = other.right,
+            PropertyId::Bottom => self.bottom = other.bottom,
+            PropertyId::Left => self.left = other.left,
+            PropertyId::MarginTop => self.margin_top = other.margin_top,
+            PropertyId::MarginRight => self.margin_right = other.margin_right,
+            PropertyId::MarginBottom => self.margin_bottom = other.margin_bottom,
+            PropertyId::MarginLeft => self.margin_left = other.margin_left,
+            PropertyId::PaddingTop => self.padding_top = other.padding_top,
+            PropertyId::PaddingRight => self.padding_right = other.padding_right,
+            PropertyId::PaddingBottom => self.padding_bottom = other.padding_bottom,
+            PropertyId::PaddingLeft => self.padding_left = other.padding_left,
+            PropertyId::FlexDirection => self.flex_direction = other.flex_direction,
+            PropertyId::FlexWrap => self.flex_wrap = other.flex_wrap,
+            PropertyId::FlexGrow => self.flex_grow = other.flex_grow,
+            PropertyId::FlexShrink => self.flex_shrink = other.flex_shrink,
+            PropertyId::FlexBasis => self.flex_basis = other.flex_basis,
+            PropertyId::JustifyContent => self.justify_content = other.justify_content,
+            PropertyId::AlignItems => self.align_items = other.align_items,
+            PropertyId::AlignSelf => self.align_self = other.align_self,
+            PropertyId::AlignContent => self.align_content = other.align_content,
+            _ => {}
+        }
+    }
+
+    // The computed value of a property, as the layout engine expects it, so
+    // that CSS-wide keywords resolved by `copy_property` reach it too.
+    pub fn to_flex_style(&self, id: PropertyId) -> Option<FlexStyle> {
+        match id {
+            PropertyId::Display => Some(FlexStyle::Display(self.display)),
+            PropertyId::Position => Some(FlexStyle::Position(self.position)),
+            PropertyId::Overflow => Some(FlexStyle::Overflow(self.overflow)),
+            PropertyId::Width => Some(FlexStyle::Width(self.width)),
+            PropertyId::Height => Some(FlexStyle::Height(self.height)),
+            PropertyId::MinWidth => Some(FlexStyle::MinWidth(self.min_width)),
+            PropertyId::MinHeight => Some(FlexStyle::MinHeight(self.min_height)),
+            PropertyId::MaxWidth => Some(FlexStyle::MaxWidth(self.max_width)),
+            PropertyId::MaxHeight => Some(FlexStyle::MaxHeight(self.max_height)),
+            PropertyId::Top => Some(FlexStyle::Top(self.top)),
+            PropertyId::Right => Some(FlexStyle::Right(self.right)),
+            PropertyId::Bottom => Some(FlexStyle::Bottom(self.bottom)),
+            PropertyId::Left => Some(FlexStyle::Left(self.left)),
+            PropertyId::MarginTop => Some(FlexStyle::MarginTop(self.margin_top)),
+            PropertyId::MarginRight => Some(FlexStyle::MarginRight(self.margin_right)),
+            PropertyId::MarginBottom => Some(FlexStyle::MarginBottom(self.margin_bottom)),
+            PropertyId::MarginLeft => Some(FlexStyle::MarginLeft(self.margin_left)),
+            PropertyId::PaddingTop => Some(FlexStyle::PaddingTop(self.padding_top)),
+            PropertyId::PaddingRight => Some(FlexStyle::PaddingRight(self.padding_right)),
+            PropertyId::PaddingBottom => Some(FlexStyle::PaddingBottom(self.padding_bottom)),
+            PropertyId::PaddingLeft => Some(FlexStyle::PaddingLeft(self.padding_left)),
+            PropertyId::FlexDirection => Some(FlexStyle::FlexDirection(self.flex_direction)),
+            PropertyId::FlexWrap => Some(FlexStyle::FlexWrap(self.flex_wrap)),
+            PropertyId::FlexGrow => Some(FlexStyle::FlexGrow(self.flex_grow.into())),
+            PropertyId::FlexShrink => Some(FlexStyle::FlexShrink(self.flex_shrink.into())),
+            PropertyId::FlexBasis => Some(FlexStyle::FlexBasis(self.flex_basis)),
+            PropertyId::JustifyContent => Some(FlexStyle::JustifyContent(self.justify_content)),
+            PropertyId::AlignItems => Some(FlexStyle::AlignItems(self.align_items)),
+            PropertyId::AlignSelf => Some(FlexStyle::AlignSelf(self.align_self)),
+            PropertyId::AlignContent => Some(FlexStyle::AlignContent(self.align_content)),
+            _ => None
+        }
+    }
+}
@@ -9,4 +9,7 @@ CONDITIONS OF ANY KIND, either express or implied. See the License for the
 specific language governing permissions and limitations under the License.
 */
 
+mod diff;
+mod layout;
+mod sharing;
 pub mod types;
@@ -0,0 +1,159 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use rsx_shared::types::KnownElementName;
+
+use computed_styles::types::ComputedStyles;
+use styles::types::{FlexStyle, InlineCounters, StyleDeclarations};
+
+// Servo uses the same number of entries for its sharing cache.
+const DEFAULT_CAPACITY: usize = 31;
+
+struct CacheEntry {
+    // Keeping the parent alive makes sure its address isn't reused by other
+    // styles while the entry exists.
+    parent: Option<Arc<ComputedStyles>>,
+    rule_ids: Vec<usize>,
+    // The counters and quote depth the element started from, which continue
+    // from the elements before it in document order.
+    counters: InlineCounters,
+    quote_depth: u32,
+    styles: Arc<ComputedStyles>,
+    layout_styles: Vec<FlexStyle>
+}
+
+impl CacheEntry {
+    fn matches(
+        &self,
+        parent: Option<&Arc<ComputedStyles>>,
+        rules: &[(usize, &StyleDeclarations)],
+        counters: &InlineCounters,
+        quote_depth: u32
+    ) -> bool {
+        let same_parent = match (self.parent.as_ref(), parent) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false
+        };
+        same_parent && self.quote_depth == quote_depth && self.counters == *counters && self.rule_ids.len() == rules.len()
+            && self.rule_ids.iter().zip(rules).all(|(&a, &(b, _))| a == b)
+    }
+}
+
+// Lets elements with the same parent and the same matched rules, typically
+// siblings, share a single computed styles allocation instead of running the
+// cascade again. Like in Servo, rules are compared by identity, using the ids
+// from `Stylesheet::rule_id`, and parents by address. Recently used entries
+// are kept, up to the capacity of the cache.
+pub struct StyleSharingCache {
+    capacity: usize,
+    entries: VecDeque<CacheEntry>
+}
+
+impl Default for StyleSharingCache {
+    fn default() -> Self {
+        StyleSharingCache::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl StyleSharingCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        StyleSharingCache {
+            capacity,
+            entries: VecDeque::with_capacity(capacity)
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear()
+    }
+
+    // Same as `ComputedStyles::compute`, given the matched rules as their ids
+    // and declarations, but returns the styles of a previous element when both
+    // have the same parent styles allocation and matched the same rules. The
+    // tag isn't part of the key: initial values are the same for every element
+    // (see `ComputedStyles::initial_values`), and the user-agent styles for a
+    // tag are part of the rules.
+    pub fn compute<T>(
+        &mut self,
+        parent: Option<&Arc<ComputedStyles>>,
+        rules: &[(usize, &StyleDeclarations)],
+        tag: T
+    ) -> (Arc<ComputedStyles>, Vec<FlexStyle>)
+    where
+        T: TryInto<KnownElementName>
+    {
+        self.compute_following(parent, None, None, rules, tag)
+    }
+
+    // Same as `ComputedStyles::compute_following`. Elements only share their
+    // styles when they also start from the same counters and quote depth, so
+    // that siblings numbered by a counter each get their own number.
+    pub fn compute_following<T>(
+        &mut self,
+        parent: Option<&Arc<ComputedStyles>>,
+        previous: Option<&ComputedStyles>,
+        last_descendant: Option<&ComputedStyles>,
+        rules: &[(usize, &StyleDeclarations)],
+        tag: T
+    ) -> (Arc<ComputedStyles>, Vec<FlexStyle>)
+    where
+        T: TryInto<KnownElementName>
+    {
+        let (counters, quote_depth) = ComputedStyles::starting_state(parent, previous, last_descendant);
+
+        if let Some(index) = self.entries
+            .iter()
+            .position(|entry| entry.matches(parent, rules, &counters, quote_depth))
+        {
+            let entry = self.entries.remove(index).unwrap();
+            let result = (Arc::clone(&entry.styles), entry.layout_styles.clone());
+            self.entries.push_front(entry);
+            return result;
+        }
+
+        let declarations: Vec<_> = rules.iter().map(|&(_, declarations)| declarations).collect();
+        let (styles, layout_styles) = ComputedStyles::compute_following(parent, previous, last_descendant, &declarations, tag);
+        let styles = Arc::new(styles);
+
+        if self.capacity > 0 {
+            if self.entries.len() == self.capacity {
+                self.entries.pop_back();
+            }
+            self.entries.push_front(CacheEntry {
+                parent: parent.cloned(),
+                rule_ids: rules.iter().map(|&(id, _)| id).collect(),
+                counters,
+                quote_depth,
+                styles: Arc::clone(&styles),
+                layout_styles: layout_styles.clone()
+            });
+        }
+
+        (styles, layout_styles)
+    }
+}
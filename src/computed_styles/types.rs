@@ -10,6 +10,8 @@ specific language governing permissions and limitations under the License.
 */
 
 use std::convert::TryInto;
+use std::iter::FromIterator;
+use std::sync::Arc;
 
 use rsx_shared::traits::{TComputedStyles, TInheritedStyles};
 use rsx_shared::types::KnownElementName;
@@ -18,36 +20,88 @@ use styles::types::{
     BorderStyle,
     BoxShadow,
     Color,
+    ColorValue,
+    Content,
+    ContentItem,
+    Counter,
+    CssWideKeyword,
     Cursor,
     FlexStyle,
     FontCaps,
     FontFamily,
     FontName,
     FontSize,
+    FontSizeTable,
     FontStretch,
     FontStyle,
     FontWeight,
     InlineBoxShadows,
+    InlineCounters,
+    InlineCustomProperties,
     InlineFontNames,
     InlineTextShadows,
+    PropertyId,
+    Quotes,
     StyleDeclaration,
     StyleDeclarations,
     TextShadow,
     ThemeStyle,
+    UnparsedDeclaration,
+    UnparsedValue,
     Visibility
 };
+use styles::util::{is_color_declaration, is_custom_property};
+use styles::variables::{find_custom_property, resolve_custom_properties};
+#[cfg(feature = "css-parse")]
+use styles::variables::{parse_substituted, substitute_variables};
 
+pub use computed_styles::diff::{StyleChange, StyleDamage};
+pub use computed_styles::layout::ComputedLayoutStyles;
+pub use computed_styles::sharing::StyleSharingCache;
+
+// Curly double quotes, then curly single quotes for nested ones.
+const AUTO_QUOTES: &[(&str, &str)] = &[("\u{201c}", "\u{201d}"), ("\u{2018}", "\u{2019}")];
+
+// The inherited part of the computed styles. Most elements don't set any
+// inherited property, so this is kept behind an `Arc` and only copied when
+// an element overrides one of them.
 #[derive(Debug, PartialEq, Clone, Default)]
+pub struct ComputedInheritedStyles {
+    // Inherited theme styles
+    // https://www.w3.org/TR/CSS21/propidx.html
+    // https://developer.mozilla.org/en-US/docs/Web/CSS/Reference
+    pub cursor: Cursor,
+    pub color: Color,
+    pub text_shadows: InlineTextShadows,
+    pub font_names: InlineFontNames,
+    pub font_style: FontStyle,
+    pub font_caps: FontCaps,
+    pub font_weight: FontWeight,
+    pub font_size: FontSize,
+    pub font_stretch: FontStretch,
+    pub visibility: Visibility,
+    pub quotes: Quotes,
+
+    // Sizes for the absolute-size font keywords, inherited so that it only
+    // needs to be configured on the root element
+    pub font_size_table: FontSizeTable,
+
+    // Inherited custom properties
+    pub custom_properties: InlineCustomProperties
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct ComputedStyles {
     // Non-inherited layout styles
     pub border_bottom_width: u32,
     pub border_left_width: u32,
     pub border_right_width: u32,
     pub border_top_width: u32,
+    pub layout: ComputedLayoutStyles,
 
     // Non-inherited theme styles
     pub background_color: Color,
-    pub opacity: u32,
+    pub opacity: f32,
     pub border_bottom_color: Color,
     pub border_bottom_style: BorderStyle,
     pub border_left_color: Color,
@@ -56,91 +110,630 @@ pub struct ComputedStyles {
     pub border_right_style: BorderStyle,
     pub border_top_color: Color,
     pub border_top_style: BorderStyle,
+    // Shadow colors are kept as specified and resolved against `color` when
+    // read, since `currentColor` in an inherited `text-shadow` refers to the
+    // color of the element it's inherited by.
     pub box_shadows: InlineBoxShadows,
+    pub content: Content,
+    pub counter_increments: InlineCounters,
+    pub counter_resets: InlineCounters,
+
+    // Generated content state: the counters in scope, from the outermost one,
+    // and the number of quotes opened. Both carry over to following siblings
+    // as well as to descendants, so they're kept out of the inherited styles,
+    // which siblings would otherwise stop sharing.
+    pub counters: InlineCounters,
+    pub quote_depth: u32,
+
+    // Inherited styles, shared with the parent until one of them is set
+    // on this element
+    pub inherited: Arc<ComputedInheritedStyles>,
+
+    // Product of the opacities of all ancestors
+    pub ancestor_opacity: f32,
+
+    // Used to resolve CSS-wide keywords
+    pub parent: ParentStyles
+}
 
-    // Inherited theme styles
-    // https://www.w3.org/TR/CSS21/propidx.html
-    // https://developer.mozilla.org/en-US/docs/Web/CSS/Reference
-    pub cursor: Cursor,
-    pub color: Color,
-    pub text_shadows: InlineTextShadows,
-    pub font_names: InlineFontNames,
-    pub font_style: FontStyle,
-    pub font_caps: FontCaps,
-    pub font_weight: FontWeight,
-    pub font_size: FontSize,
-    pub font_stretch: FontStretch,
-    pub visibility: Visibility
+// The parent styles, used to resolve CSS-wide keywords. Not compared, since
+// they're only needed while computing.
+#[derive(Debug, Clone)]
+pub enum ParentStyles {
+    None,
+    Styles(Arc<ComputedStyles>),
+    // When inheriting through `TInheritedStyles::inherit_styles`, which only
+    // gives a reference to the parent, just its inherited styles are shared.
+    Inherited(Arc<ComputedInheritedStyles>)
 }
 
-impl TComputedStyles for ComputedStyles {
-    type BackgroundColor = Color;
-    type Opacity = u32;
-    type BorderSize = u32;
-    type BorderColor = Color;
-    type BorderStyle = BorderStyle;
-    type BoxShadow = BoxShadow;
+impl Default for ParentStyles {
+    fn default() -> Self {
+        ParentStyles::None
+    }
+}
 
-    fn make_initial_computed_styles<T>(_: T) -> Self
+impl ParentStyles {
+    pub fn styles(&self) -> Option<&Arc<ComputedStyles>> {
+        match self {
+            &ParentStyles::Styles(ref styles) => Some(styles),
+            &ParentStyles::None | &ParentStyles::Inherited(_) => None
+        }
+    }
+
+    pub fn inherited(&self) -> Option<&ComputedInheritedStyles> {
+        match self {
+            &ParentStyles::None => None,
+            &ParentStyles::Styles(ref styles) => Some(&styles.inherited),
+            &ParentStyles::Inherited(ref inherited) => Some(inherited)
+        }
+    }
+}
+
+impl PartialEq for ParentStyles {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+// Stands for any element when asking for initial values, which don't depend
+// on the element's tag.
+struct AnyElement;
+
+impl TryInto<KnownElementName> for AnyElement {
+    type Error = ();
+
+    fn try_into(self) -> Result<KnownElementName, Self::Error> {
+        Err(())
+    }
+}
+
+impl Default for ComputedStyles {
+    fn default() -> Self {
+        ComputedStyles::initial_values()
+    }
+}
+
+impl ComputedStyles {
+    // Same as `make_initial_computed_styles`, which defines the initial values.
+    pub fn initial_values() -> Self {
+        ComputedStyles::make_initial_computed_styles(AnyElement)
+    }
+
+    // Computes the styles of an element, together with the layout styles to
+    // hand over to the layout engine, in this order:
+    //
+    // 1. The element starts from the initial values for its tag.
+    // 2. Inherited properties are copied from `parent`, if there is one.
+    // 3. All `declarations` are cascaded in the given order, so user-agent
+    //    declarations (from `make_user_agent_styles`) should come before
+    //    author ones, with later declarations winning.
+    // 4. Custom properties are resolved first, then `color`, then every
+    //    other theme style, so that `var()` and `currentColor` see the final
+    //    values regardless of declaration order.
+    // 5. Layout styles are collected in cascade order, with `var()` references
+    //    substituted and CSS-wide keywords resolved, so the last one for a
+    //    given property wins.
+    pub fn compute<T>(parent: Option<&Arc<ComputedStyles>>, declarations: &[&StyleDeclarations], tag: T) -> (ComputedStyles, Vec<FlexStyle>)
     where
         T: TryInto<KnownElementName>
     {
-        ComputedStyles {
-            background_color: Color::transparent(),
-            border_bottom_color: Color::transparent(),
-            border_left_color: Color::transparent(),
-            border_right_color: Color::transparent(),
-            border_top_color: Color::transparent(),
-            ..Default::default()
+        ComputedStyles::compute_following(parent, None, None, declarations, tag)
+    }
+
+    // Same as `compute`, but continues the counters and quotes of `previous`,
+    // the element's previous sibling, instead of starting from the parent's.
+    // Both follow document order, so when the previous sibling has children,
+    // `last_descendant` is the last element computed within its subtree, whose
+    // increments and quotes carry over. Counters reset by the previous sibling
+    // stay in scope, while those reset by its descendants don't. A `::before`
+    // pseudo-element is computed with its element as parent, and `::after`
+    // follows the element's last child.
+    pub fn compute_following<T>(
+        parent: Option<&Arc<ComputedStyles>>,
+        previous: Option<&ComputedStyles>,
+        last_descendant: Option<&ComputedStyles>,
+        declarations: &[&StyleDeclarations],
+        tag: T
+    ) -> (ComputedStyles, Vec<FlexStyle>)
+    where
+        T: TryInto<KnownElementName>
+    {
+        let mut computed = ComputedStyles::make_initial_computed_styles(tag);
+        match parent {
+            Some(parent) => computed.inherit_from(parent, ParentStyles::Styles(Arc::clone(parent))),
+            None => computed.resolve_initial_font_size()
         }
+        let (counters, quote_depth) = ComputedStyles::starting_state(parent, previous, last_descendant);
+        computed.counters = counters;
+        computed.quote_depth = quote_depth;
+        computed.cascade(parent, declarations)
     }
 
-    fn reset_custom_styles<T>(&mut self, tag: T)
+    // The counters and quote depth an element starts from, as described in
+    // `compute_following`. Counters after those of the previous sibling were
+    // reset by its descendants, and are left out.
+    pub(crate) fn starting_state(
+        parent: Option<&Arc<ComputedStyles>>,
+        previous: Option<&ComputedStyles>,
+        last_descendant: Option<&ComputedStyles>
+    ) -> (InlineCounters, u32) {
+        match (previous, parent) {
+            (Some(previous), _) => {
+                let last = last_descendant.unwrap_or(previous);
+                let counters = last.counters.iter().take(previous.counters.len()).cloned().collect();
+                (counters, last.quote_depth_after())
+            }
+            (None, Some(parent)) => (parent.counters.clone(), parent.quote_depth_after()),
+            (None, None) => (InlineCounters::default(), 0)
+        }
+    }
+
+    // Same as `compute` for the root element, with the sizes to use for the
+    // absolute-size font keywords and the system font size. Descendants
+    // inherit them, so this is the only place they need to be configured.
+    pub fn compute_root<T>(font_size_table: FontSizeTable, declarations: &[&StyleDeclarations], tag: T) -> (ComputedStyles, Vec<FlexStyle>)
     where
         T: TryInto<KnownElementName>
     {
-        *self = ComputedStyles::make_initial_computed_styles(tag)
+        let mut computed = ComputedStyles::make_initial_computed_styles(tag);
+        computed.inherited_mut().font_size_table = font_size_table;
+        computed.resolve_initial_font_size();
+        computed.cascade(None, declarations)
     }
 
-    fn apply_styles(&mut self, styles: &Self::Styles) {
+    // Cascades `declarations` over the styles the element starts from.
+    fn cascade(mut self, parent: Option<&Arc<ComputedStyles>>, declarations: &[&StyleDeclarations]) -> (ComputedStyles, Vec<FlexStyle>) {
+        let cascaded = StyleDeclarations::from_iter(declarations.iter().flat_map(|styles| styles.iter().cloned()));
+        self.apply_styles(&cascaded);
+
+        let scope = parent.map(|parent| parent.counters.len()).unwrap_or(0);
+        self.update_counters(scope);
+
+        let layout_styles = self.collect_layout_styles(&cascaded);
+        (self, layout_styles)
+    }
+
+    // Shares the inherited styles of `parent`, and keeps `styles` for
+    // resolving CSS-wide keywords. Its counters and quotes stay in scope.
+    fn inherit_from(&mut self, parent: &ComputedStyles, styles: ParentStyles) {
+        // Shared until this element sets one of its inherited properties.
+        self.inherited = Arc::clone(&parent.inherited);
+        self.counters = parent.counters.clone();
+        self.quote_depth = parent.quote_depth;
+        self.ancestor_opacity = parent.effective_opacity();
+        self.parent = styles;
+    }
+
+    // The initial font size is `medium`, resolved to a length using this
+    // element's keyword sizes so that it can be handed over to the layout
+    // engine. Only needed for the root and `initial`, since every other
+    // element inherits a resolved size.
+    fn resolve_initial_font_size(&mut self) {
+        if self.inherited.font_size == FontSize::System {
+            let table = self.inherited.font_size_table;
+            self.inherited_mut().font_size = FontSize::System.resolve(table.medium(), &table);
+        }
+    }
+
+    // The opacity this element is actually rendered with, once composited
+    // into all of its ancestors.
+    pub fn effective_opacity(&self) -> f32 {
+        self.opacity * self.ancestor_opacity
+    }
+
+    // Gives mutable access to the inherited styles, copying them first if
+    // they're shared with other elements.
+    pub fn inherited_mut(&mut self) -> &mut ComputedInheritedStyles {
+        Arc::make_mut(&mut self.inherited)
+    }
+
+    // The value of the innermost counter with this name, or 0 when there's
+    // none in scope, as if it had been reset.
+    pub fn counter_value<T>(&self, name: T) -> i32
+    where
+        T: AsRef<str>
+    {
+        self.counters
+            .iter()
+            .rev()
+            .find(|counter| counter.name.as_ref() == name.as_ref())
+            .map(|counter| counter.value)
+            .unwrap_or(0)
+    }
+
+    // The text generated by `content`, for the styles of a `::before` or
+    // `::after` pseudo-element. `attr` looks up the attributes of the
+    // element the pseudo-element belongs to.
+    pub fn generated_text<F>(&self, attr: F) -> Option<String>
+    where
+        F: Fn(&str) -> Option<String>
+    {
+        let items = match self.content {
+            Content::Items(ref items) => items,
+            Content::Normal | Content::None => return None
+        };
+
+        let mut text = String::new();
+        let mut depth = self.quote_depth;
+        for item in items.iter() {
+            match item {
+                &ContentItem::String(ref string) => text.push_str(string.as_ref()),
+                &ContentItem::Counter(ref name, style) => text.push_str(&style.format(self.counter_value(name))),
+                &ContentItem::Counters(ref name, ref separator, style) => {
+                    let values: Vec<_> = self.counters
+                        .iter()
+                        .filter(|counter| counter.name == *name)
+                        .map(|counter| style.format(counter.value))
+                        .collect();
+                    if values.is_empty() {
+                        text.push_str(&style.format(0));
+                    } else {
+                        text.push_str(&values.join(separator.as_ref()));
+                    }
+                }
+                &ContentItem::Attr(ref name) => text.push_str(&attr(name.as_ref()).unwrap_or_default()),
+                &ContentItem::OpenQuote => {
+                    text.push_str(self.quote(depth, true));
+                    depth += 1;
+                }
+                &ContentItem::CloseQuote => {
+                    if depth > 0 {
+                        depth -= 1;
+                        text.push_str(self.quote(depth, false));
+                    }
+                }
+                &ContentItem::NoOpenQuote => depth += 1,
+                &ContentItem::NoCloseQuote => depth = depth.saturating_sub(1)
+            }
+        }
+        Some(text)
+    }
+
+    // The number of quotes opened once this element's content is generated.
+    pub fn quote_depth_after(&self) -> u32 {
+        let items = match self.content {
+            Content::Items(ref items) => items,
+            Content::Normal | Content::None => return self.quote_depth
+        };
+        items.iter().fold(self.quote_depth, |depth, item| match item {
+            &ContentItem::OpenQuote | &ContentItem::NoOpenQuote => depth + 1,
+            &ContentItem::CloseQuote | &ContentItem::NoCloseQuote => depth.saturating_sub(1),
+            _ => depth
+        })
+    }
+
+    pub fn custom_property<T>(&self, name: T) -> Option<&UnparsedValue>
+    where
+        T: AsRef<str>
+    {
+        find_custom_property(&self.inherited.custom_properties, name.as_ref())
+    }
+
+    // Substitutes all `var()` references in the given declaration using this
+    // element's custom properties, and parses the result into typed declarations.
+    // When a reference can't be resolved and has no fallback, or the result
+    // doesn't parse, the declaration is invalid at computed-value time and
+    // its properties are `unset`, instead of letting earlier values win.
+    // Parsed results are cached, so only the substitution runs per element.
+    #[cfg(feature = "css-parse")]
+    pub fn resolve_variables(&self, declaration: &UnparsedDeclaration) -> StyleDeclarations {
+        let property = declaration.property.as_ref();
+        let custom_properties = &self.inherited.custom_properties;
+        let substituted = substitute_variables(declaration.value.as_ref(), &mut |name: &str| {
+            find_custom_property(custom_properties, name).map(|v| v.as_ref().to_owned())
+        });
+        match substituted.map(|value| parse_substituted(property, value)) {
+            Some(styles) if !styles.is_empty() => styles,
+            // Shorthands expand to every longhand they set.
+            _ => parse_substituted(property, "unset".to_owned())
+        }
+    }
+
+    fn apply_custom_properties(&mut self, styles: &StyleDeclarations) {
+        let declared: Vec<_> = styles.iter().filter_map(is_custom_property).collect();
+        if !declared.is_empty() {
+            let custom_properties = resolve_custom_properties(&self.inherited.custom_properties, &declared);
+            self.inherited_mut().custom_properties = custom_properties;
+        }
+    }
+
+    #[cfg(feature = "css-parse")]
+    fn apply_unparsed_declaration(&mut self, declaration: &UnparsedDeclaration) {
+        self.resolve_variables(declaration).iter().for_each(|style| self.apply_style(style));
+    }
+
+    #[cfg(not(feature = "css-parse"))]
+    fn apply_unparsed_declaration(&mut self, _: &UnparsedDeclaration) {
+        // Re-parsing substituted values requires the CSS parser.
+    }
+
+    fn collect_layout_styles(&self, styles: &StyleDeclarations) -> Vec<FlexStyle> {
+        let mut layout_styles = Vec::new();
+        for style in styles.iter() {
+            match style {
+                &StyleDeclaration::Layout(ref layout_style) => layout_styles.push(layout_style.clone()),
+                &StyleDeclaration::WithVariables(ref declaration) => self.collect_unparsed_layout_styles(declaration, &mut layout_styles),
+                // Resolved when the styles were applied, so the layout engine
+                // gets the resulting value instead of the keyword.
+                &StyleDeclaration::WideKeyword(id, _) => layout_styles.extend(self.layout_style(id)),
+                _ => {}
+            }
+        }
+        layout_styles
+    }
+
+    fn layout_style(&self, id: PropertyId) -> Option<FlexStyle> {
+        match id {
+            PropertyId::BorderBottomWidth => Some(FlexStyle::BorderBottom((self.border_bottom_width as f32).into())),
+            PropertyId::BorderLeftWidth => Some(FlexStyle::BorderLeft((self.border_left_width as f32).into())),
+            PropertyId::BorderRightWidth => Some(FlexStyle::BorderRight((self.border_right_width as f32).into())),
+            PropertyId::BorderTopWidth => Some(FlexStyle::BorderTop((self.border_top_width as f32).into())),
+            _ => self.layout.to_flex_style(id)
+        }
+    }
+
+    #[cfg(feature = "css-parse")]
+    fn collect_unparsed_layout_styles(&self, declaration: &UnparsedDeclaration, layout_styles: &mut Vec<FlexStyle>) {
+        for style in self.resolve_variables(declaration).iter() {
+            match style {
+                &StyleDeclaration::Layout(ref layout_style) => layout_styles.push(layout_style.clone()),
+                &StyleDeclaration::WideKeyword(id, _) => layout_styles.extend(self.layout_style(id)),
+                _ => {}
+            }
+        }
+    }
+
+    #[cfg(not(feature = "css-parse"))]
+    fn collect_unparsed_layout_styles(&self, _: &UnparsedDeclaration, _: &mut Vec<FlexStyle>) {
+        // Re-parsing substituted values requires the CSS parser.
+    }
+
+    // Quotes nested deeper than the given pairs use the innermost pair.
+    fn quote(&self, depth: u32, open: bool) -> &str {
+        let pair = match self.inherited.quotes {
+            Quotes::None => return "",
+            Quotes::Auto => AUTO_QUOTES[(depth as usize).min(AUTO_QUOTES.len() - 1)],
+            Quotes::Pairs(ref pairs) => match pairs.get((depth as usize).min(pairs.len().saturating_sub(1))) {
+                Some(pair) => (pair.open.as_ref(), pair.close.as_ref()),
+                None => return ""
+            }
+        };
+        if open {
+            pair.0
+        } else {
+            pair.1
+        }
+    }
+
+    // Applies `counter-reset` then `counter-increment`. A reset replaces a
+    // counter of the same name reset by a previous sibling, found at or after
+    // `scope` in the counters, and otherwise nests a new counter. Incrementing
+    // a counter that isn't in scope resets it first.
+    fn update_counters(&mut self, scope: usize) {
+        if self.counter_resets.is_empty() && self.counter_increments.is_empty() {
+            return;
+        }
+
+        let counters = &mut self.counters;
+        for reset in self.counter_resets.iter().cloned() {
+            match counters.iter().rposition(|counter| counter.name == reset.name) {
+                Some(i) if i >= scope => counters[i].value = reset.value,
+                _ => counters.push(reset)
+            }
+        }
+        for increment in self.counter_increments.iter().cloned() {
+            match counters.iter().rposition(|counter| counter.name == increment.name) {
+                Some(i) => counters[i].value = counters[i].value.wrapping_add(increment.value),
+                None => counters.push(increment)
+            }
+        }
+    }
+
+    fn parent_color(&self) -> Color {
+        self.parent.inherited().map(|parent| parent.color).unwrap_or_default()
+    }
+
+    fn parent_font_size(&self) -> f32 {
+        let table = &self.inherited.font_size_table;
+        let font_size = self.parent.inherited().map(|parent| parent.font_size).unwrap_or_default();
+        font_size.resolve(table.medium(), table).to_points()
+    }
+
+    fn parent_font_weight(&self) -> FontWeight {
+        self.parent.inherited().map(|parent| parent.font_weight).unwrap_or_default()
+    }
+
+    fn apply_wide_keyword(&mut self, id: PropertyId, keyword: CssWideKeyword) {
+        let inherit = match keyword {
+            CssWideKeyword::Inherit => true,
+            CssWideKeyword::Initial => false,
+            // `revert` is deliberately approximated as `unset`: cascade
+            // origins aren't tracked per property, so the user-agent value
+            // it should roll back to isn't known.
+            CssWideKeyword::Unset | CssWideKeyword::Revert => id.is_inherited()
+        };
+
+        match self.parent.clone() {
+            ParentStyles::Styles(ref parent) if inherit => self.copy_property(id, parent),
+            // Non-inherited properties of the parent aren't known, so they're
+            // taken as initial.
+            ParentStyles::Inherited(ref inherited) if inherit => {
+                let mut parent = ComputedStyles::make_initial_computed_styles(AnyElement);
+                parent.inherited = Arc::clone(inherited);
+                self.copy_property(id, &parent)
+            }
+            _ => self.copy_property(id, &ComputedStyles::make_initial_computed_styles(AnyElement))
+        }
+        if id == PropertyId::FontSize {
+            self.resolve_initial_font_size();
+        }
+    }
+
+    fn copy_property(&mut self, id: PropertyId, other: &ComputedStyles) {
+        match id {
+            // Non-inherited layout styles
+            PropertyId::BorderBottomWidth => self.border_bottom_width = other.border_bottom_width,
+            PropertyId::BorderLeftWidth => self.border_left_width = other.border_left_width,
+            PropertyId::BorderRightWidth => self.border_right_width = other.border_right_width,
+            PropertyId::BorderTopWidth => self.border_top_width = other.border_top_width,
+
+            // Non-inherited theme styles
+            PropertyId::BackgroundColor => self.background_color = other.background_color,
+            PropertyId::Opacity => self.opacity = other.opacity,
+            PropertyId::BorderBottomColor => self.border_bottom_color = other.border_bottom_color,
+            PropertyId::BorderBottomStyle => self.border_bottom_style = other.border_bottom_style,
+            PropertyId::BorderLeftColor => self.border_left_color = other.border_left_color,
+            PropertyId::BorderLeftStyle => self.border_left_style = other.border_left_style,
+            PropertyId::BorderRightColor => self.border_right_color = other.border_right_color,
+            PropertyId::BorderRightStyle => self.border_right_style = other.border_right_style,
+            PropertyId::BorderTopColor => self.border_top_color = other.border_top_color,
+            PropertyId::BorderTopStyle => self.border_top_style = other.border_top_style,
+            PropertyId::BoxShadow => self.box_shadows = other.box_shadows.clone(),
+            PropertyId::Content => self.content = other.content.clone(),
+            PropertyId::CounterIncrement => self.counter_increments = other.counter_increments.clone(),
+            PropertyId::CounterReset => self.counter_resets = other.counter_resets.clone(),
+
+            // Inherited theme styles
+            PropertyId::Cursor => self.inherited_mut().cursor = other.inherited.cursor,
+            PropertyId::Color => self.inherited_mut().color = other.inherited.color,
+            PropertyId::TextShadow => self.inherited_mut().text_shadows = other.inherited.text_shadows.clone(),
+            PropertyId::FontFamily => self.inherited_mut().font_names = other.inherited.font_names.clone(),
+            PropertyId::FontStyle => self.inherited_mut().font_style = other.inherited.font_style,
+            PropertyId::FontCaps => self.inherited_mut().font_caps = other.inherited.font_caps,
+            PropertyId::FontWeight => self.inherited_mut().font_weight = other.inherited.font_weight,
+            PropertyId::FontSize => self.inherited_mut().font_size = other.inherited.font_size,
+            PropertyId::FontStretch => self.inherited_mut().font_stretch = other.inherited.font_stretch,
+            PropertyId::Visibility => self.inherited_mut().visibility = other.inherited.visibility,
+            PropertyId::Quotes => self.inherited_mut().quotes = other.inherited.quotes.clone(),
+
+            // Other non-inherited layout styles
+            _ => self.layout.copy_property(id, &other.layout)
+        }
+    }
+
+    fn apply_style(&mut self, style: &StyleDeclaration) {
         use self::FlexStyle::*;
         use self::FontFamily::*;
         use self::StyleDeclaration::*;
         use self::ThemeStyle::*;
 
-        styles.iter().for_each(|style| match style {
+        match style {
             // Non-inherited layout styles
             &Layout(BorderBottom(border_bottom_width)) => self.border_bottom_width = border_bottom_width.into_inner() as u32,
             &Layout(BorderLeft(border_left_width)) => self.border_left_width = border_left_width.into_inner() as u32,
             &Layout(BorderRight(border_right_width)) => self.border_right_width = border_right_width.into_inner() as u32,
             &Layout(BorderTop(border_top_width)) => self.border_top_width = border_top_width.into_inner() as u32,
+            &Layout(ref layout_style) => self.layout.apply_style(layout_style),
 
             // Non-inherited theme styles
-            &Theme(BackgroundColor(background_color)) => self.background_color = background_color,
-            &Theme(Opacity(opacity)) => self.opacity = opacity,
-            &Theme(BorderBottomColor(border_bottom_color)) => self.border_bottom_color = border_bottom_color,
+            &Theme(BackgroundColor(background_color)) => self.background_color = background_color.resolve(self.inherited.color),
+            &Theme(Opacity(opacity)) => self.opacity = opacity.max(0.0).min(1.0),
+            &Theme(BorderBottomColor(border_bottom_color)) => self.border_bottom_color = border_bottom_color.resolve(self.inherited.color),
             &Theme(BorderBottomStyle(border_bottom_style)) => self.border_bottom_style = border_bottom_style,
-            &Theme(BorderLeftColor(border_left_color)) => self.border_left_color = border_left_color,
+            &Theme(BorderLeftColor(border_left_color)) => self.border_left_color = border_left_color.resolve(self.inherited.color),
             &Theme(BorderLeftStyle(border_left_style)) => self.border_left_style = border_left_style,
-            &Theme(BorderRightColor(border_right_color)) => self.border_right_color = border_right_color,
+            &Theme(BorderRightColor(border_right_color)) => self.border_right_color = border_right_color.resolve(self.inherited.color),
             &Theme(BorderRightStyle(border_right_style)) => self.border_right_style = border_right_style,
-            &Theme(BorderTopColor(border_top_color)) => self.border_top_color = border_top_color,
+            &Theme(BorderTopColor(border_top_color)) => self.border_top_color = border_top_color.resolve(self.inherited.color),
             &Theme(BorderTopStyle(border_top_style)) => self.border_top_style = border_top_style,
             &Theme(BoxShadow(ref box_shadows)) => self.box_shadows = box_shadows.clone(),
+            &Theme(Content(ref content)) => self.content = content.clone(),
+            &Theme(CounterIncrement(ref counters)) => self.counter_increments = counters.clone(),
+            &Theme(CounterReset(ref counters)) => self.counter_resets = counters.clone(),
 
             // Inherited theme styles
-            &Theme(Cursor(cursor)) => self.cursor = cursor,
-            &Theme(Color(color)) => self.color = color,
-            &Theme(TextShadow(ref text_shadows)) => self.text_shadows = text_shadows.clone(),
-            &Theme(FontFamily(Values(ref font_names))) => self.font_names = font_names.clone(),
-            &Theme(FontStyle(font_style)) => self.font_style = font_style,
-            &Theme(FontCaps(font_caps)) => self.font_caps = font_caps,
-            &Theme(FontWeight(font_weight)) => self.font_weight = font_weight,
-            &Theme(FontSize(font_size)) => self.font_size = font_size,
-            &Theme(FontStretch(font_stretch)) => self.font_stretch = font_stretch,
-            &Theme(Visibility(visibility)) => self.visibility = visibility,
+            &Theme(Cursor(cursor)) => self.inherited_mut().cursor = cursor,
+            &Theme(Color(color)) => {
+                let color = color.resolve(self.parent_color());
+                self.inherited_mut().color = color;
+            }
+            &Theme(TextShadow(ref text_shadows)) => self.inherited_mut().text_shadows = text_shadows.clone(),
+            &Theme(FontFamily(Values(ref font_names))) => self.inherited_mut().font_names = font_names.clone(),
+            &Theme(FontStyle(font_style)) => self.inherited_mut().font_style = font_style,
+            &Theme(FontCaps(font_caps)) => self.inherited_mut().font_caps = font_caps,
+            &Theme(FontWeight(font_weight)) => {
+                let font_weight = font_weight.resolve(self.parent_font_weight());
+                self.inherited_mut().font_weight = font_weight;
+            }
+            &Theme(FontSize(font_size)) => {
+                let font_size = font_size.resolve(self.parent_font_size(), &self.inherited.font_size_table);
+                self.inherited_mut().font_size = font_size;
+            }
+            &Theme(FontStretch(font_stretch)) => self.inherited_mut().font_stretch = font_stretch,
+            &Theme(Visibility(visibility)) => self.inherited_mut().visibility = visibility,
+            &Theme(Quotes(ref quotes)) => self.inherited_mut().quotes = quotes.clone(),
+
+            // Declarations depending on custom properties
+            &WithVariables(ref declaration) => self.apply_unparsed_declaration(declaration),
+
+            // CSS-wide keywords
+            &WideKeyword(id, keyword) => self.apply_wide_keyword(id, keyword),
             _ => {}
-        })
+        }
+    }
+}
+
+impl TComputedStyles for ComputedStyles {
+    type BackgroundColor = Color;
+    type Opacity = f32;
+    type BorderSize = u32;
+    type BorderColor = Color;
+    type BorderStyle = BorderStyle;
+    type BoxShadow = BoxShadow;
+
+    // Also used to resolve `initial` and `unset` for non-inherited properties,
+    // so CSS-wide keywords always agree with the styles elements start from.
+    fn make_initial_computed_styles<T>(_: T) -> Self
+    where
+        T: TryInto<KnownElementName>
+    {
+        // Every field is listed, since `ComputedStyles::default()` is defined
+        // in terms of this.
+        ComputedStyles {
+            border_bottom_width: 0,
+            border_left_width: 0,
+            border_right_width: 0,
+            border_top_width: 0,
+            layout: ComputedLayoutStyles::default(),
+            background_color: Color::transparent(),
+            opacity: 1.0,
+            border_bottom_color: Color::transparent(),
+            border_bottom_style: BorderStyle::default(),
+            border_left_color: Color::transparent(),
+            border_left_style: BorderStyle::default(),
+            border_right_color: Color::transparent(),
+            border_right_style: BorderStyle::default(),
+            border_top_color: Color::transparent(),
+            border_top_style: BorderStyle::default(),
+            box_shadows: InlineBoxShadows::default(),
+            content: Content::default(),
+            counter_increments: InlineCounters::default(),
+            counter_resets: InlineCounters::default(),
+            counters: InlineCounters::default(),
+            quote_depth: 0,
+            inherited: Arc::default(),
+            ancestor_opacity: 1.0,
+            parent: ParentStyles::default()
+        }
+    }
+
+    fn reset_custom_styles<T>(&mut self, tag: T)
+    where
+        T: TryInto<KnownElementName>
+    {
+        *self = ComputedStyles::make_initial_computed_styles(tag)
+    }
+
+    fn apply_styles(&mut self, styles: &Self::Styles) {
+        // Custom properties are resolved first, so that `var()` references see
+        // every custom property declared on this element, regardless of order.
+        self.apply_custom_properties(styles);
+
+        // Other colors may refer to this element's `color` through `currentColor`,
+        // so it's computed before anything else.
+        styles.iter().filter(|style| is_color_declaration(style)).for_each(|style| self.apply_style(style));
+        styles.iter().filter(|style| !is_color_declaration(style)).for_each(|style| self.apply_style(style))
     }
 
     fn background_color(&self) -> Self::BackgroundColor {
@@ -200,7 +793,13 @@ impl TComputedStyles for ComputedStyles {
     }
 
     fn box_shadows_copy(&self) -> Vec<Self::BoxShadow> {
-        self.box_shadows.to_vec()
+        self.box_shadows
+            .iter()
+            .map(|shadow| BoxShadow {
+                color: ColorValue::Rgba(shadow.color.resolve(self.inherited.color)),
+                ..shadow.clone()
+            })
+            .collect()
     }
 }
 
@@ -217,63 +816,62 @@ impl TInheritedStyles for ComputedStyles {
     type FontStretch = FontStretch;
     type Visibility = Visibility;
 
+    // Only given a reference, so unlike `compute`, this can't keep the parent
+    // itself, and shares its inherited styles instead.
     fn inherit_styles(&mut self, other: &Self) {
-        self.cursor = other.cursor;
-        self.color = other.color;
-        self.text_shadows = other.text_shadows.clone();
-        self.font_names = other.font_names.clone();
-        self.font_style = other.font_style;
-        self.font_caps = other.font_caps;
-        self.font_weight = other.font_weight;
-        self.font_size = other.font_size;
-        self.font_stretch = other.font_stretch;
-        self.visibility = other.visibility;
+        self.inherit_from(other, ParentStyles::Inherited(Arc::clone(&other.inherited)));
     }
 
     fn cursor(&self) -> Self::Cursor {
-        self.cursor
+        self.inherited.cursor
     }
 
     fn color(&self) -> Self::Color {
-        self.color
+        self.inherited.color
     }
 
     fn text_shadows_copy(&self) -> Vec<Self::TextShadow> {
-        self.text_shadows.to_vec()
+        self.inherited.text_shadows
+            .iter()
+            .map(|shadow| TextShadow {
+                color: ColorValue::Rgba(shadow.color.resolve(self.inherited.color)),
+                ..shadow.clone()
+            })
+            .collect()
     }
 
     fn font_names_copy(&self) -> Vec<Self::FontName> {
-        self.font_names.to_vec()
+        self.inherited.font_names.to_vec()
     }
 
     fn font_style(&self) -> Self::FontStyle {
-        self.font_style
+        self.inherited.font_style
     }
 
     fn font_caps(&self) -> Self::FontCaps {
-        self.font_caps
+        self.inherited.font_caps
     }
 
     fn font_weight(&self) -> Self::FontWeight {
-        self.font_weight
+        self.inherited.font_weight
     }
 
     fn font_size(&self) -> Self::FontSize {
-        self.font_size
+        self.inherited.font_size
     }
 
     fn font_stretch(&self) -> Self::FontStretch {
-        self.font_stretch
+        self.inherited.font_stretch
     }
 
     fn visibility(&self) -> Self::Visibility {
-        self.visibility
+        self.inherited.visibility
     }
 
     fn find_font<F, O>(&self, predicate: F) -> Option<O>
     where
         F: FnMut(&Self::FontName) -> Option<O>
     {
-        self.font_names.iter().filter_map(predicate).next()
+        self.inherited.font_names.iter().filter_map(predicate).next()
     }
 }
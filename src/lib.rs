@@ -37,3 +37,7 @@ pub mod types {
     pub use computed_styles::types::*;
     pub use styles::types::*;
 }
+
+pub mod keywords {
+    pub use styles::keywords::*;
+}
@@ -0,0 +1,754 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use styles::types::{Color, ColorValue, SystemColor};
+
+// Defines `Color::from_name` and a `ColorValue` constant for each named color,
+// so that `value!(color: red)` is checked when compiling.
+macro_rules! named_colors {
+    ($( $name:ident: [$red:expr, $green:expr, $blue:expr, $alpha:expr] ),*) => {
+        impl Color {
+            pub fn from_name<T>(name: T) -> Option<Self>
+            where
+                T: AsRef<str>
+            {
+                let name = name.as_ref();
+                $(
+                    if name.eq_ignore_ascii_case(stringify!($name)) {
+                        return Some(Color::new([$red, $green, $blue, $alpha]));
+                    }
+                )*
+                None
+            }
+        }
+
+        #[allow(non_upper_case_globals)]
+        impl ColorValue {
+            $(
+                pub const $name: ColorValue = ColorValue::Rgba(Color {
+                    red: $red,
+                    green: $green,
+                    blue: $blue,
+                    alpha: $alpha
+                });
+            )*
+        }
+    };
+}
+
+// Same as `named_colors!`, for the system colors, which are spelled as in the
+// spec, e.g. `value!(color: ButtonText)`.
+macro_rules! system_colors {
+    ($( $name:ident ),*) => {
+        #[allow(non_upper_case_globals)]
+        impl ColorValue {
+            $( pub const $name: ColorValue = ColorValue::System(SystemColor::$name); )*
+        }
+    };
+}
+
+// Hue is in degrees within [0, 360), every other component within [0, 1].
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Hsla {
+    pub hue: f32,
+    pub saturation: f32,
+    pub lightness: f32,
+    pub alpha: f32
+}
+
+// Hue is in degrees within [0, 360), every other component within [0, 1].
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Hsva {
+    pub hue: f32,
+    pub saturation: f32,
+    pub value: f32,
+    pub alpha: f32
+}
+
+// See https://www.w3.org/TR/css-color-4/#named-colors
+named_colors! {
+    transparent: [0, 0, 0, 0],
+    aliceblue: [240, 248, 255, 255],
+    antiquewhite: [250, 235, 215, 255],
+    aqua: [0, 255, 255, 255],
+    aquamarine: [127, 255, 212, 255],
+    azure: [240, 255, 255, 255],
+    beige: [245, 245, 220, 255],
+    bisque: [255, 228, 196, 255],
+    black: [0, 0, 0, 255],
+    blanchedalmond: [255, 235, 205, 255],
+    blue: [0, 0, 255, 255],
+    blueviolet: [138, 43, 226, 255],
+    brown: [165, 42, 42, 255],
+    burlywood: [222, 184, 135, 255],
+    cadetblue: [95, 158, 160, 255],
+    chartreuse: [127, 255, 0, 255],
+    chocolate: [210, 105, 30, 255],
+    coral: [255, 127, 80, 255],
+    cornflowerblue: [100, 149, 237, 255],
+    cornsilk: [255, 248, 220, 255],
+    crimson: [220, 20, 60, 255],
+    cyan: [0, 255, 255, 255],
+    darkblue: [0, 0, 139, 255],
+    darkcyan: [0, 139, 139, 255],
+    darkgoldenrod: [184, 134, 11, 255],
+    darkgray: [169, 169, 169, 255],
+    darkgreen: [0, 100, 0, 255],
+    darkgrey: [169, 169, 169, 255],
+    darkkhaki: [189, 183, 107, 255],
+    darkmagenta: [139, 0, 139, 255],
+    darkolivegreen: [85, 107, 47, 255],
+    darkorange: [255, 140, 0, 255],
+    darkorchid: [153, 50, 204, 255],
+    darkred: [139, 0, 0, 255],
+    darksalmon: [233, 150, 122, 255],
+    darkseagreen: [143, 188, 143, 255],
+    darkslateblue: [72, 61, 139, 255],
+    darkslategray: [47, 79, 79, 255],
+    darkslategrey: [47, 79, 79, 255],
+    darkturquoise: [0, 206, 209, 255],
+    darkviolet: [148, 0, 211, 255],
+    deeppink: [255, 20, 147, 255],
+    deepskyblue: [0, 191, 255, 255],
+    dimgray: [105, 105, 105, 255],
+    dimgrey: [105, 105, 105, 255],
+    dodgerblue: [30, 144, 255, 255],
+    firebrick: [178, 34, 34, 255],
+    floralwhite: [255, 250, 240, 255],
+    forestgreen: [34, 139, 34, 255],
+    fuchsia: [255, 0, 255, 255],
+    gainsboro: [220, 220, 220, 255],
+    ghostwhite: [248, 248, 255, 255],
+    gold: [255, 215, 0, 255],
+    goldenrod: [218, 165, 32, 255],
+    gray: [128, 128, 128, 255],
+    green: [0, 128, 0, 255],
+    greenyellow: [173, 255, 47, 255],
+    grey: [128, 128, 128, 255],
+    honeydew: [240, 255, 240, 255],
+    hotpink: [255, 105, 180, 255],
+    indianred: [205, 92, 92, 255],
+    indigo: [75, 0, 130, 255],
+    ivory: [255, 255, 240, 255],
+    khaki: [240, 230, 140, 255],
+    lavender: [230, 230, 250, 255],
+    lavenderblush: [255, 240, 245, 255],
+    lawngreen: [124, 252, 0, 255],
+    lemonchiffon: [255, 250, 205, 255],
+    lightblue: [173, 216, 230, 255],
+    lightcoral: [240, 128, 128, 255],
+    lightcyan: [224, 255, 255, 255],
+    lightgoldenrodyellow: [250, 250, 210, 255],
+    lightgray: [211, 211, 211, 255],
+    lightgreen: [144, 238, 144, 255],
+    lightgrey: [211, 211, 211, 255],
+    lightpink: [255, 182, 193, 255],
+    lightsalmon: [255, 160, 122, 255],
+    lightseagreen: [32, 178, 170, 255],
+    lightskyblue: [135, 206, 250, 255],
+    lightslategray: [119, 136, 153, 255],
+    lightslategrey: [119, 136, 153, 255],
+    lightsteelblue: [176, 196, 222, 255],
+    lightyellow: [255, 255, 224, 255],
+    lime: [0, 255, 0, 255],
+    limegreen: [50, 205, 50, 255],
+    linen: [250, 240, 230, 255],
+    magenta: [255, 0, 255, 255],
+    maroon: [128, 0, 0, 255],
+    mediumaquamarine: [102, 205, 170, 255],
+    mediumblue: [0, 0, 205, 255],
+    mediumorchid: [186, 85, 211, 255],
+    mediumpurple: [147, 112, 219, 255],
+    mediumseagreen: [60, 179, 113, 255],
+    mediumslateblue: [123, 104, 238, 255],
+    mediumspringgreen: [0, 250, 154, 255],
+    mediumturquoise: [72, 209, 204, 255],
+    mediumvioletred: [199, 21, 133, 255],
+    midnightblue: [25, 25, 112, 255],
+    mintcream: [245, 255, 250, 255],
+    mistyrose: [255, 228, 225, 255],
+    moccasin: [255, 228, 181, 255],
+    navajowhite: [255, 222, 173, 255],
+    navy: [0, 0, 128, 255],
+    oldlace: [253, 245, 230, 255],
+    olive: [128, 128, 0, 255],
+    olivedrab: [107, 142, 35, 255],
+    orange: [255, 165, 0, 255],
+    orangered: [255, 69, 0, 255],
+    orchid: [218, 112, 214, 255],
+    palegoldenrod: [238, 232, 170, 255],
+    palegreen: [152, 251, 152, 255],
+    paleturquoise: [175, 238, 238, 255],
+    palevioletred: [219, 112, 147, 255],
+    papayawhip: [255, 239, 213, 255],
+    peachpuff: [255, 218, 185, 255],
+    peru: [205, 133, 63, 255],
+    pink: [255, 192, 203, 255],
+    plum: [221, 160, 221, 255],
+    powderblue: [176, 224, 230, 255],
+    purple: [128, 0, 128, 255],
+    rebeccapurple: [102, 51, 153, 255],
+    red: [255, 0, 0, 255],
+    rosybrown: [188, 143, 143, 255],
+    royalblue: [65, 105, 225, 255],
+    saddlebrown: [139, 69, 19, 255],
+    salmon: [250, 128, 114, 255],
+    sandybrown: [244, 164, 96, 255],
+    seagreen: [46, 139, 87, 255],
+    seashell: [255, 245, 238, 255],
+    sienna: [160, 82, 45, 255],
+    silver: [192, 192, 192, 255],
+    skyblue: [135, 206, 235, 255],
+    slateblue: [106, 90, 205, 255],
+    slategray: [112, 128, 144, 255],
+    slategrey: [112, 128, 144, 255],
+    snow: [255, 250, 250, 255],
+    springgreen: [0, 255, 127, 255],
+    steelblue: [70, 130, 180, 255],
+    tan: [210, 180, 140, 255],
+    teal: [0, 128, 128, 255],
+    thistle: [216, 191, 216, 255],
+    tomato: [255, 99, 71, 255],
+    turquoise: [64, 224, 208, 255],
+    violet: [238, 130, 238, 255],
+    wheat: [245, 222, 179, 255],
+    white: [255, 255, 255, 255],
+    whitesmoke: [245, 245, 245, 255],
+    yellow: [255, 255, 0, 255],
+    yellowgreen: [154, 205, 50, 255]
+}
+
+impl Color {
+    // Builds a color from components within [0, 1], clamping them if needed.
+    pub fn from_rgba_f32(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Color::new([to_u8(red), to_u8(green), to_u8(blue), to_u8(alpha)])
+    }
+
+    pub fn from_hsla(hsla: Hsla) -> Self {
+        let Hsla { hue, saturation, lightness, alpha } = hsla;
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let (red, green, blue) = hue_to_rgb(hue, chroma);
+        let m = lightness - chroma / 2.0;
+        Color::from_rgba_f32(red + m, green + m, blue + m, alpha)
+    }
+
+    pub fn from_hsva(hsva: Hsva) -> Self {
+        let Hsva { hue, saturation, value, alpha } = hsva;
+        let chroma = value * saturation;
+        let (red, green, blue) = hue_to_rgb(hue, chroma);
+        let m = value - chroma;
+        Color::from_rgba_f32(red + m, green + m, blue + m, alpha)
+    }
+
+    pub fn to_hsla(&self) -> Hsla {
+        let (max, min) = self.max_min_f32();
+        let chroma = max - min;
+        let lightness = (max + min) / 2.0;
+        let saturation = if chroma == 0.0 {
+            0.0
+        } else {
+            chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+
+        Hsla {
+            hue: self.hue(),
+            saturation: clamp_unit(saturation),
+            lightness,
+            alpha: self.alpha_f32()
+        }
+    }
+
+    pub fn to_hsva(&self) -> Hsva {
+        let (max, min) = self.max_min_f32();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+        Hsva {
+            hue: self.hue(),
+            saturation,
+            value: max,
+            alpha: self.alpha_f32()
+        }
+    }
+
+    pub fn alpha_f32(&self) -> f32 {
+        f32::from(self.alpha) / 255.0
+    }
+
+    pub fn with_alpha(&self, alpha: f32) -> Self {
+        Color {
+            alpha: to_u8(alpha),
+            ..*self
+        }
+    }
+
+    // Increases the HSL lightness by `amount`, within [0, 1].
+    pub fn lighten(&self, amount: f32) -> Self {
+        let mut hsla = self.to_hsla();
+        hsla.lightness = clamp_unit(hsla.lightness + amount);
+        Color::from_hsla(hsla)
+    }
+
+    // Decreases the HSL lightness by `amount`, within [0, 1].
+    pub fn darken(&self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    // Interpolates every channel, `weight` being the proportion of `other`
+    // in the result.
+    pub fn mix(&self, other: Color, weight: f32) -> Self {
+        let weight = clamp_unit(weight);
+        let mix = |a: u8, b: u8| to_u8((f32::from(a) * (1.0 - weight) + f32::from(b) * weight) / 255.0);
+        Color::new([
+            mix(self.red, other.red),
+            mix(self.green, other.green),
+            mix(self.blue, other.blue),
+            mix(self.alpha, other.alpha),
+        ])
+    }
+
+    // Composites this color on top of `backdrop` with the source-over operator.
+    // See https://www.w3.org/TR/compositing-1/#porterduffcompositingoperators_srcover
+    pub fn over(&self, backdrop: Color) -> Self {
+        let source_alpha = self.alpha_f32();
+        let backdrop_alpha = backdrop.alpha_f32() * (1.0 - source_alpha);
+        let alpha = source_alpha + backdrop_alpha;
+        if alpha == 0.0 {
+            return Color::transparent();
+        }
+
+        let blend = |s: u8, b: u8| (f32::from(s) * source_alpha + f32::from(b) * backdrop_alpha) / 255.0 / alpha;
+        Color::from_rgba_f32(
+            blend(self.red, backdrop.red),
+            blend(self.green, backdrop.green),
+            blend(self.blue, backdrop.blue),
+            alpha
+        )
+    }
+
+    // The color channels multiplied by alpha, as most blending backends expect.
+    pub fn premultiplied(&self) -> [u8; 4] {
+        let alpha = u32::from(self.alpha);
+        let premultiply = |c: u8| ((u32::from(c) * alpha + 127) / 255) as u8;
+        [premultiply(self.red), premultiply(self.green), premultiply(self.blue), self.alpha]
+    }
+
+    // See https://www.w3.org/TR/WCAG21/#dfn-relative-luminance
+    pub fn relative_luminance(&self) -> f32 {
+        let linearize = |c: u8| {
+            let c = f32::from(c) / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linearize(self.red) + 0.7152 * linearize(self.green) + 0.0722 * linearize(self.blue)
+    }
+
+    // Ranges from 1 (no contrast) to 21 (black on white).
+    // See https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio
+    pub fn contrast_ratio(&self, other: Color) -> f32 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    // Serializes as `#rrggbb`, or `#rrggbbaa` when not fully opaque.
+    pub fn to_hex_string(&self) -> String {
+        if self.alpha == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.red, self.green, self.blue, self.alpha)
+        }
+    }
+
+    // Serializes as `rgb(r, g, b)`, or `rgba(r, g, b, a)` when not fully opaque.
+    pub fn to_rgba_string(&self) -> String {
+        if self.alpha == 255 {
+            format!("rgb({}, {}, {})", self.red, self.green, self.blue)
+        } else {
+            let alpha = (self.alpha_f32() * 1000.0).round() / 1000.0;
+            format!("rgba({}, {}, {}, {})", self.red, self.green, self.blue, alpha)
+        }
+    }
+
+    fn max_min_f32(&self) -> (f32, f32) {
+        let max = self.red.max(self.green).max(self.blue);
+        let min = self.red.min(self.green).min(self.blue);
+        (f32::from(max) / 255.0, f32::from(min) / 255.0)
+    }
+
+    fn hue(&self) -> f32 {
+        let (red, green, blue) = (f32::from(self.red), f32::from(self.green), f32::from(self.blue));
+        let max = self.red.max(self.green).max(self.blue);
+        let chroma = f32::from(max - self.red.min(self.green).min(self.blue));
+
+        if chroma == 0.0 {
+            0.0
+        } else if max == self.red {
+            normalize_hue(60.0 * ((green - blue) / chroma))
+        } else if max == self.green {
+            normalize_hue(60.0 * ((blue - red) / chroma + 2.0))
+        } else {
+            normalize_hue(60.0 * ((red - green) / chroma + 4.0))
+        }
+    }
+}
+
+impl ColorValue {
+    // Parses a color keyword: `currentColor`, a system color or a named color.
+    pub fn from_keyword<T>(keyword: T) -> Option<Self>
+    where
+        T: AsRef<str>
+    {
+        let keyword = keyword.as_ref();
+        if keyword.eq_ignore_ascii_case("currentcolor") {
+            return Some(ColorValue::CurrentColor);
+        }
+        SystemColor::from_name(keyword)
+            .map(ColorValue::System)
+            .or_else(|| Color::from_name(keyword).map(ColorValue::Rgba))
+    }
+
+    // Computes the actual color, given the value of the `color` property
+    // which `currentColor` refers to.
+    pub fn resolve(&self, current_color: Color) -> Color {
+        match self {
+            &ColorValue::Rgba(color) => color,
+            &ColorValue::CurrentColor => current_color,
+            &ColorValue::System(system_color) => system_color.to_color()
+        }
+    }
+}
+
+system_colors! {
+    ActiveBorder,
+    ActiveCaption,
+    AppWorkspace,
+    Background,
+    ButtonFace,
+    ButtonHighlight,
+    ButtonShadow,
+    ButtonText,
+    CaptionText,
+    GrayText,
+    Highlight,
+    HighlightText,
+    InactiveBorder,
+    InactiveCaption,
+    InactiveCaptionText,
+    InfoBackground,
+    InfoText,
+    Menu,
+    MenuText,
+    Scrollbar,
+    ThreeDDarkShadow,
+    ThreeDFace,
+    ThreeDHighlight,
+    ThreeDLightShadow,
+    ThreeDShadow,
+    Window,
+    WindowFrame,
+    WindowText,
+    Canvas,
+    CanvasText,
+    LinkText,
+    VisitedText,
+    ActiveText,
+    ButtonBorder,
+    Field,
+    FieldText,
+    Mark,
+    MarkText,
+    SelectedItem,
+    SelectedItemText,
+    AccentColor,
+    AccentColorText
+}
+
+impl SystemColor {
+    // See https://www.w3.org/TR/css-color-4/#css-system-colors
+    pub fn from_name<T>(name: T) -> Option<Self>
+    where
+        T: AsRef<str>
+    {
+        match name.as_ref().to_ascii_lowercase().as_str() {
+            "activeborder" => Some(SystemColor::ActiveBorder),
+            "activecaption" => Some(SystemColor::ActiveCaption),
+            "appworkspace" => Some(SystemColor::AppWorkspace),
+            "background" => Some(SystemColor::Background),
+            "buttonface" => Some(SystemColor::ButtonFace),
+            "buttonhighlight" => Some(SystemColor::ButtonHighlight),
+            "buttonshadow" => Some(SystemColor::ButtonShadow),
+            "buttontext" => Some(SystemColor::ButtonText),
+            "captiontext" => Some(SystemColor::CaptionText),
+            "graytext" => Some(SystemColor::GrayText),
+            "highlight" => Some(SystemColor::Highlight),
+            "highlighttext" => Some(SystemColor::HighlightText),
+            "inactiveborder" => Some(SystemColor::InactiveBorder),
+            "inactivecaption" => Some(SystemColor::InactiveCaption),
+            "inactivecaptiontext" => Some(SystemColor::InactiveCaptionText),
+            "infobackground" => Some(SystemColor::InfoBackground),
+            "infotext" => Some(SystemColor::InfoText),
+            "menu" => Some(SystemColor::Menu),
+            "menutext" => Some(SystemColor::MenuText),
+            "scrollbar" => Some(SystemColor::Scrollbar),
+            "threeddarkshadow" => Some(SystemColor::ThreeDDarkShadow),
+            "threedface" => Some(SystemColor::ThreeDFace),
+            "threedhighlight" => Some(SystemColor::ThreeDHighlight),
+            "threedlightshadow" => Some(SystemColor::ThreeDLightShadow),
+            "threedshadow" => Some(SystemColor::ThreeDShadow),
+            "window" => Some(SystemColor::Window),
+            "windowframe" => Some(SystemColor::WindowFrame),
+            "windowtext" => Some(SystemColor::WindowText),
+            "canvas" => Some(SystemColor::Canvas),
+            "canvastext" => Some(SystemColor::CanvasText),
+            "linktext" => Some(SystemColor::LinkText),
+            "visitedtext" => Some(SystemColor::VisitedText),
+            "activetext" => Some(SystemColor::ActiveText),
+            "buttonborder" => Some(SystemColor::ButtonBorder),
+            "field" => Some(SystemColor::Field),
+            "fieldtext" => Some(SystemColor::FieldText),
+            "mark" => Some(SystemColor::Mark),
+            "marktext" => Some(SystemColor::MarkText),
+            "selecteditem" => Some(SystemColor::SelectedItem),
+            "selecteditemtext" => Some(SystemColor::SelectedItemText),
+            "accentcolor" => Some(SystemColor::AccentColor),
+            "accentcolortext" => Some(SystemColor::AccentColorText),
+            _ => None
+        }
+    }
+
+    // The name as spelled in the spec, which is how it's serialized.
+    pub fn name(&self) -> &'static str {
+        match self {
+            &SystemColor::ActiveBorder => "ActiveBorder",
+            &SystemColor::ActiveCaption => "ActiveCaption",
+            &SystemColor::AppWorkspace => "AppWorkspace",
+            &SystemColor::Background => "Background",
+            &SystemColor::ButtonFace => "ButtonFace",
+            &SystemColor::ButtonHighlight => "ButtonHighlight",
+            &SystemColor::ButtonShadow => "ButtonShadow",
+            &SystemColor::ButtonText => "ButtonText",
+            &SystemColor::CaptionText => "CaptionText",
+            &SystemColor::GrayText => "GrayText",
+            &SystemColor::Highlight => "Highlight",
+            &SystemColor::HighlightText => "HighlightText",
+            &SystemColor::InactiveBorder => "InactiveBorder",
+            &SystemColor::InactiveCaption => "InactiveCaption",
+            &SystemColor::InactiveCaptionText => "InactiveCaptionText",
+            &SystemColor::InfoBackground => "InfoBackground",
+            &SystemColor::InfoText => "InfoText",
+            &SystemColor::Menu => "Menu",
+            &SystemColor::MenuText => "MenuText",
+            &SystemColor::Scrollbar => "Scrollbar",
+            &SystemColor::ThreeDDarkShadow => "ThreeDDarkShadow",
+            &SystemColor::ThreeDFace => "ThreeDFace",
+            &SystemColor::ThreeDHighlight => "ThreeDHighlight",
+            &SystemColor::ThreeDLightShadow => "ThreeDLightShadow",
+            &SystemColor::ThreeDShadow => "ThreeDShadow",
+            &SystemColor::Window => "Window",
+            &SystemColor::WindowFrame => "WindowFrame",
+            &SystemColor::WindowText => "WindowText",
+            &SystemColor::Canvas => "Canvas",
+            &SystemColor::CanvasText => "CanvasText",
+            &SystemColor::LinkText => "LinkText",
+            &SystemColor::VisitedText => "VisitedText",
+            &SystemColor::ActiveText => "ActiveText",
+            &SystemColor::ButtonBorder => "ButtonBorder",
+            &SystemColor::Field => "Field",
+            &SystemColor::FieldText => "FieldText",
+            &SystemColor::Mark => "Mark",
+            &SystemColor::MarkText => "MarkText",
+            &SystemColor::SelectedItem => "SelectedItem",
+            &SystemColor::SelectedItemText => "SelectedItemText",
+            &SystemColor::AccentColor => "AccentColor",
+            &SystemColor::AccentColorText => "AccentColorText"
+        }
+    }
+
+    // Resolves against a light platform palette, since there's no platform
+    // theme to query.
+    pub fn to_color(&self) -> Color {
+        match self {
+            &SystemColor::ActiveBorder => Color::new([118, 118, 118, 255]),
+            &SystemColor::ActiveCaption => Color::new([255, 255, 255, 255]),
+            &SystemColor::AppWorkspace => Color::new([255, 255, 255, 255]),
+            &SystemColor::Background => Color::new([255, 255, 255, 255]),
+            &SystemColor::ButtonFace => Color::new([239, 239, 239, 255]),
+            &SystemColor::ButtonHighlight => Color::new([239, 239, 239, 255]),
+            &SystemColor::ButtonShadow => Color::new([239, 239, 239, 255]),
+            &SystemColor::ButtonText => Color::new([0, 0, 0, 255]),
+            &SystemColor::CaptionText => Color::new([0, 0, 0, 255]),
+            &SystemColor::GrayText => Color::new([109, 109, 109, 255]),
+            &SystemColor::Highlight => Color::new([51, 144, 255, 255]),
+            &SystemColor::HighlightText => Color::new([255, 255, 255, 255]),
+            &SystemColor::InactiveBorder => Color::new([255, 255, 255, 255]),
+            &SystemColor::InactiveCaption => Color::new([255, 255, 255, 255]),
+            &SystemColor::InactiveCaptionText => Color::new([128, 128, 128, 255]),
+            &SystemColor::InfoBackground => Color::new([255, 255, 255, 255]),
+            &SystemColor::InfoText => Color::new([0, 0, 0, 255]),
+            &SystemColor::Menu => Color::new([255, 255, 255, 255]),
+            &SystemColor::MenuText => Color::new([0, 0, 0, 255]),
+            &SystemColor::Scrollbar => Color::new([255, 255, 255, 255]),
+            &SystemColor::ThreeDDarkShadow => Color::new([118, 118, 118, 255]),
+            &SystemColor::ThreeDFace => Color::new([239, 239, 239, 255]),
+            &SystemColor::ThreeDHighlight => Color::new([118, 118, 118, 255]),
+            &SystemColor::ThreeDLightShadow => Color::new([118, 118, 118, 255]),
+            &SystemColor::ThreeDShadow => Color::new([118, 118, 118, 255]),
+            &SystemColor::Window => Color::new([255, 255, 255, 255]),
+            &SystemColor::WindowFrame => Color::new([118, 118, 118, 255]),
+            &SystemColor::WindowText => Color::new([0, 0, 0, 255]),
+            &SystemColor::Canvas => Color::new([255, 255, 255, 255]),
+            &SystemColor::CanvasText => Color::new([0, 0, 0, 255]),
+            &SystemColor::LinkText => Color::new([0, 0, 238, 255]),
+            &SystemColor::VisitedText => Color::new([85, 26, 139, 255]),
+            &SystemColor::ActiveText => Color::new([255, 0, 0, 255]),
+            &SystemColor::ButtonBorder => Color::new([118, 118, 118, 255]),
+            &SystemColor::Field => Color::new([255, 255, 255, 255]),
+            &SystemColor::FieldText => Color::new([0, 0, 0, 255]),
+            &SystemColor::Mark => Color::new([255, 255, 0, 255]),
+            &SystemColor::MarkText => Color::new([0, 0, 0, 255]),
+            &SystemColor::SelectedItem => Color::new([51, 144, 255, 255]),
+            &SystemColor::SelectedItemText => Color::new([255, 255, 255, 255]),
+            &SystemColor::AccentColor => Color::new([51, 144, 255, 255]),
+            &SystemColor::AccentColorText => Color::new([255, 255, 255, 255])
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.alpha == 255 {
+            f.write_str(&self.to_hex_string())
+        } else {
+            f.write_str(&self.to_rgba_string())
+        }
+    }
+}
+
+// Parses `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`, `hsl()`,
+// `hsla()` and named colors, with either comma or space separated arguments.
+impl FromStr for Color {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let color = if s.starts_with('#') {
+            parse_hex(&s[1..])
+        } else if let Some(open) = s.find('(') {
+            parse_function(&s[..open], &s[open + 1..])
+        } else {
+            Color::from_name(s)
+        };
+        color.ok_or(())
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_digit(16)) {
+        return None;
+    }
+
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    match hex.len() {
+        3 => Some(Color::new([digit(0)?, digit(1)?, digit(2)?, 255])),
+        4 => Some(Color::new([digit(0)?, digit(1)?, digit(2)?, digit(3)?])),
+        6 => Some(Color::new([pair(0)?, pair(2)?, pair(4)?, 255])),
+        8 => Some(Color::new([pair(0)?, pair(2)?, pair(4)?, pair(6)?])),
+        _ => None
+    }
+}
+
+fn parse_function(name: &str, rest: &str) -> Option<Color> {
+    let rest = rest.trim_right();
+    if !rest.ends_with(')') {
+        return None;
+    }
+
+    let args: Vec<&str> = rest[..rest.len() - 1]
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|arg| !arg.is_empty())
+        .collect();
+
+    let alpha = match args.len() {
+        3 => 1.0,
+        4 => parse_number_or_percentage(args[3])?,
+        _ => return None
+    };
+
+    match name.trim().to_ascii_lowercase().as_str() {
+        "rgb" | "rgba" => {
+            let channel = |arg: &str| parse_percentage(arg).or_else(|| parse_number(arg).map(|n| n / 255.0));
+            Some(Color::from_rgba_f32(channel(args[0])?, channel(args[1])?, channel(args[2])?, alpha))
+        }
+        "hsl" | "hsla" => Some(Color::from_hsla(Hsla {
+            hue: parse_number(args[0].trim_right_matches("deg"))?,
+            saturation: parse_percentage(args[1])?,
+            lightness: parse_percentage(args[2])?,
+            alpha: clamp_unit(alpha)
+        })),
+        _ => None
+    }
+}
+
+fn parse_number(value: &str) -> Option<f32> {
+    value.parse::<f32>().ok()
+}
+
+fn parse_percentage(value: &str) -> Option<f32> {
+    if value.ends_with('%') {
+        parse_number(&value[..value.len() - 1]).map(|n| n / 100.0)
+    } else {
+        None
+    }
+}
+
+fn parse_number_or_percentage(value: &str) -> Option<f32> {
+    parse_percentage(value).or_else(|| parse_number(value))
+}
+
+// Returns the red, green and blue components for a hue and chroma, before
+// adding the lightness or value offset.
+fn hue_to_rgb(hue: f32, chroma: f32) -> (f32, f32, f32) {
+    let sector = normalize_hue(hue) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x)
+    }
+}
+
+fn normalize_hue(hue: f32) -> f32 {
+    let hue = hue % 360.0;
+    if hue < 0.0 {
+        hue + 360.0
+    } else {
+        hue
+    }
+}
+
+fn clamp_unit(value: f32) -> f32 {
+    value.max(0.0).min(1.0)
+}
+
+fn to_u8(value: f32) -> u8 {
+    (clamp_unit(value) * 255.0).round() as u8
+}
@@ -0,0 +1,101 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use styles::types::CounterStyle;
+
+impl CounterStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "none" => Some(CounterStyle::None),
+            "decimal" => Some(CounterStyle::Decimal),
+            "decimal-leading-zero" => Some(CounterStyle::DecimalLeadingZero),
+            "lower-alpha" | "lower-latin" => Some(CounterStyle::LowerAlpha),
+            "upper-alpha" | "upper-latin" => Some(CounterStyle::UpperAlpha),
+            "lower-roman" => Some(CounterStyle::LowerRoman),
+            "upper-roman" => Some(CounterStyle::UpperRoman),
+            "disc" => Some(CounterStyle::Disc),
+            "circle" => Some(CounterStyle::Circle),
+            "square" => Some(CounterStyle::Square),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            &CounterStyle::None => "none",
+            &CounterStyle::Decimal => "decimal",
+            &CounterStyle::DecimalLeadingZero => "decimal-leading-zero",
+            &CounterStyle::LowerAlpha => "lower-alpha",
+            &CounterStyle::UpperAlpha => "upper-alpha",
+            &CounterStyle::LowerRoman => "lower-roman",
+            &CounterStyle::UpperRoman => "upper-roman",
+            &CounterStyle::Disc => "disc",
+            &CounterStyle::Circle => "circle",
+            &CounterStyle::Square => "square"
+        }
+    }
+
+    // Values out of the range of a style fall back to decimal, as specified.
+    pub fn format(&self, value: i32) -> String {
+        match self {
+            &CounterStyle::None => String::new(),
+            &CounterStyle::Disc => "\u{2022}".to_owned(),
+            &CounterStyle::Circle => "\u{25e6}".to_owned(),
+            &CounterStyle::Square => "\u{25aa}".to_owned(),
+            &CounterStyle::DecimalLeadingZero if value >= 0 => format!("{:02}", value),
+            &CounterStyle::DecimalLeadingZero => format!("-{:02}", -i64::from(value)),
+            &CounterStyle::LowerAlpha if value > 0 => alphabetic(value),
+            &CounterStyle::UpperAlpha if value > 0 => alphabetic(value).to_uppercase(),
+            &CounterStyle::LowerRoman if value > 0 && value < 4000 => roman(value).to_lowercase(),
+            &CounterStyle::UpperRoman if value > 0 && value < 4000 => roman(value),
+            _ => value.to_string()
+        }
+    }
+}
+
+// Bijective base 26, so that 1 is `a`, 26 is `z` and 27 is `aa`.
+fn alphabetic(value: i32) -> String {
+    let mut value = value;
+    let mut letters = vec![];
+    while value > 0 {
+        value -= 1;
+        letters.push((b'a' + (value % 26) as u8) as char);
+        value /= 26;
+    }
+    letters.into_iter().rev().collect()
+}
+
+fn roman(value: i32) -> String {
+    const NUMERALS: &[(i32, &str)] = &[
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I")
+    ];
+    let mut value = value;
+    let mut numeral = String::new();
+    for &(step, letters) in NUMERALS {
+        while value >= step {
+            numeral.push_str(letters);
+            value -= step;
+        }
+    }
+    numeral
+}
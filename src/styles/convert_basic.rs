@@ -11,11 +11,29 @@ specific language governing permissions and limitations under the License.
 
 use std::borrow::Cow;
 
-use styles::types::{InlineRules, SpecificFontName, StyleSelector, Stylesheet};
+use styles::index::SelectorIndex;
+use styles::usage::UsageTracker;
+use styles::types::{
+    ContentString,
+    CounterName,
+    CustomPropertyName,
+    InlineRules,
+    PropertyName,
+    SpecificFontName,
+    StyleOrigin,
+    StyleSelector,
+    Stylesheet,
+    UnparsedValue
+};
 
 impl From<InlineRules> for Stylesheet {
     fn from(rules: InlineRules) -> Self {
-        Stylesheet { rules }
+        Stylesheet {
+            index: SelectorIndex::new(&rules),
+            origins: vec![StyleOrigin::default(); rules.len()],
+            rules,
+            usage: UsageTracker::default()
+        }
     }
 }
 
@@ -42,3 +60,63 @@ impl From<String> for SpecificFontName {
         SpecificFontName(Cow::from(string))
     }
 }
+
+impl From<&'static str> for CustomPropertyName {
+    fn from(string: &'static str) -> Self {
+        CustomPropertyName(Cow::from(string))
+    }
+}
+
+impl From<String> for CustomPropertyName {
+    fn from(string: String) -> Self {
+        CustomPropertyName(Cow::from(string))
+    }
+}
+
+impl From<&'static str> for PropertyName {
+    fn from(string: &'static str) -> Self {
+        PropertyName(Cow::from(string))
+    }
+}
+
+impl From<String> for PropertyName {
+    fn from(string: String) -> Self {
+        PropertyName(Cow::from(string))
+    }
+}
+
+impl From<&'static str> for UnparsedValue {
+    fn from(string: &'static str) -> Self {
+        UnparsedValue(Cow::from(string))
+    }
+}
+
+impl From<String> for UnparsedValue {
+    fn from(string: String) -> Self {
+        UnparsedValue(Cow::from(string))
+    }
+}
+
+impl From<&'static str> for ContentString {
+    fn from(string: &'static str) -> Self {
+        ContentString(Cow::from(string))
+    }
+}
+
+impl From<String> for ContentString {
+    fn from(string: String) -> Self {
+        ContentString(Cow::from(string))
+    }
+}
+
+impl From<&'static str> for CounterName {
+    fn from(string: &'static str) -> Self {
+        CounterName(Cow::from(string))
+    }
+}
+
+impl From<String> for CounterName {
+    fn from(string: String) -> Self {
+        CounterName(Cow::from(string))
+    }
+}
@@ -14,20 +14,26 @@ specific language governing permissions and limitations under the License.
 use std::iter::FromIterator;
 
 use servo_css_parser::cssparser::ToCss as cssparser_ToCss;
+use servo_css_parser::cssparser::{ParseError, Parser, ParserInput, Token};
+use servo_css_parser::parse;
 use servo_css_parser::selectors::parser::{Selector, SelectorList};
-use servo_css_parser::style::properties::{longhands, PropertyDeclaration};
+use servo_css_parser::style::properties::{longhands, CSSWideKeyword, DeclaredValueOwned, PropertyDeclaration};
 use servo_css_parser::style::properties::declaration_block::PropertyDeclarationBlock;
 use servo_css_parser::style::servo::selector_parser::SelectorImpl;
 use servo_css_parser::style::stylesheets::CssRule;
 use servo_css_parser::style::values::{computed, generics, specified, RGBA};
 use servo_css_parser::style_traits;
 use servo_css_parser::style_traits::values::ToCss as style_traits_ToCss;
-use servo_css_parser::types::ServoStylesheet;
+use servo_css_parser::types::{MediaList, Origin, QuirksMode, ServoStylesheet, Url};
 
 use types::{
     self,
+    ClassNames,
+    ConversionWarning,
     FlexStyle,
     SmallVec,
+    SourceLocation,
+    SourceMap,
     StyleDeclaration,
     StyleDeclarations,
     StyleRule,
@@ -39,37 +45,136 @@ use types::{
 
 impl From<ServoStylesheet> for Stylesheet {
     fn from(stylesheet: ServoStylesheet) -> Self {
-        let guard = stylesheet.shared_lock.read();
-        let rules = &stylesheet.contents.rules.read_with(&guard).0;
-        Stylesheet::from(SmallVec::from_vec(
-            rules
-                .iter()
-                .filter_map(|rule| {
-                    match rule {
-                        &CssRule::Style(ref style) => {
-                            let rule = style.read_with(&guard);
-                            let selectors = &rule.selectors;
-                            let block = rule.block.read_with(&guard);
-                            Some((selectors, block).into())
-                        }
-                        &CssRule::Namespace(..)
-                        | &CssRule::Import(..)
-                        | &CssRule::Media(..)
-                        | &CssRule::FontFace(..)
-                        | &CssRule::FontFeatureValues(..)
-                        | &CssRule::CounterStyle(..)
-                        | &CssRule::Viewport(..)
-                        | &CssRule::Keyframes(..)
-                        | &CssRule::Supports(..)
-                        | &CssRule::Page(..)
-                        | &CssRule::Document(..) => {
-                            // Not supported yet.
-                            None
+        convert_stylesheet(&stylesheet, None, None)
+    }
+}
+
+impl Stylesheet {
+    // Same as the `From` conversion, but also reports every declaration which
+    // was dropped because it can't be represented.
+    pub fn from_servo_with_warnings(stylesheet: ServoStylesheet) -> (Self, Vec<ConversionWarning>) {
+        let mut warnings = vec![];
+        let converted = convert_stylesheet(&stylesheet, Some(&mut warnings), None);
+        (converted, warnings)
+    }
+
+    // Same as the `From` conversion, but also keeps where each rule was found.
+    pub fn from_servo_with_source_map(stylesheet: ServoStylesheet) -> (Self, SourceMap) {
+        let mut source_map = SourceMap::default();
+        let converted = convert_stylesheet(&stylesheet, None, Some(&mut source_map));
+        (converted, source_map)
+    }
+
+    // Both `from_servo_with_warnings` and `from_servo_with_source_map`, in a
+    // single conversion.
+    pub fn from_servo_with_warnings_and_source_map(stylesheet: ServoStylesheet) -> (Self, Vec<ConversionWarning>, SourceMap) {
+        let mut warnings = vec![];
+        let mut source_map = SourceMap::default();
+        let converted = convert_stylesheet(&stylesheet, Some(&mut warnings), Some(&mut source_map));
+        (converted, warnings, source_map)
+    }
+
+    // Same as the `From` conversion, but with class names made local to the
+    // stylesheet, as with CSS modules. See `Stylesheet::hash_class_names`.
+    pub fn from_servo_with_class_names(stylesheet: ServoStylesheet) -> (Self, ClassNames) {
+        let mut converted = convert_stylesheet(&stylesheet, None, None);
+        let class_names = converted.hash_class_names();
+        (converted, class_names)
+    }
+}
+
+fn convert_stylesheet(
+    stylesheet: &ServoStylesheet,
+    mut warnings: Option<&mut Vec<ConversionWarning>>,
+    mut source_map: Option<&mut SourceMap>
+) -> Stylesheet {
+    let guard = stylesheet.shared_lock.read();
+    let rules = &stylesheet.contents.rules.read_with(&guard).0;
+    Stylesheet::from(SmallVec::from_vec(
+        rules
+            .iter()
+            .filter_map(|rule| {
+                match rule {
+                    &CssRule::Style(ref style) => {
+                        let rule = style.read_with(&guard);
+                        let selectors = StyleSelectors::from_iter(rule.selectors.0.iter().map(|v| v.into()));
+                        // Servo's lines start at 0, and its columns at 1.
+                        let location = SourceLocation {
+                            line: rule.source_location.line + 1,
+                            column: rule.source_location.column
+                        };
+                        let declarations = convert_declarations(&rule.block.read_with(&guard), |declaration| {
+                            if let Some(ref mut warnings) = warnings {
+                                warnings.push(dropped_declaration(&selectors, location, declaration));
+                            }
+                        });
+                        if let Some(ref mut source_map) = source_map {
+                            source_map.push(selectors.clone(), location);
                         }
+                        Some(StyleRule { selectors, declarations })
                     }
-                })
-                .collect()
-        ))
+                    &CssRule::Namespace(..)
+                    | &CssRule::Import(..)
+                    | &CssRule::Media(..)
+                    | &CssRule::FontFace(..)
+                    | &CssRule::FontFeatureValues(..)
+                    | &CssRule::CounterStyle(..)
+                    | &CssRule::Viewport(..)
+                    | &CssRule::Keyframes(..)
+                    | &CssRule::Supports(..)
+                    | &CssRule::Page(..)
+                    | &CssRule::Document(..) => {
+                        // Not supported yet.
+                        None
+                    }
+                }
+            })
+            .collect()
+    ))
+}
+
+// Converts every declaration of a block, calling `dropped` with those which
+// can't be represented.
+fn convert_declarations<F>(block: &PropertyDeclarationBlock, mut dropped: F) -> StyleDeclarations
+where
+    F: FnMut(&PropertyDeclaration)
+{
+    let mut declarations = StyleDeclarations::default();
+    for servo_declaration in block.declarations().iter() {
+        let declaration = match StyleDeclaration::from(servo_declaration).into_known() {
+            Some(declaration) => declaration,
+            None => {
+                dropped(servo_declaration);
+                continue;
+            }
+        };
+        // All longhands of a shorthand containing `var()` references carry the
+        // same unparsed shorthand, which only needs to be substituted once.
+        if let StyleDeclaration::WithVariables(..) = declaration {
+            if declarations.last() == Some(&declaration) {
+                continue;
+            }
+        }
+        declarations.push(declaration);
+    }
+    declarations
+}
+
+fn dropped_declaration(selectors: &StyleSelectors, location: SourceLocation, declaration: &PropertyDeclaration) -> ConversionWarning {
+    let property = declaration.id().to_css_string();
+    // Custom properties are always supported, only their CSS-wide keywords
+    // aren't.
+    let reason = if types::PropertyId::from_name(&property).is_some() || property.starts_with("--") {
+        types::WarningReason::UnsupportedValue
+    } else {
+        types::WarningReason::UnsupportedProperty
+    };
+    ConversionWarning {
+        selectors: selectors.clone(),
+        location,
+        property,
+        value: declaration.to_css_string(),
+        reason
     }
 }
 
@@ -77,8 +182,53 @@ impl<'a> From<(&'a SelectorList<SelectorImpl>, &'a PropertyDeclarationBlock)> fo
     fn from((selectors, block): (&SelectorList<SelectorImpl>, &PropertyDeclarationBlock)) -> Self {
         StyleRule {
             selectors: StyleSelectors::from_iter(selectors.0.iter().map(|v| v.into())),
-            declarations: StyleDeclarations::from_iter(block.declarations().iter().map(|v| v.into()))
+            declarations: block.into()
+        }
+    }
+}
+
+impl<'a> From<&'a PropertyDeclarationBlock> for StyleDeclarations {
+    fn from(block: &PropertyDeclarationBlock) -> Self {
+        convert_declarations(block, |_| {})
+    }
+}
+
+impl StyleDeclarations {
+    // Parses a single `property: value` pair, e.g. after `var()` substitution.
+    pub fn parse_declaration<T, U>(property: T, value: U) -> Self
+    where
+        T: AsRef<str>,
+        U: AsRef<str>
+    {
+        let url = Url::parse("about:blank").unwrap();
+        let origin = Origin::Author;
+        let qm = QuirksMode::NoQuirks;
+        let media = MediaList::empty();
+
+        let css = format!("* {{ {}: {} }}", property.as_ref(), value.as_ref());
+        let stylesheet = parse(&css, url, origin, qm, media);
+
+        let guard = stylesheet.shared_lock.read();
+        let rules = &stylesheet.contents.rules.read_with(&guard).0;
+        rules
+            .iter()
+            .filter_map(|rule| match rule {
+                &CssRule::Style(ref style) => Some(StyleDeclarations::from(&*style.read_with(&guard).block.read_with(&guard))),
+                _ => None
+            })
+            .next()
+            .unwrap_or_default()
+    }
+}
+
+fn to_color_value(color: &specified::Color) -> Option<types::ColorValue> {
+    match color {
+        &specified::Color::Numeric { parsed: RGBA { red, green, blue, alpha }, .. } => {
+            Some(types::ColorValue::Rgba(types::Color::new([red, green, blue, alpha])))
         }
+        &specified::Color::CurrentColor => Some(types::ColorValue::CurrentColor),
+        // System colors, which Servo keeps as keywords.
+        _ => types::SystemColor::from_name(color.to_css_string()).map(types::ColorValue::System)
     }
 }
 
@@ -92,36 +242,21 @@ impl<'a> From<&'a PropertyDeclaration> for StyleDeclaration {
     fn from(declaration: &PropertyDeclaration) -> Self {
         match declaration {
             // Theme rules
-            &PropertyDeclaration::BackgroundColor(ref value) => {
-                use self::specified::Color::Numeric;
-                match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::BackgroundColor] match value {
-                    &Numeric { parsed: RGBA { red, green, blue, alpha }, .. } => types::Color::new([red, green, blue, alpha])
-                })
-            }
-            &PropertyDeclaration::BorderTopColor(ref value) => {
-                use self::specified::Color::Numeric;
-                match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::BorderTopColor] match value {
-                    &Numeric { parsed: RGBA { red, green, blue, alpha }, .. } => types::Color::new([red, green, blue, alpha])
-                })
-            }
-            &PropertyDeclaration::BorderLeftColor(ref value) => {
-                use self::specified::Color::Numeric;
-                match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::BorderLeftColor] match value {
-                    &Numeric { parsed: RGBA { red, green, blue, alpha }, .. } => types::Color::new([red, green, blue, alpha])
-                })
-            }
-            &PropertyDeclaration::BorderBottomColor(ref value) => {
-                use self::specified::Color::Numeric;
-                match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::BorderBottomColor] match value {
-                    &Numeric { parsed: RGBA { red, green, blue, alpha }, .. } => types::Color::new([red, green, blue, alpha])
-                })
-            }
-            &PropertyDeclaration::BorderRightColor(ref value) => {
-                use self::specified::Color::Numeric;
-                match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::BorderRightColor] match value {
-                    &Numeric { parsed: RGBA { red, green, blue, alpha }, .. } => types::Color::new([red, green, blue, alpha])
-                })
-            }
+            &PropertyDeclaration::BackgroundColor(ref value) => to_color_value(value)
+                .map(|v| StyleDeclaration::Theme(ThemeStyle::BackgroundColor(v)))
+                .unwrap_or(StyleDeclaration::Unknown),
+            &PropertyDeclaration::BorderTopColor(ref value) => to_color_value(value)
+                .map(|v| StyleDeclaration::Theme(ThemeStyle::BorderTopColor(v)))
+                .unwrap_or(StyleDeclaration::Unknown),
+            &PropertyDeclaration::BorderLeftColor(ref value) => to_color_value(value)
+                .map(|v| StyleDeclaration::Theme(ThemeStyle::BorderLeftColor(v)))
+                .unwrap_or(StyleDeclaration::Unknown),
+            &PropertyDeclaration::BorderBottomColor(ref value) => to_color_value(value)
+                .map(|v| StyleDeclaration::Theme(ThemeStyle::BorderBottomColor(v)))
+                .unwrap_or(StyleDeclaration::Unknown),
+            &PropertyDeclaration::BorderRightColor(ref value) => to_color_value(value)
+                .map(|v| StyleDeclaration::Theme(ThemeStyle::BorderRightColor(v)))
+                .unwrap_or(StyleDeclaration::Unknown),
             &PropertyDeclaration::BorderTopStyle(ref value) => {
                 use self::specified::BorderStyle::{Dashed, Dotted, Double, Groove, Hidden, Inset, None, Outset, Ridge, Solid};
                 match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::BorderTopStyle] match value {
@@ -224,31 +359,25 @@ impl<'a> From<&'a PropertyDeclaration> for StyleDeclaration {
                     &Cursor(ZoomOut) => types::Cursor::ZoomOut
                 })
             }
-            &PropertyDeclaration::Color(ref value) => {
-                use self::specified::Color::Numeric;
-                use self::specified::ColorPropertyValue;
-                match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::Color] match value {
-                    &ColorPropertyValue(Numeric { parsed: RGBA { red, green, blue, alpha }, .. }) => types::Color::new([red, green, blue, alpha])
-                })
-            }
+            &PropertyDeclaration::Color(specified::ColorPropertyValue(ref value)) => to_color_value(value)
+                .map(|v| StyleDeclaration::Theme(ThemeStyle::Color(v)))
+                .unwrap_or(StyleDeclaration::Unknown),
             &PropertyDeclaration::Opacity(ref value) => {
                 use self::specified::Opacity;
                 match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::Opacity] match value {
-                    value @ &Opacity { .. } => (value.to_css_string().parse::<f32>().unwrap() * 100.0) as u32
+                    &Opacity(ref number) => number.get().max(0.0).min(1.0)
                 })
             }
             &PropertyDeclaration::BoxShadow(ref value) => {
                 use self::generics::effects::{BoxShadow, SimpleShadow};
                 use self::longhands::box_shadow::SpecifiedValue;
-                use self::specified::color::Color::Numeric;
                 use self::specified::color::RGBAColor;
                 match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::BoxShadow] match value {
                     &SpecifiedValue(ref vec) => vec.iter().map(|&BoxShadow { base: SimpleShadow { ref color, ref horizontal, ref vertical, ref blur }, ref spread, inset }| {
                         types::BoxShadow {
-                            color: color.as_ref().and_then(|&RGBAColor(ref c)| match c {
-                                &Numeric { parsed: RGBA { red, green, blue, alpha }, .. } => Some(types::Color::new([red, green, blue, alpha])),
-                                _ => None
-                            }),
+                            color: color.as_ref()
+                                .and_then(|&RGBAColor(ref c)| to_color_value(c))
+                                .unwrap_or(types::ColorValue::CurrentColor),
                             horizontal: match horizontal {
                                 &specified::Length::NoCalc(specified::NoCalcLength::Absolute(specified::AbsoluteLength::Px(px))) => types::StyleUnit::Point(px.into()),
                                 _ => types::StyleUnit::UndefinedValue,
@@ -273,15 +402,13 @@ impl<'a> From<&'a PropertyDeclaration> for StyleDeclaration {
             &PropertyDeclaration::TextShadow(ref value) => {
                 use self::generics::effects::SimpleShadow;
                 use self::longhands::text_shadow::SpecifiedValue;
-                use self::specified::color::Color::Numeric;
                 use self::specified::color::RGBAColor;
                 match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::TextShadow] match value {
                     &SpecifiedValue(ref vec) => vec.iter().map(|&SimpleShadow { ref color, ref horizontal, ref vertical, ref blur }| {
                         types::TextShadow {
-                            color: color.as_ref().and_then(|&RGBAColor(ref c)| match c {
-                                &Numeric { parsed: RGBA { red, green, blue, alpha }, .. } => Some(types::Color::new([red, green, blue, alpha])),
-                                _ => None
-                            }),
+                            color: color.as_ref()
+                                .and_then(|&RGBAColor(ref c)| to_color_value(c))
+                                .unwrap_or(types::ColorValue::CurrentColor),
                             horizontal: match horizontal {
                                 &specified::Length::NoCalc(specified::NoCalcLength::Absolute(specified::AbsoluteLength::Px(px))) => types::StyleUnit::Point(px.into()),
                                 _ => types::StyleUnit::UndefinedValue,
@@ -349,9 +476,20 @@ impl<'a> From<&'a PropertyDeclaration> for StyleDeclaration {
             &PropertyDeclaration::FontSize(ref value) => {
                 use self::computed::Percentage;
                 use self::specified::{AbsoluteLength, LengthOrPercentage, NoCalcLength};
-                use self::specified::FontSize::{Larger, Length, Smaller, System};
+                use self::specified::FontSize::{Keyword, Larger, Length, Smaller, System};
+                use self::specified::font::{KeywordInfo, KeywordSize};
                 match_value_into_type!(StyleDeclaration::Theme[ThemeStyle::FontSize] match value {
                     &System(..) => types::FontSize::System,
+                    &Keyword(KeywordInfo { kw, .. }) => types::FontSize::Keyword(match kw {
+                        KeywordSize::XXSmall => types::FontSizeKeyword::XXSmall,
+                        KeywordSize::XSmall => types::FontSizeKeyword::XSmall,
+                        KeywordSize::Small => types::FontSizeKeyword::Small,
+                        KeywordSize::Medium => types::FontSizeKeyword::Medium,
+                        KeywordSize::Large => types::FontSizeKeyword::Large,
+                        KeywordSize::XLarge => types::FontSizeKeyword::XLarge,
+                        KeywordSize::XXLarge => types::FontSizeKeyword::XXLarge,
+                        KeywordSize::XXXLarge => types::FontSizeKeyword::XXXLarge
+                    }),
                     &Smaller => types::FontSize::Smaller,
                     &Larger => types::FontSize::Larger,
                     &Length(LengthOrPercentage::Length(NoCalcLength::Absolute(AbsoluteLength::Px(px)))) => types::FontSize::Length(types::StyleUnit::Point(px.into())),
@@ -391,6 +529,19 @@ impl<'a> From<&'a PropertyDeclaration> for StyleDeclaration {
                     &Visible => types::Visibility::Visible
                 })
             }
+            // Generated content is converted from its CSS text, see `Content::parse`.
+            &PropertyDeclaration::Content(ref value) => types::Content::parse(&value.to_css_string())
+                .map(|content| StyleDeclaration::Theme(ThemeStyle::Content(content)))
+                .unwrap_or(StyleDeclaration::Unknown),
+            &PropertyDeclaration::Quotes(ref value) => types::Quotes::parse(&value.to_css_string())
+                .map(|quotes| StyleDeclaration::Theme(ThemeStyle::Quotes(quotes)))
+                .unwrap_or(StyleDeclaration::Unknown),
+            &PropertyDeclaration::CounterIncrement(ref value) => types::Counter::parse_list(&value.to_css_string(), 1)
+                .map(|counters| StyleDeclaration::Theme(ThemeStyle::CounterIncrement(counters)))
+                .unwrap_or(StyleDeclaration::Unknown),
+            &PropertyDeclaration::CounterReset(ref value) => types::Counter::parse_list(&value.to_css_string(), 0)
+                .map(|counters| StyleDeclaration::Theme(ThemeStyle::CounterReset(counters)))
+                .unwrap_or(StyleDeclaration::Unknown),
             // Layout rules
             &PropertyDeclaration::AlignContent(ref value) => {
                 use self::longhands::align_content::computed_value::T::{Center, FlexEnd, FlexStart, SpaceAround, SpaceBetween, Stretch};
@@ -738,7 +889,181 @@ impl<'a> From<&'a PropertyDeclaration> for StyleDeclaration {
                     &Relative => types::PositionType::Relative
                 })
             }
+            // Custom properties and variables
+            &PropertyDeclaration::Custom(ref name, ref value) => match value {
+                &DeclaredValueOwned::Value(ref value) => StyleDeclaration::Custom(types::CustomProperty {
+                    name: types::CustomPropertyName::from(format!("--{}", name)),
+                    value: types::UnparsedValue::from(value.to_css_string().trim().to_owned())
+                }),
+                _ => StyleDeclaration::Unknown
+            },
+            &PropertyDeclaration::WithVariables(id, ref unparsed) => {
+                let name = unparsed.from_shorthand.map(|shorthand| shorthand.name()).unwrap_or_else(|| id.name());
+                StyleDeclaration::WithVariables(types::UnparsedDeclaration {
+                    property: types::PropertyName::from(name),
+                    value: types::UnparsedValue::from(unparsed.css.trim().to_owned())
+                })
+            }
+            // CSS-wide keywords
+            &PropertyDeclaration::CSSWideKeyword(id, keyword) => match types::PropertyId::from_name(id.name()) {
+                Some(id) => StyleDeclaration::WideKeyword(
+                    id,
+                    match keyword {
+                        CSSWideKeyword::Initial => types::CssWideKeyword::Initial,
+                        CSSWideKeyword::Inherit => types::CssWideKeyword::Inherit,
+                        CSSWideKeyword::Unset => types::CssWideKeyword::Unset
+                    }
+                ),
+                None => StyleDeclaration::Unknown
+            },
             _ => StyleDeclaration::Unknown
         }
     }
 }
+
+// Generated content is parsed from its CSS text, as serialized by Servo,
+// since its specified values differ between Servo and Gecko builds of the
+// parser. Values that can't be represented parse to `None`.
+
+type ParseResult<'i, T> = Result<Option<T>, ParseError<'i, ()>>;
+
+fn parse_value<F, T>(css: &str, parse: F) -> Option<T>
+where
+    F: for<'i, 't> FnOnce(&mut Parser<'i, 't>) -> ParseResult<'i, T>
+{
+    let mut input = ParserInput::new(css);
+    let mut parser = Parser::new(&mut input);
+    parser.parse_entirely(parse).ok().and_then(|value| value)
+}
+
+impl types::Content {
+    pub fn parse(css: &str) -> Option<Self> {
+        parse_value(css, content)
+    }
+}
+
+impl types::Quotes {
+    pub fn parse(css: &str) -> Option<Self> {
+        parse_value(css, quotes)
+    }
+}
+
+impl types::Counter {
+    // Parses the value of `counter-increment` or `counter-reset`, where
+    // counters without a value get the given default.
+    pub fn parse_list(css: &str, default: i32) -> Option<types::InlineCounters> {
+        parse_value(css, |parser| counter_list(parser, default))
+    }
+}
+
+fn content<'i, 't>(parser: &mut Parser<'i, 't>) -> ParseResult<'i, types::Content> {
+    if parser.try(|parser| parser.expect_ident_matching("normal")).is_ok() {
+        return Ok(Some(types::Content::Normal));
+    }
+    if parser.try(|parser| parser.expect_ident_matching("none")).is_ok() {
+        return Ok(Some(types::Content::None));
+    }
+
+    let mut items = types::InlineContentItems::new();
+    loop {
+        match content_item(parser)? {
+            Some(item) => items.push(item),
+            None => return Ok(None)
+        }
+        if parser.is_exhausted() {
+            return Ok(Some(types::Content::Items(items)));
+        }
+    }
+}
+
+fn content_item<'i, 't>(parser: &mut Parser<'i, 't>) -> ParseResult<'i, types::ContentItem> {
+    use self::types::ContentItem;
+
+    let token = parser.next()?.clone();
+    match token {
+        Token::QuotedString(ref string) => Ok(Some(ContentItem::String(types::ContentString::from(string.to_string())))),
+        Token::Ident(ref ident) => Ok(match ident.to_lowercase().as_str() {
+            "open-quote" => Some(ContentItem::OpenQuote),
+            "close-quote" => Some(ContentItem::CloseQuote),
+            "no-open-quote" => Some(ContentItem::NoOpenQuote),
+            "no-close-quote" => Some(ContentItem::NoCloseQuote),
+            _ => None
+        }),
+        Token::Function(ref name) => {
+            let name = name.to_lowercase();
+            parser.parse_nested_block(|parser| {
+                let item = content_function(&name, parser)?;
+                parser.expect_exhausted()?;
+                Ok(item)
+            })
+        }
+        _ => Ok(None)
+    }
+}
+
+// The arguments of `counter()`, `counters()` and `attr()`.
+fn content_function<'i, 't>(name: &str, parser: &mut Parser<'i, 't>) -> ParseResult<'i, types::ContentItem> {
+    use self::types::ContentItem;
+
+    match name {
+        "counter" => {
+            let counter = types::CounterName::from(parser.expect_ident()?.to_string());
+            let style = counter_style(parser)?;
+            Ok(style.map(|style| ContentItem::Counter(counter, style)))
+        }
+        "counters" => {
+            let counter = types::CounterName::from(parser.expect_ident()?.to_string());
+            parser.expect_comma()?;
+            let separator = types::ContentString::from(parser.expect_string()?.to_string());
+            let style = counter_style(parser)?;
+            Ok(style.map(|style| ContentItem::Counters(counter, separator, style)))
+        }
+        "attr" => Ok(Some(ContentItem::Attr(types::ContentString::from(parser.expect_ident()?.to_string())))),
+        _ => Ok(None)
+    }
+}
+
+// The optional style ending the arguments of `counter()` and `counters()`.
+fn counter_style<'i, 't>(parser: &mut Parser<'i, 't>) -> ParseResult<'i, types::CounterStyle> {
+    if parser.is_exhausted() {
+        return Ok(Some(types::CounterStyle::Decimal));
+    }
+    parser.expect_comma()?;
+    let name = parser.expect_ident()?.to_string();
+    Ok(types::CounterStyle::from_name(&name))
+}
+
+fn quotes<'i, 't>(parser: &mut Parser<'i, 't>) -> ParseResult<'i, types::Quotes> {
+    if parser.try(|parser| parser.expect_ident_matching("auto")).is_ok() {
+        return Ok(Some(types::Quotes::Auto));
+    }
+    if parser.try(|parser| parser.expect_ident_matching("none")).is_ok() {
+        return Ok(Some(types::Quotes::None));
+    }
+
+    let mut pairs = types::InlineQuotePairs::new();
+    loop {
+        let open = types::ContentString::from(parser.expect_string()?.to_string());
+        let close = types::ContentString::from(parser.expect_string()?.to_string());
+        pairs.push(types::QuotePair { open, close });
+        if parser.is_exhausted() {
+            return Ok(Some(types::Quotes::Pairs(pairs)));
+        }
+    }
+}
+
+fn counter_list<'i, 't>(parser: &mut Parser<'i, 't>, default: i32) -> ParseResult<'i, types::InlineCounters> {
+    let mut counters = types::InlineCounters::new();
+    if parser.try(|parser| parser.expect_ident_matching("none")).is_ok() {
+        return Ok(Some(counters));
+    }
+
+    loop {
+        let name = types::CounterName::from(parser.expect_ident()?.to_string());
+        let value = parser.try(|parser| parser.expect_integer()).unwrap_or(default);
+        counters.push(types::Counter { name, value });
+        if parser.is_exhausted() {
+            return Ok(Some(counters));
+        }
+    }
+}
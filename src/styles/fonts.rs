@@ -0,0 +1,129 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use styles::types::{FontSize, FontSizeKeyword, FontWeight, StyleUnit};
+
+// The font size used when neither the element nor any ancestor specify one.
+pub const MEDIUM_FONT_SIZE: f32 = 16.0;
+
+// Ratios of the absolute-size keywords, from `xx-small` to `xxx-large`, to
+// the medium font size.
+// See https://drafts.csswg.org/css-fonts-4/#absolute-size-mapping
+const FONT_SIZE_SCALE: [f32; 8] = [3.0 / 5.0, 3.0 / 4.0, 8.0 / 9.0, 1.0, 6.0 / 5.0, 3.0 / 2.0, 2.0, 3.0];
+
+// Used for `smaller` and `larger` when the parent size isn't one of the
+// absolute-size keywords.
+const FONT_SIZE_RATIO: f32 = 1.2;
+
+// Point sizes of the absolute-size keywords, from `xx-small` to `xxx-large`.
+// The `medium` size is also used for the system font size.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct FontSizeTable {
+    pub sizes: [f32; 8]
+}
+
+impl Default for FontSizeTable {
+    fn default() -> Self {
+        FontSizeTable::from_medium(MEDIUM_FONT_SIZE)
+    }
+}
+
+impl FontSizeTable {
+    // Scales the CSS keyword ratios to the given medium font size.
+    pub fn from_medium(medium: f32) -> Self {
+        let mut sizes = FONT_SIZE_SCALE;
+        sizes.iter_mut().for_each(|size| *size *= medium);
+        FontSizeTable { sizes }
+    }
+
+    pub fn size(&self, keyword: FontSizeKeyword) -> f32 {
+        self.sizes[keyword as usize]
+    }
+
+    pub fn medium(&self) -> f32 {
+        self.size(FontSizeKeyword::Medium)
+    }
+
+    fn smaller(&self, parent_size: f32) -> f32 {
+        match self.sizes.iter().rev().find(|&&size| size < parent_size - 0.5) {
+            Some(&size) if self.is_keyword_size(parent_size) => size,
+            _ => parent_size / FONT_SIZE_RATIO
+        }
+    }
+
+    fn larger(&self, parent_size: f32) -> f32 {
+        match self.sizes.iter().find(|&&size| size > parent_size + 0.5) {
+            Some(&size) if self.is_keyword_size(parent_size) => size,
+            _ => parent_size * FONT_SIZE_RATIO
+        }
+    }
+
+    fn is_keyword_size(&self, size: f32) -> bool {
+        self.sizes.iter().any(|keyword_size| (keyword_size - size).abs() < 0.5)
+    }
+}
+
+impl FontSize {
+    // Computes an absolute font size, given the computed font size of the
+    // parent in points and the sizes to use for keywords.
+    pub fn resolve(&self, parent_size: f32, table: &FontSizeTable) -> FontSize {
+        let points = match self {
+            &FontSize::System => table.medium(),
+            &FontSize::Keyword(keyword) => table.size(keyword),
+            &FontSize::Smaller => table.smaller(parent_size),
+            &FontSize::Larger => table.larger(parent_size),
+            &FontSize::Length(StyleUnit::Percent(percent)) => parent_size * percent.into_inner() / 100.0,
+            &font_size => return font_size
+        };
+        FontSize::Length(StyleUnit::Point(points.into()))
+    }
+
+    // The size in points of a computed font size.
+    pub fn to_points(&self) -> f32 {
+        match self {
+            &FontSize::Length(StyleUnit::Point(points)) => points.into_inner(),
+            _ => MEDIUM_FONT_SIZE
+        }
+    }
+}
+
+impl FontWeight {
+    // Computes an absolute font weight, given the computed font weight of
+    // the parent. Relative weights are resolved, anything else is kept.
+    // See https://drafts.csswg.org/css-fonts-4/#relative-weights
+    pub fn resolve(&self, parent_weight: FontWeight) -> FontWeight {
+        let parent = parent_weight.to_number();
+        match self {
+            &FontWeight::Bolder => FontWeight::Weight(match parent {
+                0...349 => 400,
+                350...549 => 700,
+                550...899 => 900,
+                _ => parent
+            }),
+            &FontWeight::Lighter => FontWeight::Weight(match parent {
+                0...99 => parent,
+                100...549 => 100,
+                550...749 => 400,
+                _ => 700
+            }),
+            &font_weight => font_weight
+        }
+    }
+
+    // The numeric value of a computed font weight.
+    pub fn to_number(&self) -> u32 {
+        match self {
+            &FontWeight::Bold => 700,
+            &FontWeight::Weight(value) => value,
+            _ => 400
+        }
+    }
+}
@@ -0,0 +1,226 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+
+use styles::pseudo::{split_pseudo_element, PseudoElement};
+use styles::selectors::{rightmost_compound, SimpleSelector};
+use styles::types::StyleRule;
+
+// Rule ids are unique among all indices, so that they also tell apart rules
+// from different stylesheets.
+static NEXT_RULE_ID: AtomicUsize = ATOMIC_USIZE_INIT;
+
+// Where a rule's selectors are filed, based on their rightmost compound
+// selector, the same way Servo's `SelectorMap` buckets rules.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SelectorKey<'a> {
+    Id(&'a str),
+    Class(&'a str),
+    Tag(&'a str),
+    Universal
+}
+
+impl<'a> SelectorKey<'a> {
+    pub fn of(selector: &'a str) -> Self {
+        let mut class = None;
+        let mut tag = None;
+
+        for simple in rightmost_compound(selector) {
+            match simple.selector {
+                SimpleSelector::Id(id) => return SelectorKey::Id(id),
+                SimpleSelector::Class(name) => class = class.or(Some(name)),
+                SimpleSelector::Tag(name) => tag = tag.or(Some(name)),
+                _ => {}
+            }
+        }
+
+        match (class, tag) {
+            (Some(class), _) => SelectorKey::Class(class),
+            (None, Some(tag)) => SelectorKey::Tag(tag),
+            (None, None) => SelectorKey::Universal
+        }
+    }
+}
+
+// Rules, keyed by full selector text and by `SelectorKey`. Rules with several
+// selectors are filed once per selector. Selectors for pseudo-elements are
+// keyed separately from those for elements, by the selector they apply to.
+//
+// Buckets hold rule ids instead of rule indices, so that removing a rule
+// doesn't renumber the rules after it. Ids only ever increase, so the ids of
+// the rules, in order, stay sorted and are binary searched for indices.
+#[derive(Debug, Clone, Default)]
+pub struct SelectorIndex {
+    ids: Vec<usize>,
+    by_text: HashMap<String, Vec<usize>>,
+    elements: KeyBuckets,
+    before: KeyBuckets,
+    after: KeyBuckets
+}
+
+#[derive(Debug, Clone, Default)]
+struct KeyBuckets {
+    by_id: HashMap<String, Vec<usize>>,
+    by_class: HashMap<String, Vec<usize>>,
+    by_tag: HashMap<String, Vec<usize>>,
+    universal: Vec<usize>
+}
+
+impl SelectorIndex {
+    pub fn new(rules: &[StyleRule]) -> Self {
+        let mut index = SelectorIndex::default();
+        for rule in rules {
+            index.push(rule);
+        }
+        index
+    }
+
+    // Files a rule added after all the others.
+    pub fn push(&mut self, rule: &StyleRule) {
+        let id = NEXT_RULE_ID.fetch_add(1, Ordering::Relaxed);
+        self.ids.push(id);
+        for selector in &rule.selectors {
+            let selector = selector.as_ref();
+            self.by_text.entry(selector.to_owned()).or_insert_with(Vec::new).push(id);
+            let (selector, pseudo) = split_pseudo_element(selector);
+            self.buckets_mut(pseudo).insert(SelectorKey::of(selector), id);
+        }
+    }
+
+    // Forgets a removed rule. Rules after it move down by one, like in the
+    // rules list itself, while keeping their ids.
+    pub fn remove(&mut self, index: usize, rule: &StyleRule) {
+        for selector in &rule.selectors {
+            self.remove_selector(index, selector.as_ref());
+        }
+        self.ids.remove(index);
+    }
+
+    // Forgets one occurrence of a selector in a rule that's kept.
+    pub fn remove_selector(&mut self, index: usize, selector: &str) {
+        let id = self.ids[index];
+        remove_from_bucket(&mut self.by_text, selector, id);
+        let (selector, pseudo) = split_pseudo_element(selector);
+        self.buckets_mut(pseudo).remove(SelectorKey::of(selector), id);
+    }
+
+    // The first rule with exactly this selector, in source order.
+    pub fn first(&self, selector: &str) -> Option<usize> {
+        self.by_text
+            .get(selector)
+            .and_then(|ids| ids.iter().min())
+            .map(|&id| self.index_of(id))
+    }
+
+    // Indices of the element rules filed under the given key, sorted and
+    // deduplicated.
+    pub fn get(&self, key: SelectorKey) -> Vec<usize> {
+        self.indices_of(self.elements.get(key))
+    }
+
+    // Same as `get`, for the rules of a pseudo-element of the elements.
+    pub fn get_pseudo(&self, key: SelectorKey, pseudo: PseudoElement) -> Vec<usize> {
+        self.indices_of(self.buckets(Some(pseudo)).get(key))
+    }
+
+    pub fn id(&self, index: usize) -> usize {
+        self.ids[index]
+    }
+
+    fn index_of(&self, id: usize) -> usize {
+        self.ids.binary_search(&id).expect("Filed rules should be in the index")
+    }
+
+    // Sorted ids map to sorted indices.
+    fn indices_of(&self, ids: Vec<usize>) -> Vec<usize> {
+        ids.into_iter().map(|id| self.index_of(id)).collect()
+    }
+
+    fn buckets(&self, pseudo: Option<PseudoElement>) -> &KeyBuckets {
+        match pseudo {
+            None => &self.elements,
+            Some(PseudoElement::Before) => &self.before,
+            Some(PseudoElement::After) => &self.after
+        }
+    }
+
+    fn buckets_mut(&mut self, pseudo: Option<PseudoElement>) -> &mut KeyBuckets {
+        match pseudo {
+            None => &mut self.elements,
+            Some(PseudoElement::Before) => &mut self.before,
+            Some(PseudoElement::After) => &mut self.after
+        }
+    }
+}
+
+impl KeyBuckets {
+    fn insert(&mut self, key: SelectorKey, rule_id: usize) {
+        match key {
+            SelectorKey::Id(id) => self.by_id.entry(id.to_owned()).or_insert_with(Vec::new).push(rule_id),
+            SelectorKey::Class(class) => self.by_class.entry(class.to_owned()).or_insert_with(Vec::new).push(rule_id),
+            SelectorKey::Tag(tag) => self.by_tag.entry(tag.to_owned()).or_insert_with(Vec::new).push(rule_id),
+            SelectorKey::Universal => self.universal.push(rule_id)
+        }
+    }
+
+    fn remove(&mut self, key: SelectorKey, rule_id: usize) {
+        match key {
+            SelectorKey::Id(id) => remove_from_bucket(&mut self.by_id, id, rule_id),
+            SelectorKey::Class(class) => remove_from_bucket(&mut self.by_class, class, rule_id),
+            SelectorKey::Tag(tag) => remove_from_bucket(&mut self.by_tag, tag, rule_id),
+            SelectorKey::Universal => remove_id(&mut self.universal, rule_id)
+        }
+    }
+
+    fn get(&self, key: SelectorKey) -> Vec<usize> {
+        let ids = match key {
+            SelectorKey::Id(id) => self.by_id.get(id),
+            SelectorKey::Class(class) => self.by_class.get(class),
+            SelectorKey::Tag(tag) => self.by_tag.get(tag),
+            SelectorKey::Universal => Some(&self.universal)
+        };
+        let mut ids = ids.cloned().unwrap_or_default();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+}
+
+// The index is derived from the rules, and its ids and the order within its
+// buckets depend on the order of insertions and removals.
+impl PartialEq for SelectorIndex {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+fn remove_from_bucket(buckets: &mut HashMap<String, Vec<usize>>, key: &str, rule_id: usize) {
+    let is_empty = match buckets.get_mut(key) {
+        Some(ids) => {
+            remove_id(ids, rule_id);
+            ids.is_empty()
+        }
+        None => false
+    };
+    if is_empty {
+        buckets.remove(key);
+    }
+}
+
+// Removes one occurrence of `rule_id`. Selectors repeated within a rule are
+// filed, and thus removed, once per occurrence.
+fn remove_id(ids: &mut Vec<usize>, rule_id: usize) {
+    if let Some(position) = ids.iter().position(|&id| id == rule_id) {
+        ids.swap_remove(position);
+    }
+}
@@ -0,0 +1,231 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use styles::types::{
+    Align,
+    BorderStyle,
+    Cursor,
+    Display,
+    FlexDirection,
+    FontCaps,
+    FontSize,
+    FontSizeKeyword,
+    FontStretch,
+    FontStyle,
+    FontWeight,
+    GenericFontName,
+    Justify,
+    Overflow,
+    PositionType,
+    Visibility,
+    Wrap
+};
+
+// The keywords of each property, as written in CSS and in the `value!` macro.
+// Each table defines a list of the keywords, which the checked macros
+// validate against, and a function returning the keyword of a value, used
+// when serializing.
+
+// For types where every value is a keyword.
+macro_rules! keywords {
+    ($( $table:ident, $name_of:ident: $ty:ident { $( $keyword:expr => $variant:ident ),* } )*) => {
+        $(
+            pub const $table: &[&str] = &[$( $keyword ),*];
+
+            pub fn $name_of(value: $ty) -> &'static str {
+                match value {
+                    $( $ty::$variant => $keyword ),*
+                }
+            }
+        )*
+    };
+}
+
+// For types where only some values are keywords.
+macro_rules! some_keywords {
+    ($( $table:ident, $name_of:ident: $ty:ident { $( $keyword:expr => $variant:ident ),* } )*) => {
+        $(
+            pub const $table: &[&str] = &[$( $keyword ),*];
+
+            pub fn $name_of(value: $ty) -> Option<&'static str> {
+                match value {
+                    $( $ty::$variant => Some($keyword), )*
+                    _ => None
+                }
+            }
+        )*
+    };
+}
+
+keywords! {
+    ALIGN, align: Align {
+        "auto" => Auto,
+        "flex-start" => FlexStart,
+        "center" => Center,
+        "flex-end" => FlexEnd,
+        "stretch" => Stretch,
+        "baseline" => Baseline,
+        "space-between" => SpaceBetween,
+        "space-around" => SpaceAround
+    }
+
+    DISPLAY, display: Display {
+        "flex" => Flex,
+        "none" => None
+    }
+
+    FLEX_DIRECTION, flex_direction: FlexDirection {
+        "row" => Row,
+        "row-reverse" => RowReverse,
+        "column" => Column,
+        "column-reverse" => ColumnReverse
+    }
+
+    FLEX_WRAP, flex_wrap: Wrap {
+        "nowrap" => NoWrap,
+        "wrap" => Wrap,
+        "wrap-reverse" => WrapReverse
+    }
+
+    JUSTIFY, justify: Justify {
+        "flex-start" => FlexStart,
+        "center" => Center,
+        "flex-end" => FlexEnd,
+        "space-between" => SpaceBetween,
+        "space-around" => SpaceAround,
+        "space-evenly" => SpaceEvenly
+    }
+
+    OVERFLOW, overflow: Overflow {
+        "visible" => Visible,
+        "hidden" => Hidden,
+        "scroll" => Scroll
+    }
+
+    POSITION, position: PositionType {
+        "relative" => Relative,
+        "absolute" => Absolute
+    }
+
+    CURSOR, cursor: Cursor {
+        "auto" => Auto,
+        "none" => None,
+        "default" => Default,
+        "pointer" => Pointer,
+        "context-menu" => ContextMenu,
+        "help" => Help,
+        "progress" => Progress,
+        "wait" => Wait,
+        "cell" => Cell,
+        "crosshair" => Crosshair,
+        "text" => Text,
+        "vertical-text" => VerticalText,
+        "alias" => Alias,
+        "copy" => Copy,
+        "move" => Move,
+        "no-drop" => NoDrop,
+        "not-allowed" => NotAllowed,
+        "grab" => Grab,
+        "grabbing" => Grabbing,
+        "e-resize" => EResize,
+        "n-resize" => NResize,
+        "ne-resize" => NeResize,
+        "nw-resize" => NwResize,
+        "s-resize" => SResize,
+        "se-resize" => SeResize,
+        "sw-resize" => SwResize,
+        "w-resize" => WResize,
+        "ew-resize" => EwResize,
+        "ns-resize" => NsResize,
+        "nesw-resize" => NeswResize,
+        "nwse-resize" => NwseResize,
+        "col-resize" => ColResize,
+        "row-resize" => RowResize,
+        "all-scroll" => AllScroll,
+        "zoom-in" => ZoomIn,
+        "zoom-out" => ZoomOut
+    }
+
+    BORDER_STYLE, border_style: BorderStyle {
+        "none" => None,
+        "solid" => Solid,
+        "double" => Double,
+        "dotted" => Dotted,
+        "dashed" => Dashed,
+        "hidden" => Hidden,
+        "groove" => Groove,
+        "ridge" => Ridge,
+        "inset" => Inset,
+        "outset" => Outset
+    }
+
+    FONT_SIZE_KEYWORD, font_size_keyword: FontSizeKeyword {
+        "xx-small" => XXSmall,
+        "x-small" => XSmall,
+        "small" => Small,
+        "medium" => Medium,
+        "large" => Large,
+        "x-large" => XLarge,
+        "xx-large" => XXLarge,
+        "xxx-large" => XXXLarge
+    }
+
+    VISIBILITY, visibility: Visibility {
+        "visible" => Visible,
+        "hidden" => Hidden
+    }
+
+    GENERIC_FONT_NAME, generic_font_name: GenericFontName {
+        "serif" => Serif,
+        "sans-serif" => SansSerif,
+        "monospace" => Monospace,
+        "cursive" => Cursive,
+        "fantasy" => Fantasy,
+        "system-ui" => SystemUI
+    }
+}
+
+some_keywords! {
+    FONT_STYLE, font_style: FontStyle {
+        "normal" => Normal,
+        "italic" => Italic,
+        "oblique" => Oblique
+    }
+
+    FONT_CAPS, font_caps: FontCaps {
+        "normal" => Normal,
+        "small-caps" => SmallCaps
+    }
+
+    FONT_WEIGHT, font_weight: FontWeight {
+        "normal" => Normal,
+        "bold" => Bold,
+        "bolder" => Bolder,
+        "lighter" => Lighter
+    }
+
+    RELATIVE_FONT_SIZE, relative_font_size: FontSize {
+        "smaller" => Smaller,
+        "larger" => Larger
+    }
+
+    FONT_STRETCH, font_stretch: FontStretch {
+        "normal" => Normal,
+        "ultra-condensed" => UltraCondensed,
+        "extra-condensed" => ExtraCondensed,
+        "condensed" => Condensed,
+        "semi-condensed" => SemiCondensed,
+        "semi-expanded" => SemiExpanded,
+        "expanded" => Expanded,
+        "extra-expanded" => ExtraExpanded,
+        "ultra-expanded" => UltraExpanded
+    }
+}
@@ -23,12 +23,19 @@ pub use yoga::{Align, Display, FlexDirection, FlexStyle, Justify, Overflow, Posi
 pub type InlineBoxShadows = SmallVec<[BoxShadow; 1]>;
 pub type InlineTextShadows = SmallVec<[TextShadow; 1]>;
 pub type InlineFontNames = SmallVec<[FontName; 1]>;
+pub type InlineCustomProperties = SmallVec<[CustomProperty; 1]>;
+pub type InlineContentItems = SmallVec<[ContentItem; 1]>;
+pub type InlineQuotePairs = SmallVec<[QuotePair; 2]>;
+pub type InlineCounters = SmallVec<[Counter; 1]>;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, SelfTokenize)]
 pub enum StyleDeclaration {
     Unknown,
     Theme(ThemeStyle),
-    Layout(FlexStyle)
+    Layout(FlexStyle),
+    Custom(CustomProperty),
+    WithVariables(UnparsedDeclaration),
+    WideKeyword(PropertyId, CssWideKeyword)
 }
 
 impl StyleDeclaration {
@@ -36,21 +43,296 @@ impl StyleDeclaration {
         match self {
             StyleDeclaration::Unknown => None,
             StyleDeclaration::Theme(theme) => Some(StyleDeclaration::Theme(theme)),
-            StyleDeclaration::Layout(layout) => Some(StyleDeclaration::Layout(layout))
+            StyleDeclaration::Layout(layout) => Some(StyleDeclaration::Layout(layout)),
+            StyleDeclaration::Custom(custom) => Some(StyleDeclaration::Custom(custom)),
+            StyleDeclaration::WithVariables(unparsed) => Some(StyleDeclaration::WithVariables(unparsed)),
+            StyleDeclaration::WideKeyword(id, keyword) => Some(StyleDeclaration::WideKeyword(id, keyword))
         }
     }
 }
 
+// Identifies a longhand property which can be represented by a `ThemeStyle`
+// or a `FlexStyle`, independently of its value.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum PropertyId {
+    // Theme styles
+    Cursor,
+    Color,
+    BackgroundColor,
+    Opacity,
+    BorderTopColor,
+    BorderLeftColor,
+    BorderBottomColor,
+    BorderRightColor,
+    BorderTopStyle,
+    BorderLeftStyle,
+    BorderBottomStyle,
+    BorderRightStyle,
+    BoxShadow,
+    TextShadow,
+    FontFamily,
+    FontStyle,
+    FontCaps,
+    FontWeight,
+    FontSize,
+    FontStretch,
+    Visibility,
+    Content,
+    Quotes,
+    CounterIncrement,
+    CounterReset,
+
+    // Layout styles
+    AlignContent,
+    AlignItems,
+    AlignSelf,
+    BorderTopWidth,
+    BorderLeftWidth,
+    BorderBottomWidth,
+    BorderRightWidth,
+    Top,
+    Left,
+    Bottom,
+    Right,
+    Display,
+    FlexBasis,
+    FlexDirection,
+    FlexGrow,
+    FlexShrink,
+    FlexWrap,
+    Height,
+    Width,
+    MaxHeight,
+    MaxWidth,
+    MinHeight,
+    MinWidth,
+    Overflow,
+    JustifyContent,
+    MarginTop,
+    MarginLeft,
+    MarginBottom,
+    MarginRight,
+    PaddingTop,
+    PaddingLeft,
+    PaddingBottom,
+    PaddingRight,
+    Position
+}
+
+impl PropertyId {
+    pub fn from_name<T>(name: T) -> Option<Self>
+    where
+        T: AsRef<str>
+    {
+        match name.as_ref() {
+            "cursor" => Some(PropertyId::Cursor),
+            "color" => Some(PropertyId::Color),
+            "background-color" => Some(PropertyId::BackgroundColor),
+            "opacity" => Some(PropertyId::Opacity),
+            "border-top-color" => Some(PropertyId::BorderTopColor),
+            "border-left-color" => Some(PropertyId::BorderLeftColor),
+            "border-bottom-color" => Some(PropertyId::BorderBottomColor),
+            "border-right-color" => Some(PropertyId::BorderRightColor),
+            "border-top-style" => Some(PropertyId::BorderTopStyle),
+            "border-left-style" => Some(PropertyId::BorderLeftStyle),
+            "border-bottom-style" => Some(PropertyId::BorderBottomStyle),
+            "border-right-style" => Some(PropertyId::BorderRightStyle),
+            "box-shadow" => Some(PropertyId::BoxShadow),
+            "text-shadow" => Some(PropertyId::TextShadow),
+            "font-family" => Some(PropertyId::FontFamily),
+            "font-style" => Some(PropertyId::FontStyle),
+            "font-variant-caps" => Some(PropertyId::FontCaps),
+            "font-weight" => Some(PropertyId::FontWeight),
+            "font-size" => Some(PropertyId::FontSize),
+            "font-stretch" => Some(PropertyId::FontStretch),
+            "visibility" => Some(PropertyId::Visibility),
+            "content" => Some(PropertyId::Content),
+            "quotes" => Some(PropertyId::Quotes),
+            "counter-increment" => Some(PropertyId::CounterIncrement),
+            "counter-reset" => Some(PropertyId::CounterReset),
+            "align-content" => Some(PropertyId::AlignContent),
+            "align-items" => Some(PropertyId::AlignItems),
+            "align-self" => Some(PropertyId::AlignSelf),
+            "border-top-width" => Some(PropertyId::BorderTopWidth),
+            "border-left-width" => Some(PropertyId::BorderLeftWidth),
+            "border-bottom-width" => Some(PropertyId::BorderBottomWidth),
+            "border-right-width" => Some(PropertyId::BorderRightWidth),
+            "top" => Some(PropertyId::Top),
+            "left" => Some(PropertyId::Left),
+            "bottom" => Some(PropertyId::Bottom),
+            "right" => Some(PropertyId::Right),
+            "display" => Some(PropertyId::Display),
+            "flex-basis" => Some(PropertyId::FlexBasis),
+            "flex-direction" => Some(PropertyId::FlexDirection),
+            "flex-grow" => Some(PropertyId::FlexGrow),
+            "flex-shrink" => Some(PropertyId::FlexShrink),
+            "flex-wrap" => Some(PropertyId::FlexWrap),
+            "height" => Some(PropertyId::Height),
+            "width" => Some(PropertyId::Width),
+            "max-height" => Some(PropertyId::MaxHeight),
+            "max-width" => Some(PropertyId::MaxWidth),
+            "min-height" => Some(PropertyId::MinHeight),
+            "min-width" => Some(PropertyId::MinWidth),
+            "overflow" | "overflow-x" | "overflow-y" => Some(PropertyId::Overflow),
+            "justify-content" => Some(PropertyId::JustifyContent),
+            "margin-top" => Some(PropertyId::MarginTop),
+            "margin-left" => Some(PropertyId::MarginLeft),
+            "margin-bottom" => Some(PropertyId::MarginBottom),
+            "margin-right" => Some(PropertyId::MarginRight),
+            "padding-top" => Some(PropertyId::PaddingTop),
+            "padding-left" => Some(PropertyId::PaddingLeft),
+            "padding-bottom" => Some(PropertyId::PaddingBottom),
+            "padding-right" => Some(PropertyId::PaddingRight),
+            "position" => Some(PropertyId::Position),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            &PropertyId::Cursor => "cursor",
+            &PropertyId::Color => "color",
+            &PropertyId::BackgroundColor => "background-color",
+            &PropertyId::Opacity => "opacity",
+            &PropertyId::BorderTopColor => "border-top-color",
+            &PropertyId::BorderLeftColor => "border-left-color",
+            &PropertyId::BorderBottomColor => "border-bottom-color",
+            &PropertyId::BorderRightColor => "border-right-color",
+            &PropertyId::BorderTopStyle => "border-top-style",
+            &PropertyId::BorderLeftStyle => "border-left-style",
+            &PropertyId::BorderBottomStyle => "border-bottom-style",
+            &PropertyId::BorderRightStyle => "border-right-style",
+            &PropertyId::BoxShadow => "box-shadow",
+            &PropertyId::TextShadow => "text-shadow",
+            &PropertyId::FontFamily => "font-family",
+            &PropertyId::FontStyle => "font-style",
+            &PropertyId::FontCaps => "font-variant-caps",
+            &PropertyId::FontWeight => "font-weight",
+            &PropertyId::FontSize => "font-size",
+            &PropertyId::FontStretch => "font-stretch",
+            &PropertyId::Visibility => "visibility",
+            &PropertyId::Content => "content",
+            &PropertyId::Quotes => "quotes",
+            &PropertyId::CounterIncrement => "counter-increment",
+            &PropertyId::CounterReset => "counter-reset",
+            &PropertyId::AlignContent => "align-content",
+            &PropertyId::AlignItems => "align-items",
+            &PropertyId::AlignSelf => "align-self",
+            &PropertyId::BorderTopWidth => "border-top-width",
+            &PropertyId::BorderLeftWidth => "border-left-width",
+            &PropertyId::BorderBottomWidth => "border-bottom-width",
+            &PropertyId::BorderRightWidth => "border-right-width",
+            &PropertyId::Top => "top",
+            &PropertyId::Left => "left",
+            &PropertyId::Bottom => "bottom",
+            &PropertyId::Right => "right",
+            &PropertyId::Display => "display",
+            &PropertyId::FlexBasis => "flex-basis",
+            &PropertyId::FlexDirection => "flex-direction",
+            &PropertyId::FlexGrow => "flex-grow",
+            &PropertyId::FlexShrink => "flex-shrink",
+            &PropertyId::FlexWrap => "flex-wrap",
+            &PropertyId::Height => "height",
+            &PropertyId::Width => "width",
+            &PropertyId::MaxHeight => "max-height",
+            &PropertyId::MaxWidth => "max-width",
+            &PropertyId::MinHeight => "min-height",
+            &PropertyId::MinWidth => "min-width",
+            &PropertyId::Overflow => "overflow",
+            &PropertyId::JustifyContent => "justify-content",
+            &PropertyId::MarginTop => "margin-top",
+            &PropertyId::MarginLeft => "margin-left",
+            &PropertyId::MarginBottom => "margin-bottom",
+            &PropertyId::MarginRight => "margin-right",
+            &PropertyId::PaddingTop => "padding-top",
+            &PropertyId::PaddingLeft => "padding-left",
+            &PropertyId::PaddingBottom => "padding-bottom",
+            &PropertyId::PaddingRight => "padding-right",
+            &PropertyId::Position => "position"
+        }
+    }
+
+    // See https://www.w3.org/TR/CSS21/propidx.html
+    pub fn is_inherited(&self) -> bool {
+        match self {
+            &PropertyId::Cursor
+            | &PropertyId::Color
+            | &PropertyId::TextShadow
+            | &PropertyId::FontFamily
+            | &PropertyId::FontStyle
+            | &PropertyId::FontCaps
+            | &PropertyId::FontWeight
+            | &PropertyId::FontSize
+            | &PropertyId::FontStretch
+            | &PropertyId::Visibility
+            | &PropertyId::Quotes => true,
+            _ => false
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum CssWideKeyword {
+    Initial,
+    Inherit,
+    Unset,
+    Revert
+}
+
+// A `--name: value` declaration. The name includes the leading dashes and the
+// value is kept as raw CSS text, since it's only given meaning by `var()`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, SelfTokenize)]
+pub struct CustomProperty {
+    pub name: CustomPropertyName,
+    pub value: UnparsedValue
+}
+
+// A declaration containing `var()` references. It can't be typed until the
+// custom properties it refers to are known, so it's re-parsed after substitution
+// at style resolution time.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, SelfTokenize)]
+pub struct UnparsedDeclaration {
+    pub property: PropertyName,
+    pub value: UnparsedValue
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, DefaultQuote)]
+pub struct CustomPropertyName(pub Cow<'static, str>);
+
+impl AsRef<str> for CustomPropertyName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, DefaultQuote)]
+pub struct PropertyName(pub Cow<'static, str>);
+
+impl AsRef<str> for PropertyName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, DefaultQuote)]
+pub struct UnparsedValue(pub Cow<'static, str>);
+
+impl AsRef<str> for UnparsedValue {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, SelfTokenize)]
 pub enum ThemeStyle {
     Cursor(Cursor),
-    Color(Color),
-    BackgroundColor(Color),
-    Opacity(u32),
-    BorderTopColor(Color),
-    BorderLeftColor(Color),
-    BorderBottomColor(Color),
-    BorderRightColor(Color),
+    Color(ColorValue),
+    BackgroundColor(ColorValue),
+    Opacity(f32),
+    BorderTopColor(ColorValue),
+    BorderLeftColor(ColorValue),
+    BorderBottomColor(ColorValue),
+    BorderRightColor(ColorValue),
     BorderTopStyle(BorderStyle),
     BorderBottomStyle(BorderStyle),
     BorderLeftStyle(BorderStyle),
@@ -63,7 +345,11 @@ pub enum ThemeStyle {
     FontWeight(FontWeight),
     FontSize(FontSize),
     FontStretch(FontStretch),
-    Visibility(Visibility)
+    Visibility(Visibility),
+    Content(Content),
+    Quotes(Quotes),
+    CounterIncrement(InlineCounters),
+    CounterReset(InlineCounters)
 }
 
 #[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize, EnumStrSnakeCase, SelfTokenize)]
@@ -155,6 +441,72 @@ impl Color {
     }
 }
 
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum ColorValue {
+    Rgba(Color),
+    CurrentColor,
+    System(SystemColor)
+}
+
+impl Default for ColorValue {
+    fn default() -> Self {
+        ColorValue::CurrentColor
+    }
+}
+
+impl From<Color> for ColorValue {
+    fn from(color: Color) -> Self {
+        ColorValue::Rgba(color)
+    }
+}
+
+// See https://www.w3.org/TR/css-color-4/#css-system-colors
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum SystemColor {
+    ActiveBorder,
+    ActiveCaption,
+    AppWorkspace,
+    Background,
+    ButtonFace,
+    ButtonHighlight,
+    ButtonShadow,
+    ButtonText,
+    CaptionText,
+    GrayText,
+    Highlight,
+    HighlightText,
+    InactiveBorder,
+    InactiveCaption,
+    InactiveCaptionText,
+    InfoBackground,
+    InfoText,
+    Menu,
+    MenuText,
+    Scrollbar,
+    ThreeDDarkShadow,
+    ThreeDFace,
+    ThreeDHighlight,
+    ThreeDLightShadow,
+    ThreeDShadow,
+    Window,
+    WindowFrame,
+    WindowText,
+    Canvas,
+    CanvasText,
+    LinkText,
+    VisitedText,
+    ActiveText,
+    ButtonBorder,
+    Field,
+    FieldText,
+    Mark,
+    MarkText,
+    SelectedItem,
+    SelectedItemText,
+    AccentColor,
+    AccentColorText
+}
+
 #[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize, EnumStrSnakeCase, SelfTokenize)]
 pub enum BorderStyle {
     None,
@@ -177,7 +529,7 @@ impl Default for BorderStyle {
 
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, SelfTokenize)]
 pub struct BoxShadow {
-    pub color: Option<Color>,
+    pub color: ColorValue,
     pub horizontal: StyleUnit,
     pub vertical: StyleUnit,
     pub blur: Option<StyleUnit>,
@@ -187,13 +539,13 @@ pub struct BoxShadow {
 
 impl Into<[u8; 4]> for BoxShadow {
     fn into(self) -> [u8; 4] {
-        self.color.unwrap_or_default().into()
+        self.color.resolve(Color::default()).into()
     }
 }
 
 #[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, SelfTokenize)]
 pub struct TextShadow {
-    pub color: Option<Color>,
+    pub color: ColorValue,
     pub horizontal: StyleUnit,
     pub vertical: StyleUnit,
     pub blur: Option<StyleUnit>
@@ -201,7 +553,7 @@ pub struct TextShadow {
 
 impl Into<[u8; 4]> for TextShadow {
     fn into(self) -> [u8; 4] {
-        self.color.unwrap_or_default().into()
+        self.color.resolve(Color::default()).into()
     }
 }
 
@@ -325,6 +677,7 @@ pub enum FontSize {
     System,
     Smaller,
     Larger,
+    Keyword(FontSizeKeyword),
     Length(StyleUnit)
 }
 
@@ -339,12 +692,25 @@ impl TryInto<SharedUnit> for FontSize {
 
     fn try_into(self) -> Result<SharedUnit, Self::Error> {
         match self {
-            FontSize::System | FontSize::Smaller | FontSize::Larger => Err(()),
+            FontSize::System | FontSize::Smaller | FontSize::Larger | FontSize::Keyword(_) => Err(()),
             FontSize::Length(v) => Ok(v.into())
         }
     }
 }
 
+// See https://drafts.csswg.org/css-fonts-4/#absolute-size-value
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum FontSizeKeyword {
+    XXSmall,
+    XSmall,
+    Small,
+    Medium,
+    Large,
+    XLarge,
+    XXLarge,
+    XXXLarge
+}
+
 #[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize, EnumStrSnakeCase, SelfTokenize)]
 pub enum FontStretch {
     System,
@@ -385,3 +751,99 @@ impl Into<bool> for Visibility {
         }
     }
 }
+
+// See https://www.w3.org/TR/CSS21/generate.html#content
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum Content {
+    Normal,
+    None,
+    Items(InlineContentItems)
+}
+
+impl Default for Content {
+    fn default() -> Self {
+        Content::Normal
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum ContentItem {
+    String(ContentString),
+    // `counter(name, style)`
+    Counter(CounterName, CounterStyle),
+    // `counters(name, separator, style)`, for the values of all the nested
+    // counters with this name, from the outermost one
+    Counters(CounterName, ContentString, CounterStyle),
+    // `attr(name)`
+    Attr(ContentString),
+    OpenQuote,
+    CloseQuote,
+    NoOpenQuote,
+    NoCloseQuote
+}
+
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum CounterStyle {
+    None,
+    Decimal,
+    DecimalLeadingZero,
+    LowerAlpha,
+    UpperAlpha,
+    LowerRoman,
+    UpperRoman,
+    Disc,
+    Circle,
+    Square
+}
+
+impl Default for CounterStyle {
+    fn default() -> Self {
+        CounterStyle::Decimal
+    }
+}
+
+// See https://www.w3.org/TR/css-content-3/#quotes
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum Quotes {
+    Auto,
+    None,
+    Pairs(InlineQuotePairs)
+}
+
+impl Default for Quotes {
+    fn default() -> Self {
+        Quotes::Auto
+    }
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, SelfTokenize)]
+pub struct QuotePair {
+    pub open: ContentString,
+    pub close: ContentString
+}
+
+// A counter name with a value, as given to `counter-increment` and
+// `counter-reset`, or as kept while generating content.
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, SelfTokenize)]
+pub struct Counter {
+    pub name: CounterName,
+    pub value: i32
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, DefaultQuote)]
+pub struct ContentString(pub Cow<'static, str>);
+
+impl AsRef<str> for ContentString {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, PartialEq, Default, Clone, Serialize, Deserialize, DefaultQuote)]
+pub struct CounterName(pub Cow<'static, str>);
+
+impl AsRef<str> for CounterName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
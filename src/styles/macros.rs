@@ -131,8 +131,294 @@ macro_rules! value {
     };
 
     // Theme styles
-    // TODO: Handle more longhands.
 
+    (color: rgb($( $tt:tt )*)) => {
+        ColorValue::Rgba(rgb!($( $tt )*))
+    };
+    (color: rgba($( $tt:tt )*)) => {
+        ColorValue::Rgba(rgba!($( $tt )*))
+    };
+    (color: currentColor) => {
+        ColorValue::CurrentColor
+    };
+    // Named colors are lowercase and system colors are spelled as in the spec,
+    // e.g. `red` or `ButtonText`. Any other identifier is a variable.
+    (color: $keyword:ident) => {
+        value!(@color_keyword $keyword)
+    };
+    (color: $val:expr) => {
+        ColorValue::from($val)
+    };
+    (border-style: none) => {
+        BorderStyle::None
+    };
+    (border-style: solid) => {
+        BorderStyle::Solid
+    };
+    (border-style: double) => {
+        BorderStyle::Double
+    };
+    (border-style: dotted) => {
+        BorderStyle::Dotted
+    };
+    (border-style: dashed) => {
+        BorderStyle::Dashed
+    };
+    (border-style: hidden) => {
+        BorderStyle::Hidden
+    };
+    (border-style: groove) => {
+        BorderStyle::Groove
+    };
+    (border-style: ridge) => {
+        BorderStyle::Ridge
+    };
+    (border-style: inset) => {
+        BorderStyle::Inset
+    };
+    (border-style: outset) => {
+        BorderStyle::Outset
+    };
+    (border-style: $val:expr) => {
+        $val.into()
+    };
+    (cursor: auto) => {
+        Cursor::Auto
+    };
+    (cursor: none) => {
+        Cursor::None
+    };
+    (cursor: default) => {
+        Cursor::Default
+    };
+    (cursor: pointer) => {
+        Cursor::Pointer
+    };
+    (cursor: context-menu) => {
+        Cursor::ContextMenu
+    };
+    (cursor: help) => {
+        Cursor::Help
+    };
+    (cursor: progress) => {
+        Cursor::Progress
+    };
+    (cursor: wait) => {
+        Cursor::Wait
+    };
+    (cursor: cell) => {
+        Cursor::Cell
+    };
+    (cursor: crosshair) => {
+        Cursor::Crosshair
+    };
+    (cursor: text) => {
+        Cursor::Text
+    };
+    (cursor: vertical-text) => {
+        Cursor::VerticalText
+    };
+    (cursor: alias) => {
+        Cursor::Alias
+    };
+    (cursor: copy) => {
+        Cursor::Copy
+    };
+    (cursor: move) => {
+        Cursor::Move
+    };
+    (cursor: no-drop) => {
+        Cursor::NoDrop
+    };
+    (cursor: not-allowed) => {
+        Cursor::NotAllowed
+    };
+    (cursor: grab) => {
+        Cursor::Grab
+    };
+    (cursor: grabbing) => {
+        Cursor::Grabbing
+    };
+    (cursor: e-resize) => {
+        Cursor::EResize
+    };
+    (cursor: n-resize) => {
+        Cursor::NResize
+    };
+    (cursor: ne-resize) => {
+        Cursor::NeResize
+    };
+    (cursor: nw-resize) => {
+        Cursor::NwResize
+    };
+    (cursor: s-resize) => {
+        Cursor::SResize
+    };
+    (cursor: se-resize) => {
+        Cursor::SeResize
+    };
+    (cursor: sw-resize) => {
+        Cursor::SwResize
+    };
+    (cursor: w-resize) => {
+        Cursor::WResize
+    };
+    (cursor: ew-resize) => {
+        Cursor::EwResize
+    };
+    (cursor: ns-resize) => {
+        Cursor::NsResize
+    };
+    (cursor: nesw-resize) => {
+        Cursor::NeswResize
+    };
+    (cursor: nwse-resize) => {
+        Cursor::NwseResize
+    };
+    (cursor: col-resize) => {
+        Cursor::ColResize
+    };
+    (cursor: row-resize) => {
+        Cursor::RowResize
+    };
+    (cursor: all-scroll) => {
+        Cursor::AllScroll
+    };
+    (cursor: zoom-in) => {
+        Cursor::ZoomIn
+    };
+    (cursor: zoom-out) => {
+        Cursor::ZoomOut
+    };
+    (cursor: $val:expr) => {
+        $val.into()
+    };
+    (font-weight: normal) => {
+        FontWeight::Normal
+    };
+    (font-weight: bold) => {
+        FontWeight::Bold
+    };
+    (font-weight: bolder) => {
+        FontWeight::Bolder
+    };
+    (font-weight: lighter) => {
+        FontWeight::Lighter
+    };
+    (font-weight: $val:ident) => {
+        $val.into()
+    };
+    (font-weight: $weight:tt) => {
+        FontWeight::Weight($weight)
+    };
+    (font-weight: $val:expr) => {
+        $val.into()
+    };
+    (font-style: normal) => {
+        FontStyle::Normal
+    };
+    (font-style: italic) => {
+        FontStyle::Italic
+    };
+    (font-style: oblique) => {
+        FontStyle::Oblique
+    };
+    (font-style: $val:expr) => {
+        $val.into()
+    };
+    (font-caps: normal) => {
+        FontCaps::Normal
+    };
+    (font-caps: small-caps) => {
+        FontCaps::SmallCaps
+    };
+    (font-caps: $val:expr) => {
+        $val.into()
+    };
+    (font-stretch: normal) => {
+        FontStretch::Normal
+    };
+    (font-stretch: ultra-condensed) => {
+        FontStretch::UltraCondensed
+    };
+    (font-stretch: extra-condensed) => {
+        FontStretch::ExtraCondensed
+    };
+    (font-stretch: condensed) => {
+        FontStretch::Condensed
+    };
+    (font-stretch: semi-condensed) => {
+        FontStretch::SemiCondensed
+    };
+    (font-stretch: semi-expanded) => {
+        FontStretch::SemiExpanded
+    };
+    (font-stretch: expanded) => {
+        FontStretch::Expanded
+    };
+    (font-stretch: extra-expanded) => {
+        FontStretch::ExtraExpanded
+    };
+    (font-stretch: ultra-expanded) => {
+        FontStretch::UltraExpanded
+    };
+    (font-stretch: $val:expr) => {
+        $val.into()
+    };
+    (visibility: visible) => {
+        Visibility::Visible
+    };
+    (visibility: hidden) => {
+        Visibility::Hidden
+    };
+    (visibility: $val:expr) => {
+        $val.into()
+    };
+    (font-family: $( $value:tt )+) => {
+        FontFamily::Values(value!(@list font_name [] [] $( $value )+))
+    };
+    (box-shadow: none) => {
+        SmallVec::new()
+    };
+    (box-shadow: $( $value:tt )+) => {
+        value!(@list box_shadow [] [] $( $value )+)
+    };
+    (text-shadow: none) => {
+        SmallVec::new()
+    };
+    (text-shadow: $( $value:tt )+) => {
+        value!(@list text_shadow [] [] $( $value )+)
+    };
+    (font-size: xx-small) => {
+        FontSize::Keyword(FontSizeKeyword::XXSmall)
+    };
+    (font-size: x-small) => {
+        FontSize::Keyword(FontSizeKeyword::XSmall)
+    };
+    (font-size: small) => {
+        FontSize::Keyword(FontSizeKeyword::Small)
+    };
+    (font-size: medium) => {
+        FontSize::Keyword(FontSizeKeyword::Medium)
+    };
+    (font-size: large) => {
+        FontSize::Keyword(FontSizeKeyword::Large)
+    };
+    (font-size: x-large) => {
+        FontSize::Keyword(FontSizeKeyword::XLarge)
+    };
+    (font-size: xx-large) => {
+        FontSize::Keyword(FontSizeKeyword::XXLarge)
+    };
+    (font-size: xxx-large) => {
+        FontSize::Keyword(FontSizeKeyword::XXXLarge)
+    };
+    (font-size: smaller) => {
+        FontSize::Smaller
+    };
+    (font-size: larger) => {
+        FontSize::Larger
+    };
     (font-size: $( $tt:tt )*) => {
         FontSize::Length(value!(unit: $( $tt )*))
     };
@@ -166,6 +452,316 @@ macro_rules! value {
         $val.percent()
     }};
 
+    // Comma separated lists, whose items are parsed by the `@$item` rules
+
+    (@list $item:ident [$( $done:tt )*] [$( $current:tt )*] , $( $rest:tt )*) => {
+        value!(@list $item [$( $done )* ($( $current )*)] [] $( $rest )*)
+    };
+    (@list $item:ident [$( $done:tt )*] [$( $current:tt )*] $next:tt $( $rest:tt )*) => {
+        value!(@list $item [$( $done )*] [$( $current )* $next] $( $rest )*)
+    };
+    (@list $item:ident [$( $done:tt )*] [$( $current:tt )+]) => {
+        value!(@list $item [$( $done )* ($( $current )+)] [])
+    };
+    (@list $item:ident [$( ($( $value:tt )*) )*] []) => {
+        SmallVec::from_vec(vec![$( value!(@$item $( $value )*) ),*])
+    };
+    (@font_name serif) => {
+        FontName::Generic(GenericFontName::Serif)
+    };
+    (@font_name sans-serif) => {
+        FontName::Generic(GenericFontName::SansSerif)
+    };
+    (@font_name monospace) => {
+        FontName::Generic(GenericFontName::Monospace)
+    };
+    (@font_name cursive) => {
+        FontName::Generic(GenericFontName::Cursive)
+    };
+    (@font_name fantasy) => {
+        FontName::Generic(GenericFontName::Fantasy)
+    };
+    (@font_name system-ui) => {
+        FontName::Generic(GenericFontName::SystemUI)
+    };
+    (@font_name $( $name:ident )+) => {
+        FontName::Specific(SpecificFontName(stringify!($( $name )*).into()))
+    };
+    (@font_name $name:tt) => {
+        // Quoted names keep their quotes, like when parsed from CSS.
+        FontName::Specific(SpecificFontName(concat!("\"", $name, "\"").into()))
+    };
+
+    // Shadows are given as an optional `inset`, then offsets, blur radius and
+    // spread distance in pixels, followed or preceded by an optional color
+
+    (@box_shadow inset $( $rest:tt )+) => {
+        value!(@box_shadow_values true $( $rest )+)
+    };
+    (@box_shadow $( $rest:tt )+) => {
+        value!(@box_shadow_values false $( $rest )+)
+    };
+    (@box_shadow_values $inset:tt $h:tt px $v:tt px $blur:tt px $spread:tt px $( $color:tt )*) => {
+        BoxShadow {
+            color: value!(@shadow_color $( $color )*),
+            horizontal: value!(unit: $h px),
+            vertical: value!(unit: $v px),
+            blur: Some(value!(unit: $blur px)),
+            spread: Some(value!(unit: $spread px)),
+            inset: $inset
+        }
+    };
+    (@box_shadow_values $inset:tt $h:tt px $v:tt px $blur:tt px $( $color:tt )*) => {
+        BoxShadow {
+            color: value!(@shadow_color $( $color )*),
+            horizontal: value!(unit: $h px),
+            vertical: value!(unit: $v px),
+            blur: Some(value!(unit: $blur px)),
+            spread: None,
+            inset: $inset
+        }
+    };
+    (@box_shadow_values $inset:tt $h:tt px $v:tt px $( $color:tt )*) => {
+        BoxShadow {
+            color: value!(@shadow_color $( $color )*),
+            horizontal: value!(unit: $h px),
+            vertical: value!(unit: $v px),
+            blur: None,
+            spread: None,
+            inset: $inset
+        }
+    };
+    (@box_shadow_values $inset:tt $color:ident ($( $args:tt )*) $( $rest:tt )+) => {
+        value!(@box_shadow_values $inset $( $rest )+ $color($( $args )*))
+    };
+    (@box_shadow_values $inset:tt $color:ident $( $rest:tt )+) => {
+        value!(@box_shadow_values $inset $( $rest )+ $color)
+    };
+    (@text_shadow $h:tt px $v:tt px $blur:tt px $( $color:tt )*) => {
+        TextShadow {
+            color: value!(@shadow_color $( $color )*),
+            horizontal: value!(unit: $h px),
+            vertical: value!(unit: $v px),
+            blur: Some(value!(unit: $blur px))
+        }
+    };
+    (@text_shadow $h:tt px $v:tt px $( $color:tt )*) => {
+        TextShadow {
+            color: value!(@shadow_color $( $color )*),
+            horizontal: value!(unit: $h px),
+            vertical: value!(unit: $v px),
+            blur: None
+        }
+    };
+    (@text_shadow $color:ident ($( $args:tt )*) $( $rest:tt )+) => {
+        value!(@text_shadow $( $rest )+ $color($( $args )*))
+    };
+    (@text_shadow $color:ident $( $rest:tt )+) => {
+        value!(@text_shadow $( $rest )+ $color)
+    };
+    (@shadow_color) => {
+        ColorValue::CurrentColor
+    };
+    (@shadow_color $( $color:tt )+) => {
+        value!(color: $( $color )+)
+    };
+
+    // See `named_colors!` and `system_colors!`
+    (@color_keyword transparent) => { ColorValue::transparent };
+    (@color_keyword aliceblue) => { ColorValue::aliceblue };
+    (@color_keyword antiquewhite) => { ColorValue::antiquewhite };
+    (@color_keyword aqua) => { ColorValue::aqua };
+    (@color_keyword aquamarine) => { ColorValue::aquamarine };
+    (@color_keyword azure) => { ColorValue::azure };
+    (@color_keyword beige) => { ColorValue::beige };
+    (@color_keyword bisque) => { ColorValue::bisque };
+    (@color_keyword black) => { ColorValue::black };
+    (@color_keyword blanchedalmond) => { ColorValue::blanchedalmond };
+    (@color_keyword blue) => { ColorValue::blue };
+    (@color_keyword blueviolet) => { ColorValue::blueviolet };
+    (@color_keyword brown) => { ColorValue::brown };
+    (@color_keyword burlywood) => { ColorValue::burlywood };
+    (@color_keyword cadetblue) => { ColorValue::cadetblue };
+    (@color_keyword chartreuse) => { ColorValue::chartreuse };
+    (@color_keyword chocolate) => { ColorValue::chocolate };
+    (@color_keyword coral) => { ColorValue::coral };
+    (@color_keyword cornflowerblue) => { ColorValue::cornflowerblue };
+    (@color_keyword cornsilk) => { ColorValue::cornsilk };
+    (@color_keyword crimson) => { ColorValue::crimson };
+    (@color_keyword cyan) => { ColorValue::cyan };
+    (@color_keyword darkblue) => { ColorValue::darkblue };
+    (@color_keyword darkcyan) => { ColorValue::darkcyan };
+    (@color_keyword darkgoldenrod) => { ColorValue::darkgoldenrod };
+    (@color_keyword darkgray) => { ColorValue::darkgray };
+    (@color_keyword darkgreen) => { ColorValue::darkgreen };
+    (@color_keyword darkgrey) => { ColorValue::darkgrey };
+    (@color_keyword darkkhaki) => { ColorValue::darkkhaki };
+    (@color_keyword darkmagenta) => { ColorValue::darkmagenta };
+    (@color_keyword darkolivegreen) => { ColorValue::darkolivegreen };
+    (@color_keyword darkorange) => { ColorValue::darkorange };
+    (@color_keyword darkorchid) => { ColorValue::darkorchid };
+    (@color_keyword darkred) => { ColorValue::darkred };
+    (@color_keyword darksalmon) => { ColorValue::darksalmon };
+    (@color_keyword darkseagreen) => { ColorValue::darkseagreen };
+    (@color_keyword darkslateblue) => { ColorValue::darkslateblue };
+    (@color_keyword darkslategray) => { ColorValue::darkslategray };
+    (@color_keyword darkslategrey) => { ColorValue::darkslategrey };
+    (@color_keyword darkturquoise) => { ColorValue::darkturquoise };
+    (@color_keyword darkviolet) => { ColorValue::darkviolet };
+    (@color_keyword deeppink) => { ColorValue::deeppink };
+    (@color_keyword deepskyblue) => { ColorValue::deepskyblue };
+    (@color_keyword dimgray) => { ColorValue::dimgray };
+    (@color_keyword dimgrey) => { ColorValue::dimgrey };
+    (@color_keyword dodgerblue) => { ColorValue::dodgerblue };
+    (@color_keyword firebrick) => { ColorValue::firebrick };
+    (@color_keyword floralwhite) => { ColorValue::floralwhite };
+    (@color_keyword forestgreen) => { ColorValue::forestgreen };
+    (@color_keyword fuchsia) => { ColorValue::fuchsia };
+    (@color_keyword gainsboro) => { ColorValue::gainsboro };
+    (@color_keyword ghostwhite) => { ColorValue::ghostwhite };
+    (@color_keyword gold) => { ColorValue::gold };
+    (@color_keyword goldenrod) => { ColorValue::goldenrod };
+    (@color_keyword gray) => { ColorValue::gray };
+    (@color_keyword green) => { ColorValue::green };
+    (@color_keyword greenyellow) => { ColorValue::greenyellow };
+    (@color_keyword grey) => { ColorValue::grey };
+    (@color_keyword honeydew) => { ColorValue::honeydew };
+    (@color_keyword hotpink) => { ColorValue::hotpink };
+    (@color_keyword indianred) => { ColorValue::indianred };
+    (@color_keyword indigo) => { ColorValue::indigo };
+    (@color_keyword ivory) => { ColorValue::ivory };
+    (@color_keyword khaki) => { ColorValue::khaki };
+    (@color_keyword lavender) => { ColorValue::lavender };
+    (@color_keyword lavenderblush) => { ColorValue::lavenderblush };
+    (@color_keyword lawngreen) => { ColorValue::lawngreen };
+    (@color_keyword lemonchiffon) => { ColorValue::lemonchiffon };
+    (@color_keyword lightblue) => { ColorValue::lightblue };
+    (@color_keyword lightcoral) => { ColorValue::lightcoral };
+    (@color_keyword lightcyan) => { ColorValue::lightcyan };
+    (@color_keyword lightgoldenrodyellow) => { ColorValue::lightgoldenrodyellow };
+    (@color_keyword lightgray) => { ColorValue::lightgray };
+    (@color_keyword lightgreen) => { ColorValue::lightgreen };
+    (@color_keyword lightgrey) => { ColorValue::lightgrey };
+    (@color_keyword lightpink) => { ColorValue::lightpink };
+    (@color_keyword lightsalmon) => { ColorValue::lightsalmon };
+    (@color_keyword lightseagreen) => { ColorValue::lightseagreen };
+    (@color_keyword lightskyblue) => { ColorValue::lightskyblue };
+    (@color_keyword lightslategray) => { ColorValue::lightslategray };
+    (@color_keyword lightslategrey) => { ColorValue::lightslategrey };
+    (@color_keyword lightsteelblue) => { ColorValue::lightsteelblue };
+    (@color_keyword lightyellow) => { ColorValue::lightyellow };
+    (@color_keyword lime) => { ColorValue::lime };
+    (@color_keyword limegreen) => { ColorValue::limegreen };
+    (@color_keyword linen) => { ColorValue::linen };
+    (@color_keyword magenta) => { ColorValue::magenta };
+    (@color_keyword maroon) => { ColorValue::maroon };
+    (@color_keyword mediumaquamarine) => { ColorValue::mediumaquamarine };
+    (@color_keyword mediumblue) => { ColorValue::mediumblue };
+    (@color_keyword mediumorchid) => { ColorValue::mediumorchid };
+    (@color_keyword mediumpurple) => { ColorValue::mediumpurple };
+    (@color_keyword mediumseagreen) => { ColorValue::mediumseagreen };
+    (@color_keyword mediumslateblue) => { ColorValue::mediumslateblue };
+    (@color_keyword mediumspringgreen) => { ColorValue::mediumspringgreen };
+    (@color_keyword mediumturquoise) => { ColorValue::mediumturquoise };
+    (@color_keyword mediumvioletred) => { ColorValue::mediumvioletred };
+    (@color_keyword midnightblue) => { ColorValue::midnightblue };
+    (@color_keyword mintcream) => { ColorValue::mintcream };
+    (@color_keyword mistyrose) => { ColorValue::mistyrose };
+    (@color_keyword moccasin) => { ColorValue::moccasin };
+    (@color_keyword navajowhite) => { ColorValue::navajowhite };
+    (@color_keyword navy) => { ColorValue::navy };
+    (@color_keyword oldlace) => { ColorValue::oldlace };
+    (@color_keyword olive) => { ColorValue::olive };
+    (@color_keyword olivedrab) => { ColorValue::olivedrab };
+    (@color_keyword orange) => { ColorValue::orange };
+    (@color_keyword orangered) => { ColorValue::orangered };
+    (@color_keyword orchid) => { ColorValue::orchid };
+    (@color_keyword palegoldenrod) => { ColorValue::palegoldenrod };
+    (@color_keyword palegreen) => { ColorValue::palegreen };
+    (@color_keyword paleturquoise) => { ColorValue::paleturquoise };
+    (@color_keyword palevioletred) => { ColorValue::palevioletred };
+    (@color_keyword papayawhip) => { ColorValue::papayawhip };
+    (@color_keyword peachpuff) => { ColorValue::peachpuff };
+    (@color_keyword peru) => { ColorValue::peru };
+    (@color_keyword pink) => { ColorValue::pink };
+    (@color_keyword plum) => { ColorValue::plum };
+    (@color_keyword powderblue) => { ColorValue::powderblue };
+    (@color_keyword purple) => { ColorValue::purple };
+    (@color_keyword rebeccapurple) => { ColorValue::rebeccapurple };
+    (@color_keyword red) => { ColorValue::red };
+    (@color_keyword rosybrown) => { ColorValue::rosybrown };
+    (@color_keyword royalblue) => { ColorValue::royalblue };
+    (@color_keyword saddlebrown) => { ColorValue::saddlebrown };
+    (@color_keyword salmon) => { ColorValue::salmon };
+    (@color_keyword sandybrown) => { ColorValue::sandybrown };
+    (@color_keyword seagreen) => { ColorValue::seagreen };
+    (@color_keyword seashell) => { ColorValue::seashell };
+    (@color_keyword sienna) => { ColorValue::sienna };
+    (@color_keyword silver) => { ColorValue::silver };
+    (@color_keyword skyblue) => { ColorValue::skyblue };
+    (@color_keyword slateblue) => { ColorValue::slateblue };
+    (@color_keyword slategray) => { ColorValue::slategray };
+    (@color_keyword slategrey) => { ColorValue::slategrey };
+    (@color_keyword snow) => { ColorValue::snow };
+    (@color_keyword springgreen) => { ColorValue::springgreen };
+    (@color_keyword steelblue) => { ColorValue::steelblue };
+    (@color_keyword tan) => { ColorValue::tan };
+    (@color_keyword teal) => { ColorValue::teal };
+    (@color_keyword thistle) => { ColorValue::thistle };
+    (@color_keyword tomato) => { ColorValue::tomato };
+    (@color_keyword turquoise) => { ColorValue::turquoise };
+    (@color_keyword violet) => { ColorValue::violet };
+    (@color_keyword wheat) => { ColorValue::wheat };
+    (@color_keyword white) => { ColorValue::white };
+    (@color_keyword whitesmoke) => { ColorValue::whitesmoke };
+    (@color_keyword yellow) => { ColorValue::yellow };
+    (@color_keyword yellowgreen) => { ColorValue::yellowgreen };
+    (@color_keyword ActiveBorder) => { ColorValue::ActiveBorder };
+    (@color_keyword ActiveCaption) => { ColorValue::ActiveCaption };
+    (@color_keyword AppWorkspace) => { ColorValue::AppWorkspace };
+    (@color_keyword Background) => { ColorValue::Background };
+    (@color_keyword ButtonFace) => { ColorValue::ButtonFace };
+    (@color_keyword ButtonHighlight) => { ColorValue::ButtonHighlight };
+    (@color_keyword ButtonShadow) => { ColorValue::ButtonShadow };
+    (@color_keyword ButtonText) => { ColorValue::ButtonText };
+    (@color_keyword CaptionText) => { ColorValue::CaptionText };
+    (@color_keyword GrayText) => { ColorValue::GrayText };
+    (@color_keyword Highlight) => { ColorValue::Highlight };
+    (@color_keyword HighlightText) => { ColorValue::HighlightText };
+    (@color_keyword InactiveBorder) => { ColorValue::InactiveBorder };
+    (@color_keyword InactiveCaption) => { ColorValue::InactiveCaption };
+    (@color_keyword InactiveCaptionText) => { ColorValue::InactiveCaptionText };
+    (@color_keyword InfoBackground) => { ColorValue::InfoBackground };
+    (@color_keyword InfoText) => { ColorValue::InfoText };
+    (@color_keyword Menu) => { ColorValue::Menu };
+    (@color_keyword MenuText) => { ColorValue::MenuText };
+    (@color_keyword Scrollbar) => { ColorValue::Scrollbar };
+    (@color_keyword ThreeDDarkShadow) => { ColorValue::ThreeDDarkShadow };
+    (@color_keyword ThreeDFace) => { ColorValue::ThreeDFace };
+    (@color_keyword ThreeDHighlight) => { ColorValue::ThreeDHighlight };
+    (@color_keyword ThreeDLightShadow) => { ColorValue::ThreeDLightShadow };
+    (@color_keyword ThreeDShadow) => { ColorValue::ThreeDShadow };
+    (@color_keyword Window) => { ColorValue::Window };
+    (@color_keyword WindowFrame) => { ColorValue::WindowFrame };
+    (@color_keyword WindowText) => { ColorValue::WindowText };
+    (@color_keyword Canvas) => { ColorValue::Canvas };
+    (@color_keyword CanvasText) => { ColorValue::CanvasText };
+    (@color_keyword LinkText) => { ColorValue::LinkText };
+    (@color_keyword VisitedText) => { ColorValue::VisitedText };
+    (@color_keyword ActiveText) => { ColorValue::ActiveText };
+    (@color_keyword ButtonBorder) => { ColorValue::ButtonBorder };
+    (@color_keyword Field) => { ColorValue::Field };
+    (@color_keyword FieldText) => { ColorValue::FieldText };
+    (@color_keyword Mark) => { ColorValue::Mark };
+    (@color_keyword MarkText) => { ColorValue::MarkText };
+    (@color_keyword SelectedItem) => { ColorValue::SelectedItem };
+    (@color_keyword SelectedItemText) => { ColorValue::SelectedItemText };
+    (@color_keyword AccentColor) => { ColorValue::AccentColor };
+    (@color_keyword AccentColorText) => { ColorValue::AccentColorText };
+    (@color_keyword $val:ident) => {
+        ColorValue::from($val)
+    };
+
     // Catch all
 
     ($val:expr) => {
@@ -334,81 +930,320 @@ macro_rules! declaration {
     // Theme styles
 
     (cursor: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::Cursor(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::Cursor(value!(cursor: $( $value )*)))
     };
     (color: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::Color(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::Color(value!(color: $( $value )*)))
     };
     (background-color: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BackgroundColor(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BackgroundColor(value!(color: $( $value )*)))
     };
     (opacity: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::Opacity(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::Opacity(value!(f32: $( $value )*)))
     };
     (border-top-color: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BorderTopColor(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BorderTopColor(value!(color: $( $value )*)))
     };
     (border-left-color: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BorderLeftColor(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BorderLeftColor(value!(color: $( $value )*)))
     };
     (border-bottom-color: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BorderBottomColor(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BorderBottomColor(value!(color: $( $value )*)))
     };
     (border-right-color: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BorderRightColor(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BorderRightColor(value!(color: $( $value )*)))
     };
     (border-top-style: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BorderTopStyle(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BorderTopStyle(value!(border-style: $( $value )*)))
     };
     (border-bottom-style: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BorderBottomStyle(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BorderBottomStyle(value!(border-style: $( $value )*)))
     };
     (border-left-style: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BorderLeftStyle(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BorderLeftStyle(value!(border-style: $( $value )*)))
     };
     (border-right-style: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BorderRightStyle(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BorderRightStyle(value!(border-style: $( $value )*)))
     };
     (box-shadow: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::BoxShadow(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::BoxShadow(value!(box-shadow: $( $value )*)))
     };
     (text-shadow: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::TextShadow(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::TextShadow(value!(text-shadow: $( $value )*)))
     };
     (font-family: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::FontFamily(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::FontFamily(value!(font-family: $( $value )*)))
     };
     (font-style: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::FontStyle(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::FontStyle(value!(font-style: $( $value )*)))
     };
     (font-caps: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::FontCaps(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::FontCaps(value!(font-caps: $( $value )*)))
     };
     (font-weight: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::FontWeight(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::FontWeight(value!(font-weight: $( $value )*)))
     };
     (font-size: $( $value:tt )*) => {
         StyleDeclaration::Theme(ThemeStyle::FontSize(value!(font-size: $( $value )*)))
     };
     (font-stretch: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::FontStretch(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::FontStretch(value!(font-stretch: $( $value )*)))
     };
     (visibility: $( $value:tt )*) => {
-        StyleDeclaration::Theme(ThemeStyle::Visibility(value!($( $value )*)))
+        StyleDeclaration::Theme(ThemeStyle::Visibility(value!(visibility: $( $value )*)))
     };
 }
 
+// Expands a declaration into its longhands, so that shorthands taking more
+// than one value can be used in `style!`. Omitted values are reset to their
+// initial values, like when the shorthands are parsed from CSS. Given as
+// `@into declarations; ...`, the longhands are pushed onto `declarations`
+// instead of being returned in a new `Vec`.
 #[macro_export]
-macro_rules! style {
-    ( $( $name:tt$(-$suffix:tt)*: { $( $value:tt )* } );* $(;)*) => {
-        StyleDeclarations(SmallVec::from_vec(vec![
-            $(
-                declaration!(
-                    $name$(-$suffix)* : $($value)*
-                ),
-            )*
-        ]))
+macro_rules! declarations {
+    // Margins and paddings, given one to four lengths, `auto` or `undefined`
+
+    (@into $d:ident; margin: $( $value:tt )*) => {
+        declarations!(@sides $d margin [] $( $value )*);
+    };
+    (@into $d:ident; padding: $( $value:tt )*) => {
+        declarations!(@sides $d padding [] $( $value )*);
+    };
+    (@sides $d:ident $name:ident [$( $parsed:tt )*] auto $( $rest:tt )*) => {
+        declarations!(@sides $d $name [$( $parsed )* (auto)] $( $rest )*);
+    };
+    (@sides $d:ident $name:ident [$( $parsed:tt )*] undefined $( $rest:tt )*) => {
+        declarations!(@sides $d $name [$( $parsed )* (undefined)] $( $rest )*);
+    };
+    (@sides $d:ident $name:ident [$( $parsed:tt )*] $val:tt px $( $rest:tt )*) => {
+        declarations!(@sides $d $name [$( $parsed )* ($val px)] $( $rest )*);
+    };
+    (@sides $d:ident $name:ident [$( $parsed:tt )*] $val:tt % $( $rest:tt )*) => {
+        declarations!(@sides $d $name [$( $parsed )* ($val %)] $( $rest )*);
+    };
+    (@sides $d:ident $name:ident [($( $all:tt )*)]) => {
+        $d.push(declaration!($name: $( $all )*));
+    };
+    (@sides $d:ident $name:ident [($( $vertical:tt )*) ($( $horizontal:tt )*)]) => {
+        declarations!(@sides $d $name [($( $vertical )*) ($( $horizontal )*) ($( $vertical )*) ($( $horizontal )*)]);
+    };
+    (@sides $d:ident $name:ident [($( $top:tt )*) ($( $horizontal:tt )*) ($( $bottom:tt )*)]) => {
+        declarations!(@sides $d $name [($( $top )*) ($( $horizontal )*) ($( $bottom )*) ($( $horizontal )*)]);
+    };
+    (@sides $d:ident margin [($( $top:tt )*) ($( $right:tt )*) ($( $bottom:tt )*) ($( $left:tt )*)]) => {
+        $d.push(declaration!(margin-top: $( $top )*));
+        $d.push(declaration!(margin-right: $( $right )*));
+        $d.push(declaration!(margin-bottom: $( $bottom )*));
+        $d.push(declaration!(margin-left: $( $left )*));
+    };
+    (@sides $d:ident padding [($( $top:tt )*) ($( $right:tt )*) ($( $bottom:tt )*) ($( $left:tt )*)]) => {
+        $d.push(declaration!(padding-top: $( $top )*));
+        $d.push(declaration!(padding-right: $( $right )*));
+        $d.push(declaration!(padding-bottom: $( $bottom )*));
+        $d.push(declaration!(padding-left: $( $left )*));
+    };
+
+    // Flexibility, given as `none`, `auto`, or a grow factor, shrink factor
+    // and basis in this order, any of which may be omitted
+
+    (@into $d:ident; flex: none) => {
+        declarations!(@flex $d (0) (0) (auto));
+    };
+    (@into $d:ident; flex: auto) => {
+        declarations!(@flex $d (1) (1) (auto));
+    };
+    (@into $d:ident; flex: $grow:tt $shrink:tt auto) => {
+        declarations!(@flex $d ($grow) ($shrink) (auto));
+    };
+    (@into $d:ident; flex: $grow:tt $shrink:tt $basis:tt px) => {
+        declarations!(@flex $d ($grow) ($shrink) ($basis px));
+    };
+    (@into $d:ident; flex: $grow:tt $shrink:tt $basis:tt %) => {
+        declarations!(@flex $d ($grow) ($shrink) ($basis %));
+    };
+    (@into $d:ident; flex: $grow:tt auto) => {
+        declarations!(@flex $d ($grow) (1) (auto));
+    };
+    (@into $d:ident; flex: $grow:tt $basis:tt px) => {
+        declarations!(@flex $d ($grow) (1) ($basis px));
+    };
+    (@into $d:ident; flex: $grow:tt $basis:tt %) => {
+        declarations!(@flex $d ($grow) (1) ($basis %));
+    };
+    (@into $d:ident; flex: $basis:tt px) => {
+        declarations!(@flex $d (1) (1) ($basis px));
+    };
+    (@into $d:ident; flex: $basis:tt %) => {
+        declarations!(@flex $d (1) (1) ($basis %));
+    };
+    (@into $d:ident; flex: $grow:tt $shrink:tt) => {
+        declarations!(@flex $d ($grow) ($shrink) (0 %));
+    };
+    (@into $d:ident; flex: $grow:tt) => {
+        declarations!(@flex $d ($grow) (1) (0 %));
+    };
+    (@flex $d:ident ($grow:tt) ($shrink:tt) ($( $basis:tt )*)) => {
+        $d.push(declaration!(flex-grow: $grow));
+        $d.push(declaration!(flex-shrink: $shrink));
+        $d.push(declaration!(flex-basis: $( $basis )*));
+    };
+
+    // Borders, given a width in pixels, a style and a color in any order, any
+    // of which may be omitted. The width defaults to `medium`, which is 3
+    // pixels like in Servo.
+
+    (@into $d:ident; border: $( $value:tt )*) => {
+        declarations!(@border $d [3] [none] [currentColor] $( $value )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] $width:tt px $( $rest:tt )*) => {
+        declarations!(@border $d [$width] [$( $s )*] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] none $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [none] [$( $c )*] $( $rest )*);
     };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] solid $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [solid] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] double $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [double] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] dotted $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [dotted] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] dashed $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [dashed] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] hidden $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [hidden] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] groove $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [groove] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] ridge $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [ridge] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] inset $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [inset] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] outset $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [outset] [$( $c )*] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] $color:ident ($( $args:tt )*) $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [$( $s )*] [$color($( $args )*)] $( $rest )*);
+    };
+    (@border $d:ident [$( $w:tt )*] [$( $s:tt )*] [$( $c:tt )*] $color:ident $( $rest:tt )*) => {
+        declarations!(@border $d [$( $w )*] [$( $s )*] [$color] $( $rest )*);
+    };
+    (@border $d:ident [$width:tt] [$style:ident] [$( $color:tt )+]) => {
+        $d.push(declaration!(border-top-width: $width));
+        $d.push(declaration!(border-right-width: $width));
+        $d.push(declaration!(border-bottom-width: $width));
+        $d.push(declaration!(border-left-width: $width));
+        $d.push(declaration!(border-top-style: $style));
+        $d.push(declaration!(border-right-style: $style));
+        $d.push(declaration!(border-bottom-style: $style));
+        $d.push(declaration!(border-left-style: $style));
+        $d.push(declaration!(border-top-color: $( $color )+));
+        $d.push(declaration!(border-right-color: $( $color )+));
+        $d.push(declaration!(border-bottom-color: $( $color )+));
+        $d.push(declaration!(border-left-color: $( $color )+));
+    };
+
+    // Fonts, given optional style, caps, weight and stretch keywords in any
+    // order, followed by a size and a family
+
+    (@into $d:ident; font: $( $value:tt )*) => {
+        declarations!(@font $d [Normal] [Normal] [Normal] [Normal] $( $value )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] normal $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [$( $st )*] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] italic $( $rest:tt )*) => {
+        declarations!(@font $d [Italic] [$( $c )*] [$( $w )*] [$( $st )*] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] oblique $( $rest:tt )*) => {
+        declarations!(@font $d [Oblique] [$( $c )*] [$( $w )*] [$( $st )*] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] small-caps $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [SmallCaps] [$( $w )*] [$( $st )*] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] bold $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [Bold] [$( $st )*] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] bolder $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [Bolder] [$( $st )*] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] lighter $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [Lighter] [$( $st )*] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] ultra-condensed $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [UltraCondensed] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] extra-condensed $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [ExtraCondensed] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] condensed $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [Condensed] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] semi-condensed $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [SemiCondensed] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] semi-expanded $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [SemiExpanded] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] expanded $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [Expanded] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] extra-expanded $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [ExtraExpanded] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] ultra-expanded $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [$( $w )*] [UltraExpanded] $( $rest )*);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] $size:tt px $( $family:tt )+) => {
+        declarations!(@font-end $d [$( $s )*] [$( $c )*] [$( $w )*] [$( $st )*] ($size px) $( $family )+);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] $size:tt % $( $family:tt )+) => {
+        declarations!(@font-end $d [$( $s )*] [$( $c )*] [$( $w )*] [$( $st )*] ($size %) $( $family )+);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] $first:ident-$second:ident $( $family:tt )+) => {
+        declarations!(@font-end $d [$( $s )*] [$( $c )*] [$( $w )*] [$( $st )*] ($first-$second) $( $family )+);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] $size:ident $( $family:tt )+) => {
+        declarations!(@font-end $d [$( $s )*] [$( $c )*] [$( $w )*] [$( $st )*] ($size) $( $family )+);
+    };
+    (@font $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] $weight:tt $( $rest:tt )*) => {
+        declarations!(@font $d [$( $s )*] [$( $c )*] [Weight($weight)] [$( $st )*] $( $rest )*);
+    };
+    (@font-end $d:ident [$( $s:tt )*] [$( $c:tt )*] [$( $w:tt )*] [$( $st:tt )*] ($( $size:tt )*) $( $family:tt )+) => {
+        $d.push(StyleDeclaration::Theme(ThemeStyle::FontStyle(FontStyle::$( $s )*)));
+        $d.push(StyleDeclaration::Theme(ThemeStyle::FontCaps(FontCaps::$( $c )*)));
+        $d.push(StyleDeclaration::Theme(ThemeStyle::FontWeight(FontWeight::$( $w )*)));
+        $d.push(StyleDeclaration::Theme(ThemeStyle::FontStretch(FontStretch::$( $st )*)));
+        $d.push(declaration!(font-size: $( $size )*));
+        $d.push(StyleDeclaration::Theme(ThemeStyle::FontFamily(value!(font-family: $( $family )+))));
+    };
+
+    // Longhands, and shorthands given a single value
+
+    (@into $d:ident; $( $tt:tt )*) => {
+        $d.push(declaration!($( $tt )*));
+    };
+
+    ($( $tt:tt )*) => {{
+        let mut declarations = Vec::new();
+        declarations!(@into declarations; $( $tt )*);
+        declarations
+    }};
+}
+
+#[macro_export]
+macro_rules! style {
+    ( $( $name:tt$(-$suffix:tt)*: { $( $value:tt )* } );* $(;)*) => {{
+        let mut declarations = StyleDeclarations::default();
+        $( declarations!(@into declarations; $name$(-$suffix)* : $($value)*); )*
+        declarations
+    }};
     ( #include("defaults.css") $(;)*) => {
         StyleDeclarations(SmallVec::from_buf([
             declaration!(flex-direction: row),
@@ -421,21 +1256,9 @@ macro_rules! style {
             declaration!(flex-basis: auto)
         ]))
     };
-    ( #include("defaults.css"); $( $name:tt$(-$suffix:tt)*: { $( $value:tt )* } );* $(;)*) => {
-        StyleDeclarations(SmallVec::from_vec(vec![
-            declaration!(flex-direction: row),
-            declaration!(flex-wrap: nowrap),
-            declaration!(justify-content: flex-start),
-            declaration!(align-items: stretch),
-            declaration!(align-content: stretch),
-            declaration!(flex-grow: 0),
-            declaration!(flex-shrink: 1),
-            declaration!(flex-basis: auto),
-            $(
-                declaration!(
-                    $name$(-$suffix)* : $($value)*
-                ),
-            )*
-        ]))
-    };
+    ( #include("defaults.css"); $( $name:tt$(-$suffix:tt)*: { $( $value:tt )* } );* $(;)*) => {{
+        let mut declarations = style!(#include("defaults.css"));
+        $( declarations!(@into declarations; $name$(-$suffix)* : $($value)*); )*
+        declarations
+    }};
 }
@@ -11,11 +11,23 @@ specific language governing permissions and limitations under the License.
 
 #[macro_use]
 mod macros;
+mod color;
+mod fonts;
+mod content;
 mod convert_basic;
+mod index;
+mod modules;
+mod pseudo;
+mod selectors;
+mod state;
+mod usage;
 #[cfg(feature = "css-parse")]
 mod convert_servo;
 
 pub mod types;
+pub mod keywords;
 pub mod longhands;
+pub mod serialize;
 pub mod tokenize;
 pub mod util;
+pub mod variables;
@@ -0,0 +1,128 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use std::collections::{btree_map, BTreeMap};
+
+use styles::serialize::{CssFormat, ToCss};
+use styles::types::{StyleSelector, Stylesheet};
+
+// Maps the class names written in a stylesheet to the hashed names they were
+// rewritten to, e.g. `button` to `button_3fa2c41e`.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct ClassNames(pub(crate) BTreeMap<String, String>);
+
+impl ClassNames {
+    pub fn get<T>(&self, name: T) -> Option<&str>
+    where
+        T: AsRef<str>
+    {
+        self.0.get(name.as_ref()).map(|hashed| hashed.as_str())
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<String, String> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Vec<(&'static str, &'static str)>> for ClassNames {
+    fn from(names: Vec<(&'static str, &'static str)>) -> Self {
+        ClassNames(names.into_iter().map(|(name, hashed)| (name.to_owned(), hashed.to_owned())).collect())
+    }
+}
+
+impl Stylesheet {
+    // Rewrites every class selector to a name unique to this stylesheet, the
+    // way CSS modules keep class names local to a component. The hash only
+    // depends on the class name and the stylesheet's contents, so converting
+    // the same CSS always gives the same names.
+    pub fn hash_class_names(&mut self) -> ClassNames {
+        let seed = self.to_css_string(CssFormat::Minified);
+        let mut names = BTreeMap::new();
+        self.map_selectors(|selector| {
+            let rewritten = rewrite_classes(selector.as_ref(), |class| {
+                names
+                    .entry(class.to_owned())
+                    .or_insert_with(|| format!("{}_{:08x}", class, hash(&seed, class)))
+                    .clone()
+            });
+            StyleSelector::from(rewritten)
+        });
+        ClassNames(names)
+    }
+}
+
+// 32-bit FNV-1a. Unlike the standard library's hasher, it's guaranteed to
+// stay the same between compiler versions, which matters since names are also
+// computed at compile time.
+fn hash(seed: &str, class: &str) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for &byte in seed.as_bytes().iter().chain(&[0]).chain(class.as_bytes()) {
+        hash ^= u32::from(byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+// Replaces each class name in a selector, leaving attribute selectors
+// untouched.
+fn rewrite_classes<F>(selector: &str, mut f: F) -> String
+where
+    F: FnMut(&str) -> String
+{
+    let mut rewritten = String::with_capacity(selector.len());
+    let mut chars = selector.char_indices().peekable();
+    let mut depth = 0;
+
+    while let Some((i, c)) = chars.next() {
+        rewritten.push(c);
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    rewritten.push(escaped);
+                }
+            }
+            '.' if depth == 0 => {
+                let start = i + 1;
+                let mut end = start;
+                loop {
+                    let next = chars.peek().cloned();
+                    end = match next {
+                        Some((j, '\\')) => {
+                            chars.next();
+                            chars.next().map(|(k, c)| k + c.len_utf8()).unwrap_or(j + 1)
+                        }
+                        Some((j, c)) if c.is_alphanumeric() || c == '-' || c == '_' => {
+                            chars.next();
+                            j + c.len_utf8()
+                        }
+                        _ => break
+                    };
+                }
+                if end > start {
+                    rewritten.push_str(&f(&selector[start..end]));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    rewritten
+}
@@ -0,0 +1,80 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use styles::index::SelectorKey;
+use styles::selectors::{rightmost_compound, Simple, SimpleSelector};
+use styles::types::{StyleDeclarations, StyleRule, Stylesheet};
+
+// The pseudo-elements generating content before or after an element's own.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Serialize, Deserialize)]
+pub enum PseudoElement {
+    Before,
+    After
+}
+
+impl PseudoElement {
+    pub fn from_name<T>(name: T) -> Option<Self>
+    where
+        T: AsRef<str>
+    {
+        let name = name.as_ref();
+        if name.eq_ignore_ascii_case("before") {
+            Some(PseudoElement::Before)
+        } else if name.eq_ignore_ascii_case("after") {
+            Some(PseudoElement::After)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            &PseudoElement::Before => "before",
+            &PseudoElement::After => "after"
+        }
+    }
+}
+
+impl Stylesheet {
+    // The declarations of the first rule for a pseudo-element of the elements
+    // matching `selector`, so `get_pseudo("li", PseudoElement::Before)` finds
+    // the rule for `li::before`.
+    pub fn get_pseudo<T>(&self, selector: T, pseudo: PseudoElement) -> Option<&StyleDeclarations>
+    where
+        T: AsRef<str>
+    {
+        let selector = selector.as_ref();
+        self.find(format!("{}::{}", selector, pseudo.name()))
+            .or_else(|| self.find(format!("{}:{}", selector, pseudo.name())))
+            .map(move |i| &self.rules[i].declarations)
+    }
+
+    // Same as `rules_for`, for the rules of a pseudo-element. Those are never
+    // returned by `rules_for`, which only finds rules for elements.
+    pub fn pseudo_rules_for(&self, key: SelectorKey, pseudo: PseudoElement) -> Vec<&StyleRule> {
+        self.index.get_pseudo(key, pseudo).into_iter().map(|i| &self.rules[i]).collect()
+    }
+}
+
+// Splits a trailing pseudo-element off a selector, so that `li::before`
+// becomes `li` with `PseudoElement::Before`. The legacy single colon syntax
+// is accepted too.
+pub fn split_pseudo_element(selector: &str) -> (&str, Option<PseudoElement>) {
+    let selector = selector.trim();
+    let (start, name) = match rightmost_compound(selector).last() {
+        Some(&Simple { start, selector: SimpleSelector::PseudoElement(name) }) => (start, name),
+        _ => return (selector, None)
+    };
+    match PseudoElement::from_name(name) {
+        Some(pseudo) => (selector[..start].trim_right(), Some(pseudo)),
+        None => (selector, None)
+    }
+}
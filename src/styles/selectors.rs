@@ -0,0 +1,170 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+#[cfg(feature = "css-parse")]
+use servo_css_parser::cssparser::{Parser, ParserInput, Token};
+
+// The parts of a compound selector that rules are looked up by. Names are
+// kept as written, escapes included.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SimpleSelector<'a> {
+    Id(&'a str),
+    Class(&'a str),
+    Tag(&'a str),
+    Universal,
+    Attribute,
+    PseudoClass(&'a str),
+    // Functional pseudo-classes, like `:not(...)`, whose arguments belong to
+    // other compound selectors.
+    PseudoClassFunction,
+    PseudoElement(&'a str)
+}
+
+// A simple selector, and the offset it starts at in the selector text.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Simple<'a> {
+    pub start: usize,
+    pub selector: SimpleSelector<'a>
+}
+
+// The simple selectors of the part of a complex selector after its last
+// combinator, in order. The selector is tokenized, so that strings, escapes
+// and the contents of attribute selectors and functional pseudo-classes are
+// never mistaken for other simple selectors.
+#[cfg(feature = "css-parse")]
+pub fn rightmost_compound(selector: &str) -> Vec<Simple> {
+    let mut input = ParserInput::new(selector);
+    let mut parser = Parser::new(&mut input);
+    let begin = parser.position();
+    let mut compound = vec![];
+
+    loop {
+        let start = parser.position();
+        let token = match parser.next_including_whitespace() {
+            Ok(token) => token.clone(),
+            Err(_) => return compound
+        };
+        let simple = match token {
+            Token::WhiteSpace(_) | Token::Delim('>') | Token::Delim('+') | Token::Delim('~') => {
+                compound.clear();
+                continue;
+            }
+            Token::IDHash(_) | Token::Hash(_) => SimpleSelector::Id(&parser.slice_from(start)[1..]),
+            Token::Delim('.') => {
+                let name = parser.position();
+                let is_ident = match parser.next_including_whitespace() {
+                    Ok(&Token::Ident(_)) => true,
+                    _ => false
+                };
+                if !is_ident {
+                    continue;
+                }
+                SimpleSelector::Class(parser.slice_from(name))
+            }
+            Token::Ident(_) => SimpleSelector::Tag(parser.slice_from(start)),
+            Token::Delim('*') => SimpleSelector::Universal,
+            Token::SquareBracketBlock => SimpleSelector::Attribute,
+            Token::Colon => match pseudo(&mut parser) {
+                Some(simple) => simple,
+                None => continue
+            },
+            _ => continue
+        };
+        compound.push(Simple {
+            start: parser.slice(begin..start).len(),
+            selector: simple
+        });
+    }
+}
+
+// Looking into selectors requires the CSS parser. Without it, no simple
+// selectors are found, so rules are all filed as universal ones, and neither
+// states nor pseudo-elements are split off.
+#[cfg(not(feature = "css-parse"))]
+pub fn rightmost_compound(_: &str) -> Vec<Simple> {
+    vec![]
+}
+
+// The selectors of a selector list, split at the commas outside of strings,
+// attribute selectors and functional pseudo-classes.
+#[cfg(feature = "css-parse")]
+pub fn split_selector_list(list: &str) -> Vec<&str> {
+    let mut input = ParserInput::new(list);
+    let mut parser = Parser::new(&mut input);
+    let mut selectors = vec![];
+    let mut start = parser.position();
+
+    loop {
+        let end = parser.position();
+        match parser.next_including_whitespace() {
+            Ok(&Token::Comma) => {}
+            Ok(_) => continue,
+            Err(_) => break
+        }
+        selectors.push(parser.slice(start..end).trim());
+        start = parser.position();
+    }
+
+    selectors.push(parser.slice_from(start).trim());
+    selectors
+}
+
+#[cfg(not(feature = "css-parse"))]
+pub fn split_selector_list(list: &str) -> Vec<&str> {
+    vec![list.trim()]
+}
+
+// Whether a selector starts with a pseudo-element, without a compound
+// selector for it to apply to, like `::before`.
+#[cfg(feature = "css-parse")]
+pub fn starts_with_pseudo_element(selector: &str) -> bool {
+    let mut input = ParserInput::new(selector);
+    let mut parser = Parser::new(&mut input);
+    let is_colon = match parser.next() {
+        Ok(&Token::Colon) => true,
+        _ => false
+    };
+    if !is_colon {
+        return false;
+    }
+    match pseudo(&mut parser) {
+        Some(SimpleSelector::PseudoElement(_)) => true,
+        _ => false
+    }
+}
+
+#[cfg(not(feature = "css-parse"))]
+pub fn starts_with_pseudo_element(selector: &str) -> bool {
+    selector.trim_left().starts_with("::")
+}
+
+// The pseudo-class or pseudo-element following a colon. The CSS 2 ones are
+// pseudo-elements even with the legacy single colon syntax.
+#[cfg(feature = "css-parse")]
+fn pseudo<'i, 't>(parser: &mut Parser<'i, 't>) -> Option<SimpleSelector<'i>> {
+    let start = parser.position();
+    let token = parser.next_including_whitespace().ok()?.clone();
+    match token {
+        Token::Colon => {
+            let start = parser.position();
+            match parser.next_including_whitespace().ok()?.clone() {
+                Token::Ident(_) => Some(SimpleSelector::PseudoElement(parser.slice_from(start))),
+                _ => None
+            }
+        }
+        Token::Ident(ref name) if ["before", "after", "first-line", "first-letter"].iter().any(|legacy| name.eq_ignore_ascii_case(legacy)) => {
+            Some(SimpleSelector::PseudoElement(parser.slice_from(start)))
+        }
+        Token::Ident(_) => Some(SimpleSelector::PseudoClass(parser.slice_from(start))),
+        Token::Function(_) => Some(SimpleSelector::PseudoClassFunction),
+        _ => None
+    }
+}
@@ -0,0 +1,439 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use std::fmt;
+
+use styles::keywords;
+use styles::types::{
+    BoxShadow,
+    ColorValue,
+    Content,
+    ContentItem,
+    Counter,
+    CounterStyle,
+    CssWideKeyword,
+    FlexStyle,
+    FontFamily,
+    FontName,
+    FontSize,
+    FontWeight,
+    Quotes,
+    StyleDeclaration,
+    StyleDeclarations,
+    StyleRule,
+    StyleUnit,
+    Stylesheet,
+    TextShadow,
+    ThemeStyle
+};
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CssFormat {
+    Pretty,
+    Minified
+}
+
+impl CssFormat {
+    fn comma(&self) -> &'static str {
+        match self {
+            &CssFormat::Pretty => ", ",
+            &CssFormat::Minified => ","
+        }
+    }
+}
+
+// Serializes back to CSS text, which parses into the same values.
+pub trait ToCss {
+    fn to_css<W>(&self, dest: &mut W, format: CssFormat) -> fmt::Result
+    where
+        W: fmt::Write;
+
+    fn to_css_string(&self, format: CssFormat) -> String {
+        let mut css = String::new();
+        self.to_css(&mut css, format).unwrap();
+        css
+    }
+}
+
+impl ToCss for Stylesheet {
+    fn to_css<W>(&self, dest: &mut W, format: CssFormat) -> fmt::Result
+    where
+        W: fmt::Write
+    {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if i > 0 && format == CssFormat::Pretty {
+                dest.write_char('\n')?;
+            }
+            rule.to_css(dest, format)?;
+        }
+        Ok(())
+    }
+}
+
+impl ToCss for StyleRule {
+    fn to_css<W>(&self, dest: &mut W, format: CssFormat) -> fmt::Result
+    where
+        W: fmt::Write
+    {
+        for (i, selector) in self.selectors.iter().enumerate() {
+            if i > 0 {
+                dest.write_str(format.comma())?;
+            }
+            dest.write_str(selector.as_ref())?;
+        }
+        match format {
+            CssFormat::Pretty => {
+                dest.write_str(" {\n")?;
+                write_declarations(dest, &self.declarations, format, "    ")?;
+                dest.write_str("}\n")
+            }
+            CssFormat::Minified => {
+                dest.write_char('{')?;
+                write_declarations(dest, &self.declarations, format, "")?;
+                dest.write_char('}')
+            }
+        }
+    }
+}
+
+impl ToCss for StyleDeclarations {
+    fn to_css<W>(&self, dest: &mut W, format: CssFormat) -> fmt::Result
+    where
+        W: fmt::Write
+    {
+        write_declarations(dest, self, format, "")
+    }
+}
+
+impl ToCss for StyleDeclaration {
+    fn to_css<W>(&self, dest: &mut W, format: CssFormat) -> fmt::Result
+    where
+        W: fmt::Write
+    {
+        for (i, (name, value)) in properties(self, format).into_iter().enumerate() {
+            if i > 0 {
+                dest.write_str(if format == CssFormat::Pretty { "; " } else { ";" })?;
+            }
+            write_property(dest, name, &value, format)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_declarations<W>(dest: &mut W, declarations: &StyleDeclarations, format: CssFormat, indent: &str) -> fmt::Result
+where
+    W: fmt::Write
+{
+    let properties = declarations.iter().flat_map(|declaration| properties(declaration, format));
+    for (i, (name, value)) in properties.enumerate() {
+        match format {
+            CssFormat::Pretty => {
+                dest.write_str(indent)?;
+                write_property(dest, name, &value, format)?;
+                dest.write_str(";\n")?;
+            }
+            CssFormat::Minified => {
+                if i > 0 {
+                    dest.write_char(';')?;
+                }
+                write_property(dest, name, &value, format)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_property<W>(dest: &mut W, name: &str, value: &str, format: CssFormat) -> fmt::Result
+where
+    W: fmt::Write
+{
+    match format {
+        CssFormat::Pretty => write!(dest, "{}: {}", name, value),
+        CssFormat::Minified => write!(dest, "{}:{}", name, value)
+    }
+}
+
+// The CSS properties a declaration stands for. Most map to a single property,
+// but some Yoga styles set several at once. Values only settable through a
+// `font` system font have no longhand equivalent and are left out, as are
+// undefined lengths which are already the initial values.
+fn properties(declaration: &StyleDeclaration, format: CssFormat) -> Vec<(&str, String)> {
+    match declaration {
+        &StyleDeclaration::Unknown => vec![],
+        &StyleDeclaration::Theme(ref theme) => theme_property(theme, format).into_iter().collect(),
+        &StyleDeclaration::Layout(ref layout) => layout_properties(layout),
+        &StyleDeclaration::Custom(ref custom) => vec![(custom.name.as_ref(), custom.value.as_ref().to_owned())],
+        &StyleDeclaration::WithVariables(ref unparsed) => vec![(unparsed.property.as_ref(), unparsed.value.as_ref().to_owned())],
+        &StyleDeclaration::WideKeyword(id, keyword) => vec![(id.name(), wide_keyword(keyword).to_owned())]
+    }
+}
+
+fn theme_property(style: &ThemeStyle, format: CssFormat) -> Option<(&'static str, String)> {
+    let property = match style {
+        &ThemeStyle::Cursor(value) => ("cursor", keywords::cursor(value).to_owned()),
+        &ThemeStyle::Color(ref value) => ("color", color(value)),
+        &ThemeStyle::BackgroundColor(ref value) => ("background-color", color(value)),
+        &ThemeStyle::Opacity(value) => ("opacity", value.to_string()),
+        &ThemeStyle::BorderTopColor(ref value) => ("border-top-color", color(value)),
+        &ThemeStyle::BorderLeftColor(ref value) => ("border-left-color", color(value)),
+        &ThemeStyle::BorderBottomColor(ref value) => ("border-bottom-color", color(value)),
+        &ThemeStyle::BorderRightColor(ref value) => ("border-right-color", color(value)),
+        &ThemeStyle::BorderTopStyle(value) => ("border-top-style", keywords::border_style(value).to_owned()),
+        &ThemeStyle::BorderBottomStyle(value) => ("border-bottom-style", keywords::border_style(value).to_owned()),
+        &ThemeStyle::BorderLeftStyle(value) => ("border-left-style", keywords::border_style(value).to_owned()),
+        &ThemeStyle::BorderRightStyle(value) => ("border-right-style", keywords::border_style(value).to_owned()),
+        &ThemeStyle::BoxShadow(ref shadows) => ("box-shadow", list(shadows.iter().filter_map(box_shadow), format)),
+        &ThemeStyle::TextShadow(ref shadows) => ("text-shadow", list(shadows.iter().filter_map(text_shadow), format)),
+        &ThemeStyle::FontFamily(FontFamily::System) => return None,
+        &ThemeStyle::FontFamily(FontFamily::Values(ref names)) => ("font-family", list(names.iter().map(font_name), format)),
+        &ThemeStyle::FontStyle(value) => ("font-style", keywords::font_style(value)?.to_owned()),
+        &ThemeStyle::FontCaps(value) => ("font-variant-caps", keywords::font_caps(value)?.to_owned()),
+        &ThemeStyle::FontWeight(value) => ("font-weight", font_weight(value)?),
+        &ThemeStyle::FontSize(value) => ("font-size", font_size(value)?),
+        &ThemeStyle::FontStretch(value) => ("font-stretch", keywords::font_stretch(value)?.to_owned()),
+        &ThemeStyle::Visibility(value) => ("visibility", keywords::visibility(value).to_owned()),
+        &ThemeStyle::Content(ref value) => ("content", content(value)),
+        &ThemeStyle::Quotes(ref value) => ("quotes", quotes(value)),
+        &ThemeStyle::CounterIncrement(ref counters) => ("counter-increment", counter_list(counters)),
+        &ThemeStyle::CounterReset(ref counters) => ("counter-reset", counter_list(counters))
+    };
+    Some(property)
+}
+
+fn layout_properties(style: &FlexStyle) -> Vec<(&'static str, String)> {
+    match style {
+        &FlexStyle::AlignContent(value) => vec![("align-content", keywords::align(value).to_owned())],
+        &FlexStyle::AlignItems(value) => vec![("align-items", keywords::align(value).to_owned())],
+        &FlexStyle::AlignSelf(value) => vec![("align-self", keywords::align(value).to_owned())],
+        &FlexStyle::AspectRatio(value) => vec![("aspect-ratio", value.into_inner().to_string())],
+        &FlexStyle::BorderBottom(value) => vec![("border-bottom-width", format!("{}px", value.into_inner()))],
+        &FlexStyle::BorderEnd(value) | &FlexStyle::BorderRight(value) => vec![("border-right-width", format!("{}px", value.into_inner()))],
+        &FlexStyle::BorderLeft(value) | &FlexStyle::BorderStart(value) => vec![("border-left-width", format!("{}px", value.into_inner()))],
+        &FlexStyle::BorderTop(value) => vec![("border-top-width", format!("{}px", value.into_inner()))],
+        &FlexStyle::Border(value) => vec![("border-width", format!("{}px", value.into_inner()))],
+        &FlexStyle::Bottom(value) => lengths(&["bottom"], value),
+        &FlexStyle::Display(value) => vec![("display", keywords::display(value).to_owned())],
+        &FlexStyle::End(value) | &FlexStyle::Right(value) => lengths(&["right"], value),
+        &FlexStyle::Flex(value) => {
+            // Yoga's `flex` is a single number rather than the CSS shorthand.
+            let flex = value.into_inner();
+            vec![("flex-grow", flex.max(0.0).to_string()), ("flex-shrink", (-flex).max(0.0).to_string())]
+        }
+        &FlexStyle::FlexBasis(value) => vec![("flex-basis", size(value))],
+        &FlexStyle::FlexDirection(value) => vec![("flex-direction", keywords::flex_direction(value).to_owned())],
+        &FlexStyle::FlexGrow(value) => vec![("flex-grow", value.into_inner().to_string())],
+        &FlexStyle::FlexShrink(value) => vec![("flex-shrink", value.into_inner().to_string())],
+        &FlexStyle::FlexWrap(value) => vec![("flex-wrap", keywords::flex_wrap(value).to_owned())],
+        &FlexStyle::Height(value) => vec![("height", size(value))],
+        &FlexStyle::JustifyContent(value) => vec![("justify-content", keywords::justify(value).to_owned())],
+        &FlexStyle::Left(value) | &FlexStyle::Start(value) => lengths(&["left"], value),
+        &FlexStyle::Margin(value) => lengths(&["margin"], value),
+        &FlexStyle::MarginBottom(value) => lengths(&["margin-bottom"], value),
+        &FlexStyle::MarginEnd(value) | &FlexStyle::MarginRight(value) => lengths(&["margin-right"], value),
+        &FlexStyle::MarginHorizontal(value) => lengths(&["margin-left", "margin-right"], value),
+        &FlexStyle::MarginLeft(value) | &FlexStyle::MarginStart(value) => lengths(&["margin-left"], value),
+        &FlexStyle::MarginTop(value) => lengths(&["margin-top"], value),
+        &FlexStyle::MarginVertical(value) => lengths(&["margin-top", "margin-bottom"], value),
+        &FlexStyle::MaxHeight(value) => vec![("max-height", max_size(value))],
+        &FlexStyle::MaxWidth(value) => vec![("max-width", max_size(value))],
+        &FlexStyle::MinHeight(value) => lengths(&["min-height"], value),
+        &FlexStyle::MinWidth(value) => lengths(&["min-width"], value),
+        &FlexStyle::Overflow(value) => vec![("overflow", keywords::overflow(value).to_owned())],
+        &FlexStyle::Padding(value) => lengths(&["padding"], value),
+        &FlexStyle::PaddingBottom(value) => lengths(&["padding-bottom"], value),
+        &FlexStyle::PaddingEnd(value) | &FlexStyle::PaddingRight(value) => lengths(&["padding-right"], value),
+        &FlexStyle::PaddingHorizontal(value) => lengths(&["padding-left", "padding-right"], value),
+        &FlexStyle::PaddingLeft(value) | &FlexStyle::PaddingStart(value) => lengths(&["padding-left"], value),
+        &FlexStyle::PaddingTop(value) => lengths(&["padding-top"], value),
+        &FlexStyle::PaddingVertical(value) => lengths(&["padding-top", "padding-bottom"], value),
+        &FlexStyle::Position(value) => vec![("position", keywords::position(value).to_owned())],
+        &FlexStyle::Top(value) => lengths(&["top"], value),
+        &FlexStyle::Width(value) => vec![("width", size(value))]
+    }
+}
+
+fn list<I>(items: I, format: CssFormat) -> String
+where
+    I: Iterator<Item = String>
+{
+    let items: Vec<String> = items.collect();
+    if items.is_empty() {
+        "none".to_owned()
+    } else {
+        items.join(format.comma())
+    }
+}
+
+// Undefined lengths have no CSS value of their own, and are written out
+// depending on the property they're given to.
+fn unit(value: StyleUnit) -> Option<String> {
+    match value {
+        StyleUnit::Point(value) => Some(format!("{}px", value.into_inner())),
+        StyleUnit::Percent(value) => Some(format!("{}%", value.into_inner())),
+        StyleUnit::Auto => Some("auto".to_owned()),
+        StyleUnit::UndefinedValue => None
+    }
+}
+
+// Yoga lays out undefined sizes as `auto` ones.
+fn size(value: StyleUnit) -> String {
+    unit(value).unwrap_or_else(|| "auto".to_owned())
+}
+
+// Converted from `none` maximum sizes.
+fn max_size(value: StyleUnit) -> String {
+    unit(value).unwrap_or_else(|| "none".to_owned())
+}
+
+// Undefined offsets, margins, paddings and minimum sizes are the initial
+// values, so their properties are left out.
+fn lengths(names: &[&'static str], value: StyleUnit) -> Vec<(&'static str, String)> {
+    match unit(value) {
+        Some(value) => names.iter().map(|&name| (name, value.clone())).collect(),
+        None => vec![]
+    }
+}
+
+fn color(value: &ColorValue) -> String {
+    match value {
+        &ColorValue::Rgba(color) => color.to_string(),
+        &ColorValue::CurrentColor => "currentColor".to_owned(),
+        &ColorValue::System(color) => color.name().to_owned()
+    }
+}
+
+// Shadows with undefined lengths are left out.
+fn box_shadow(shadow: &BoxShadow) -> Option<String> {
+    let mut css = String::new();
+    if shadow.inset {
+        css.push_str("inset ");
+    }
+    css.push_str(&format!("{} {}", unit(shadow.horizontal)?, unit(shadow.vertical)?));
+    match (shadow.blur, shadow.spread) {
+        (blur, Some(spread)) => css.push_str(&format!(" {} {}", unit(blur.unwrap_or(StyleUnit::Point(0.0.into())))?, unit(spread)?)),
+        (Some(blur), None) => css.push_str(&format!(" {}", unit(blur)?)),
+        (None, None) => {}
+    }
+    if shadow.color != ColorValue::CurrentColor {
+        css.push_str(&format!(" {}", color(&shadow.color)));
+    }
+    Some(css)
+}
+
+fn text_shadow(shadow: &TextShadow) -> Option<String> {
+    let mut css = format!("{} {}", unit(shadow.horizontal)?, unit(shadow.vertical)?);
+    if let Some(blur) = shadow.blur {
+        css.push_str(&format!(" {}", unit(blur)?));
+    }
+    if shadow.color != ColorValue::CurrentColor {
+        css.push_str(&format!(" {}", color(&shadow.color)));
+    }
+    Some(css)
+}
+
+fn font_name(name: &FontName) -> String {
+    match name {
+        &FontName::Generic(name) => keywords::generic_font_name(name).to_owned(),
+        // Specific names keep their quotes, if any.
+        &FontName::Specific(ref name) => name.as_ref().to_owned()
+    }
+}
+
+fn content(value: &Content) -> String {
+    match value {
+        &Content::Normal => "normal".to_owned(),
+        &Content::None => "none".to_owned(),
+        &Content::Items(ref items) => items.iter().map(content_item).collect::<Vec<_>>().join(" ")
+    }
+}
+
+fn content_item(item: &ContentItem) -> String {
+    match item {
+        &ContentItem::String(ref value) => string(value.as_ref()),
+        &ContentItem::Counter(ref name, CounterStyle::Decimal) => format!("counter({})", name.as_ref()),
+        &ContentItem::Counter(ref name, style) => format!("counter({}, {})", name.as_ref(), style.name()),
+        &ContentItem::Counters(ref name, ref separator, CounterStyle::Decimal) => {
+            format!("counters({}, {})", name.as_ref(), string(separator.as_ref()))
+        }
+        &ContentItem::Counters(ref name, ref separator, style) => {
+            format!("counters({}, {}, {})", name.as_ref(), string(separator.as_ref()), style.name())
+        }
+        &ContentItem::Attr(ref name) => format!("attr({})", name.as_ref()),
+        &ContentItem::OpenQuote => "open-quote".to_owned(),
+        &ContentItem::CloseQuote => "close-quote".to_owned(),
+        &ContentItem::NoOpenQuote => "no-open-quote".to_owned(),
+        &ContentItem::NoCloseQuote => "no-close-quote".to_owned()
+    }
+}
+
+fn quotes(value: &Quotes) -> String {
+    match value {
+        &Quotes::Auto => "auto".to_owned(),
+        &Quotes::None => "none".to_owned(),
+        &Quotes::Pairs(ref pairs) => pairs
+            .iter()
+            .map(|pair| format!("{} {}", string(pair.open.as_ref()), string(pair.close.as_ref())))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn counter_list(counters: &[Counter]) -> String {
+    if counters.is_empty() {
+        return "none".to_owned();
+    }
+    counters
+        .iter()
+        .map(|counter| format!("{} {}", counter.name.as_ref(), counter.value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn string(value: &str) -> String {
+    let mut css = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                css.push('\\');
+                css.push(c);
+            }
+            '\n' => css.push_str("\\a "),
+            _ => css.push(c)
+        }
+    }
+    css.push('"');
+    css
+}
+
+fn font_weight(value: FontWeight) -> Option<String> {
+    match value {
+        FontWeight::Weight(weight) => Some(weight.to_string()),
+        value => keywords::font_weight(value).map(str::to_owned)
+    }
+}
+
+fn font_size(value: FontSize) -> Option<String> {
+    match value {
+        FontSize::System => None,
+        FontSize::Smaller | FontSize::Larger => keywords::relative_font_size(value).map(str::to_owned),
+        FontSize::Keyword(keyword) => Some(keywords::font_size_keyword(keyword).to_owned()),
+        FontSize::Length(length) => unit(length)
+    }
+}
+
+fn wide_keyword(value: CssWideKeyword) -> &'static str {
+    match value {
+        CssWideKeyword::Initial => "initial",
+        CssWideKeyword::Inherit => "inherit",
+        CssWideKeyword::Unset => "unset",
+        CssWideKeyword::Revert => "revert"
+    }
+}
@@ -0,0 +1,202 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use std::collections::BTreeMap;
+use std::ops::{BitAnd, BitOr, BitOrAssign, BitXor};
+
+use styles::index::SelectorKey;
+use styles::selectors::{rightmost_compound, SimpleSelector};
+use styles::types::{StyleDeclarations, Stylesheet};
+
+// The dynamic states of an element which pseudo-classes can select.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Default, Serialize, Deserialize)]
+pub struct ElementState(u8);
+
+impl ElementState {
+    pub const HOVER: ElementState = ElementState(1 << 0);
+    pub const ACTIVE: ElementState = ElementState(1 << 1);
+    pub const FOCUS: ElementState = ElementState(1 << 2);
+    pub const DISABLED: ElementState = ElementState(1 << 3);
+
+    pub fn empty() -> Self {
+        ElementState(0)
+    }
+
+    pub fn all() -> Self {
+        ElementState::HOVER | ElementState::ACTIVE | ElementState::FOCUS | ElementState::DISABLED
+    }
+
+    pub fn from_pseudo_class<T>(name: T) -> Option<Self>
+    where
+        T: AsRef<str>
+    {
+        let name = name.as_ref();
+        [
+            ("hover", ElementState::HOVER),
+            ("active", ElementState::ACTIVE),
+            ("focus", ElementState::FOCUS),
+            ("disabled", ElementState::DISABLED)
+        ].iter()
+            .find(|&&(pseudo_class, _)| pseudo_class.eq_ignore_ascii_case(name))
+            .map(|&(_, state)| state)
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: ElementState) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(&self, other: ElementState) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn insert(&mut self, other: ElementState) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: ElementState) {
+        self.0 &= !other.0;
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+impl BitOr for ElementState {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        ElementState(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for ElementState {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for ElementState {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        ElementState(self.0 & other.0)
+    }
+}
+
+impl BitXor for ElementState {
+    type Output = Self;
+
+    fn bitxor(self, other: Self) -> Self {
+        ElementState(self.0 ^ other.0)
+    }
+}
+
+impl Stylesheet {
+    // The declarations for elements matching `selector` while in `state`:
+    // those of rules for the selector itself, followed by those of rules for
+    // the selector with state pseudo-classes, like `.btn:hover`, when all of
+    // their states are set. Pseudo-classes add to specificity, so rules with
+    // more of them come later, and win once applied.
+    pub fn get_for_state<T>(&self, selector: T, state: ElementState) -> StyleDeclarations
+    where
+        T: AsRef<str>
+    {
+        let selector = selector.as_ref();
+        let mut matched = vec![];
+        for i in self.index.get(SelectorKey::of(selector)) {
+            for rule_selector in &self.rules[i].selectors {
+                let (base, required) = split_state(rule_selector.as_ref());
+                if base == selector && state.contains(required) {
+                    self.usage.record(rule_selector.as_ref());
+                    matched.push((self.origins[i], required.count(), i));
+                    break;
+                }
+            }
+        }
+
+        matched.sort();
+        StyleDeclarations(
+            matched
+                .into_iter()
+                .flat_map(move |(_, _, i)| self.rules[i].declarations.iter().cloned())
+                .collect()
+        )
+    }
+
+    // The states which change the declarations for elements matching
+    // `selector`. Changes to other states don't need restyling.
+    pub fn affecting_states<T>(&self, selector: T) -> ElementState
+    where
+        T: AsRef<str>
+    {
+        let selector = selector.as_ref();
+        let mut states = ElementState::empty();
+        for i in self.index.get(SelectorKey::of(selector)) {
+            for rule_selector in &self.rules[i].selectors {
+                let (base, required) = split_state(rule_selector.as_ref());
+                if base == selector {
+                    states |= required;
+                }
+            }
+        }
+        states
+    }
+
+    // Same as `affecting_states`, precomputed for every selector with state
+    // pseudo-classes in the stylesheet, keyed by the selector without them.
+    pub fn state_dependencies(&self) -> BTreeMap<String, ElementState> {
+        let mut dependencies = BTreeMap::new();
+        for rule in &self.rules {
+            for selector in &rule.selectors {
+                let (base, required) = split_state(selector.as_ref());
+                if !required.is_empty() {
+                    *dependencies.entry(base.to_owned()).or_insert_with(ElementState::empty) |= required;
+                }
+            }
+        }
+        dependencies
+    }
+}
+
+// Splits the state pseudo-classes off the end of a selector, so that
+// `.btn:hover:focus` becomes `.btn` with the hover and focus states. Only
+// pseudo-classes applying to the selected element itself are split off, not
+// those of its ancestors, those within `:not(...)`, nor those following a
+// combinator.
+fn split_state(selector: &str) -> (&str, ElementState) {
+    let selector = selector.trim();
+    let compound = rightmost_compound(selector);
+    let mut base = selector;
+    let mut state = ElementState::empty();
+
+    for (i, simple) in compound.iter().enumerate().rev() {
+        let flag = match simple.selector {
+            SimpleSelector::PseudoClass(name) if i > 0 => ElementState::from_pseudo_class(name),
+            _ => None
+        };
+        match flag {
+            Some(flag) => state.insert(flag),
+            None => break
+        }
+        base = &selector[..simple.start];
+    }
+
+    (base, state)
+}
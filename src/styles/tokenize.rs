@@ -11,13 +11,53 @@ specific language governing permissions and limitations under the License.
 
 use self_tokenize_trait::{ToCustomTokens, Tokens};
 
-use styles::types::{FontName, GenericFontName, SpecificFontName, StyleSelector, Stylesheet};
+use styles::types::{
+    ClassNames,
+    ContentString,
+    CounterName,
+    CustomPropertyName,
+    FontName,
+    GenericFontName,
+    InlineRules,
+    PropertyName,
+    SpecificFontName,
+    StyleOrigin,
+    StyleSelector,
+    Stylesheet,
+    UnparsedValue
+};
 
 impl ToCustomTokens for Stylesheet {
     fn to_custom_tokens(&self, tokens: &mut Tokens) {
+        if self.origins.iter().all(|&origin| origin == StyleOrigin::default()) {
+            let mut inner_tokens = Tokens::new();
+            self.rules.to_custom_tokens(&mut inner_tokens);
+            tokens.append(quote! { Stylesheet::from(#inner_tokens) });
+            return;
+        }
+
+        // Combine runs of rules sharing the same origin.
         let mut inner_tokens = Tokens::new();
-        self.rules.to_custom_tokens(&mut inner_tokens);
-        tokens.append(quote! { Stylesheet::from(#inner_tokens) });
+        let mut start = 0;
+        for end in 1..self.rules.len() + 1 {
+            if end < self.rules.len() && self.origins[end] == self.origins[start] {
+                continue;
+            }
+            let mut rules_tokens = Tokens::new();
+            let mut origin_tokens = Tokens::new();
+            self.rules[start..end].iter().cloned().collect::<InlineRules>().to_custom_tokens(&mut rules_tokens);
+            self.origins[start].to_custom_tokens(&mut origin_tokens);
+            inner_tokens.append(quote! { Stylesheet::from(#rules_tokens).with_origin(#origin_tokens), });
+            start = end;
+        }
+        tokens.append(quote! { vec![#inner_tokens].into_iter().collect::<Stylesheet>() });
+    }
+}
+
+impl ToCustomTokens for ClassNames {
+    fn to_custom_tokens(&self, tokens: &mut Tokens) {
+        let names = self.iter().map(|(name, hashed)| quote! { (#name, #hashed) });
+        tokens.append(quote! { ClassNames::from(vec![#(#names),*]) });
     }
 }
 
@@ -65,3 +105,38 @@ impl ToCustomTokens for SpecificFontName {
         tokens.append(quote! { SpecificFontName::from(#string_ref) });
     }
 }
+
+impl ToCustomTokens for CustomPropertyName {
+    fn to_custom_tokens(&self, tokens: &mut Tokens) {
+        let string_ref: &str = self.as_ref();
+        tokens.append(quote! { CustomPropertyName::from(#string_ref) });
+    }
+}
+
+impl ToCustomTokens for PropertyName {
+    fn to_custom_tokens(&self, tokens: &mut Tokens) {
+        let string_ref: &str = self.as_ref();
+        tokens.append(quote! { PropertyName::from(#string_ref) });
+    }
+}
+
+impl ToCustomTokens for UnparsedValue {
+    fn to_custom_tokens(&self, tokens: &mut Tokens) {
+        let string_ref: &str = self.as_ref();
+        tokens.append(quote! { UnparsedValue::from(#string_ref) });
+    }
+}
+
+impl ToCustomTokens for ContentString {
+    fn to_custom_tokens(&self, tokens: &mut Tokens) {
+        let string_ref: &str = self.as_ref();
+        tokens.append(quote! { ContentString::from(#string_ref) });
+    }
+}
+
+impl ToCustomTokens for CounterName {
+    fn to_custom_tokens(&self, tokens: &mut Tokens) {
+        let string_ref: &str = self.as_ref();
+        tokens.append(quote! { CounterName::from(#string_ref) });
+    }
+}
@@ -11,6 +11,7 @@ specific language governing permissions and limitations under the License.
 
 use std::borrow::Cow;
 use std::convert::TryInto;
+use std::fmt;
 use std::iter::FromIterator;
 use std::iter::Iterator;
 use std::ops::{Deref, DerefMut};
@@ -21,23 +22,149 @@ use rsx_shared::types::KnownElementName;
 use self_tokenize_macro::{DefaultQuote, SelfTokenize};
 use self_tokenize_trait::ToCustomTokens;
 
+use serde::{Deserialize, Deserializer};
+
+use styles::index::SelectorIndex;
+use styles::selectors::{split_selector_list, starts_with_pseudo_element};
+use styles::usage::UsageTracker;
 use styles::util::{is_layout_style, is_theme_style};
 
+pub use styles::color::{Hsla, Hsva};
+pub use styles::fonts::FontSizeTable;
+pub use styles::index::SelectorKey;
 pub use styles::longhands::*;
+pub use styles::modules::ClassNames;
+pub use styles::pseudo::PseudoElement;
+pub use styles::serialize::{CssFormat, ToCss};
+pub use styles::state::ElementState;
+pub use styles::usage::{SelectorUsage, UsageReport};
 
 pub type InlineRules = SmallVec<[StyleRule; 1]>;
 pub type InlineSelectors = SmallVec<[StyleSelector; 1]>;
 pub type InlineDeclarations = SmallVec<[StyleDeclaration; 8]>;
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, DefaultQuote)]
+#[derive(Debug, PartialEq, Clone, Serialize, DefaultQuote)]
 pub struct Stylesheet {
-    pub(crate) rules: InlineRules
+    pub(crate) rules: InlineRules,
+    // The origin of each rule, since stylesheets can be combined.
+    pub(crate) origins: Vec<StyleOrigin>,
+    #[serde(skip)]
+    pub(crate) index: SelectorIndex,
+    #[serde(skip)]
+    pub(crate) usage: UsageTracker
+}
+
+// The index isn't serialized, so rebuild it from the rules.
+impl<'de> Deserialize<'de> for Stylesheet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        #[derive(Deserialize)]
+        struct Rules {
+            rules: InlineRules,
+            #[serde(default)]
+            origins: Vec<StyleOrigin>
+        }
+
+        Rules::deserialize(deserializer).map(|Rules { rules, origins }| {
+            let mut stylesheet = Stylesheet::from(rules);
+            if origins.len() == stylesheet.rules.len() {
+                stylesheet.origins = origins;
+            }
+            stylesheet
+        })
+    }
+}
+
+// Combines stylesheets in order, keeping the origin of their rules.
+impl FromIterator<Stylesheet> for Stylesheet {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Stylesheet>
+    {
+        let mut combined = Stylesheet::from(InlineRules::new());
+        for stylesheet in iter {
+            combined.extend(stylesheet);
+        }
+        combined
+    }
 }
 
 impl Stylesheet {
     pub fn push(&mut self, rule: StyleRule) {
         // TODO: dedupe declarations when already pushed with the same selector.
+        self.index.push(&rule);
         self.rules.push(rule);
+        self.origins.push(StyleOrigin::default());
+    }
+
+    // Appends the rules of another stylesheet after this one's, keeping the
+    // origin of each rule.
+    pub fn extend(&mut self, mut other: Stylesheet) {
+        for (rule, origin) in other.rules.drain().zip(other.origins.drain(..)) {
+            self.index.push(&rule);
+            self.rules.push(rule);
+            self.origins.push(origin);
+        }
+    }
+
+    // Marks every rule as coming from the given origin. Converted and parsed
+    // stylesheets are author stylesheets by default.
+    pub fn with_origin(mut self, origin: StyleOrigin) -> Self {
+        for rule_origin in &mut self.origins {
+            *rule_origin = origin;
+        }
+        self
+    }
+
+    // Prefixes every selector with a scope, so that `.title` becomes
+    // `.component-x .title` when scoped to `.component-x`. Pseudo-elements
+    // without a compound selector of their own apply to the scope itself,
+    // so `::before` becomes `.component-x::before`. Selector lists are split
+    // into selectors, each scoped on its own.
+    pub fn scoped<T>(mut self, scope: T) -> Self
+    where
+        T: AsRef<str>
+    {
+        let scope = scope.as_ref();
+        for rule in &mut self.rules {
+            let selectors = rule.selectors
+                .iter()
+                .flat_map(|selector| split_selector_list(selector.as_ref()))
+                .map(|selector| {
+                    if starts_with_pseudo_element(selector) {
+                        StyleSelector::from(format!("{}{}", scope, selector))
+                    } else {
+                        StyleSelector::from(format!("{} {}", scope, selector))
+                    }
+                })
+                .collect();
+            rule.selectors = StyleSelectors(selectors);
+        }
+        self.index = SelectorIndex::new(&self.rules);
+        self
+    }
+
+    // Replaces every selector, in every rule.
+    pub(crate) fn map_selectors<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&StyleSelector) -> StyleSelector
+    {
+        for rule in &mut self.rules {
+            for selector in &mut rule.selectors {
+                *selector = f(selector);
+            }
+        }
+        self.index = SelectorIndex::new(&self.rules);
+    }
+
+    // Rules in cascade order: by origin, then in source order, so that
+    // declarations from later rules win once applied.
+    pub fn cascade_order(&self) -> Vec<(StyleOrigin, &StyleRule)> {
+        let mut rules: Vec<_> = self.origins.iter().cloned().zip(self.rules.iter()).collect();
+        rules.sort_by_key(|&(origin, _)| origin);
+        rules
     }
 
     pub fn index_of<T>(&self, selector: T) -> Option<usize>
@@ -45,25 +172,91 @@ impl Stylesheet {
         T: AsRef<str>
     {
         // TODO: actually handle selector matching, specificity etc.
-        self.rules
-            .iter()
-            .position(|&StyleRule { ref selectors, .. }| selectors.iter().any(|s| s.as_ref() == selector.as_ref()))
+        self.index.first(selector.as_ref())
+    }
+
+    // Rules with a selector filed under the given key, in source order. For
+    // example, `SelectorKey::Class("foo")` finds both `.foo` and `div > .foo`.
+    pub fn rules_for(&self, key: SelectorKey) -> Vec<&StyleRule> {
+        self.index.get(key).into_iter().map(|i| &self.rules[i]).collect()
     }
 
+    // Same as `index_of`, but counts as a use of the selector when found.
+    pub(crate) fn find<T>(&self, selector: T) -> Option<usize>
+    where
+        T: AsRef<str>
+    {
+        let index = self.index_of(&selector);
+        if index.is_some() {
+            self.usage.record(selector.as_ref());
+        }
+        index
+    }
+
+    // Takes the selector out of the first rule containing it, keeping the rule
+    // and the order of all rules intact for its other selectors.
     pub fn take<T>(&mut self, selector: T) -> StyleDeclarations
     where
         T: AsRef<str>
     {
-        self.index_of(selector)
-            .map(|i| self.rules.swap_remove(i).declarations)
-            .unwrap_or_else(|| StyleDeclarations(InlineDeclarations::default()))
+        match self.find(&selector) {
+            Some(i) => self.detach(i, selector.as_ref()),
+            None => StyleDeclarations(InlineDeclarations::default())
+        }
+    }
+
+    // Takes the selector out of every rule containing it, merging their
+    // declarations in cascade order so that later ones win once applied.
+    pub fn take_all<T>(&mut self, selector: T) -> StyleDeclarations
+    where
+        T: AsRef<str>
+    {
+        let mut taken = vec![];
+        let mut next = self.find(&selector);
+        while let Some(i) = next {
+            let origin = self.origins[i];
+            taken.push((origin, self.detach(i, selector.as_ref())));
+            next = self.index_of(&selector);
+        }
+        taken.sort_by_key(|&(origin, _)| origin);
+        StyleDeclarations(taken.into_iter().flat_map(|(_, declarations)| declarations.0).collect())
+    }
+
+    // Removes the selector from every rule containing it, without counting as
+    // a use. Rules are only removed once they have no selectors left. Returns
+    // whether any rule contained the selector.
+    pub fn remove_selector<T>(&mut self, selector: T) -> bool
+    where
+        T: AsRef<str>
+    {
+        let mut removed = false;
+        while let Some(i) = self.index_of(&selector) {
+            self.detach(i, selector.as_ref());
+            removed = true;
+        }
+        removed
+    }
+
+    fn detach(&mut self, i: usize, selector: &str) -> StyleDeclarations {
+        if self.rules[i].selectors.iter().all(|s| s.as_ref() == selector) {
+            let rule = self.rules.remove(i);
+            self.origins.remove(i);
+            self.index.remove(i, &rule);
+            return rule.declarations;
+        }
+        let occurrences = self.rules[i].selectors.iter().filter(|s| s.as_ref() == selector).count();
+        for _ in 0..occurrences {
+            self.index.remove_selector(i, selector);
+        }
+        self.rules[i].selectors.retain(|s| s.as_ref() != selector);
+        self.rules[i].declarations.clone()
     }
 
     pub fn get_copy<T>(&mut self, selector: T) -> Option<StyleDeclarations>
     where
         T: AsRef<str>
     {
-        self.index_of(selector)
+        self.find(selector)
             .map(|i| self.rules[i].declarations.clone())
     }
 
@@ -71,29 +264,145 @@ impl Stylesheet {
     where
         T: AsRef<str>
     {
-        self.index_of(selector)
+        self.find(selector)
             .map(move |i| &self.rules[i].declarations)
     }
 
+    // Same as `get_ref`, together with the id of the rule.
+    pub fn get_rule<T>(&self, selector: T) -> Option<(usize, &StyleDeclarations)>
+    where
+        T: AsRef<str>
+    {
+        self.find(selector)
+            .map(move |i| (self.index.id(i), &self.rules[i].declarations))
+    }
+
+    // Identifies the rule at this index, among the rules of all stylesheets,
+    // for as long as it's kept. Used as the key of `StyleSharingCache`, which
+    // has to be cleared when declarations are changed in place. Clones of a
+    // stylesheet keep the ids of its rules.
+    pub fn rule_id(&self, index: usize) -> usize {
+        self.index.id(index)
+    }
+
     pub fn get_mut<T>(&mut self, selector: T) -> Option<&mut StyleDeclarations>
     where
         T: AsRef<str>
     {
-        self.index_of(selector)
+        self.find(selector)
             .map(move |i| &mut self.rules[i].declarations)
     }
+
+    // Starts or stops counting selector lookups, for `usage_report`. Usage
+    // isn't tracked by default.
+    pub fn track_usage(&self, enabled: bool) {
+        self.usage.set_enabled(enabled);
+    }
+
+    // Which selectors were looked up while usage was tracked, and which
+    // weren't. Unlike taking rules, this doesn't change the stylesheet.
+    pub fn usage_report(&self) -> UsageReport {
+        UsageReport {
+            used: self.usage.used(),
+            unused: self.rules
+                .iter()
+                .flat_map(|rule| rule.selectors.iter())
+                .filter(|selector| self.usage.hits(selector.as_ref()) == 0)
+                .cloned()
+                .collect()
+        }
+    }
+
+    pub fn reset_usage(&self) {
+        self.usage.clear();
+    }
+}
+
+// Where a stylesheet comes from, in increasing order of precedence.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize, SelfTokenize)]
+pub enum StyleOrigin {
+    UserAgent,
+    User,
+    Author
+}
+
+impl Default for StyleOrigin {
+    fn default() -> Self {
+        StyleOrigin::Author
+    }
+}
+
+// A declaration dropped when converting a parsed stylesheet, because it can't
+// be represented by a `StyleDeclaration`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ConversionWarning {
+    pub selectors: StyleSelectors,
+    pub location: SourceLocation,
+    pub property: String,
+    pub value: String,
+    pub reason: WarningReason
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum WarningReason {
+    UnsupportedProperty,
+    UnsupportedValue
+}
+
+impl fmt::Display for ConversionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let selectors: Vec<&str> = self.selectors.iter().map(|selector| selector.as_ref()).collect();
+        let reason = match self.reason {
+            WarningReason::UnsupportedProperty => "unsupported property",
+            WarningReason::UnsupportedValue => "unsupported value"
+        };
+        write!(f, "{}: {}: {} in `{}: {}`", self.location, selectors.join(", "), reason, self.property, self.value)
+    }
+}
+
+// Both start at 1.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub line: u32,
+    pub column: u32
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
 }
 
-#[cfg(debug_assertions)]
-#[cfg(feature = "log-unused")]
-impl Drop for Stylesheet {
-    fn drop(&mut self) {
-        self.rules
-            .iter()
-            .flat_map(|rule| rule.selectors.iter())
-            .for_each(|selector| {
-                println!("Warning: Unused selector: {:?}", selector.css_string.0);
-            });
+// Where the rules of a converted stylesheet come from in the source. It's kept
+// apart from the `Stylesheet` so that it doesn't end up in the tokenized output,
+// and has an entry for each rule, in the order of the rules as converted, so
+// rules with the same selectors are told apart.
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct SourceMap {
+    pub(crate) rules: Vec<(StyleSelectors, SourceLocation)>
+}
+
+impl SourceMap {
+    pub fn push(&mut self, selectors: StyleSelectors, location: SourceLocation) {
+        self.rules.push((selectors, location));
+    }
+
+    // The location of the rule at this index in the converted stylesheet,
+    // before any rule was added to it or taken out of it.
+    pub fn rule_location(&self, index: usize) -> Option<SourceLocation> {
+        self.rules.get(index).map(|&(_, location)| location)
+    }
+
+    pub fn iter(&self) -> slice::Iter<(StyleSelectors, SourceLocation)> {
+        self.rules.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
     }
 }
 
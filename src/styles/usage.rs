@@ -0,0 +1,99 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use styles::types::StyleSelector;
+
+// Counts how many times each selector was looked up. Lookups only borrow the
+// stylesheet, hence the interior mutability, behind a lock so that
+// stylesheets can still be shared between threads. Tracking is off until
+// enabled, so that lookups don't pay for the lock otherwise.
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    enabled: AtomicBool,
+    hits: Mutex<BTreeMap<String, usize>>
+}
+
+impl UsageTracker {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn record(&self, selector: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut hits = self.hits.lock().unwrap();
+        if let Some(count) = hits.get_mut(selector) {
+            *count += 1;
+            return;
+        }
+        hits.insert(selector.to_owned(), 1);
+    }
+
+    pub fn hits(&self, selector: &str) -> usize {
+        self.hits.lock().unwrap().get(selector).cloned().unwrap_or(0)
+    }
+
+    pub fn used(&self) -> Vec<SelectorUsage> {
+        self.hits
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(selector, &hits)| SelectorUsage {
+                selector: StyleSelector::from(selector.clone()),
+                hits
+            })
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.hits.lock().unwrap().clear();
+    }
+}
+
+impl Clone for UsageTracker {
+    fn clone(&self) -> Self {
+        UsageTracker {
+            enabled: AtomicBool::new(self.is_enabled()),
+            hits: Mutex::new(self.hits.lock().unwrap().clone())
+        }
+    }
+}
+
+// Usage isn't part of a stylesheet's value.
+impl PartialEq for UsageTracker {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct UsageReport {
+    // Sorted by selector, including the selectors of rules taken out of the
+    // stylesheet since.
+    pub used: Vec<SelectorUsage>,
+    // In source order.
+    pub unused: Vec<StyleSelector>
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SelectorUsage {
+    pub selector: StyleSelector,
+    pub hits: usize
+}
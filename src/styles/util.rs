@@ -9,7 +9,7 @@ CONDITIONS OF ANY KIND, either express or implied. See the License for the
 specific language governing permissions and limitations under the License.
 */
 
-use types::{FlexStyle, StyleDeclaration, ThemeStyle};
+use types::{CustomProperty, FlexStyle, PropertyId, StyleDeclaration, ThemeStyle};
 
 pub fn is_layout_style(declaration: &StyleDeclaration) -> Option<&FlexStyle> {
     match declaration {
@@ -24,3 +24,18 @@ pub fn is_theme_style(declaration: &StyleDeclaration) -> Option<&ThemeStyle> {
         _ => None
     }
 }
+
+pub fn is_custom_property(declaration: &StyleDeclaration) -> Option<&CustomProperty> {
+    match declaration {
+        &StyleDeclaration::Custom(ref v) => Some(v),
+        _ => None
+    }
+}
+
+pub fn is_color_declaration(declaration: &StyleDeclaration) -> bool {
+    match declaration {
+        &StyleDeclaration::Theme(ThemeStyle::Color(..)) | &StyleDeclaration::WideKeyword(PropertyId::Color, ..) => true,
+        &StyleDeclaration::WithVariables(ref v) => v.property.as_ref() == "color",
+        _ => false
+    }
+}
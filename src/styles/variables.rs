@@ -0,0 +1,204 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+#[cfg(feature = "css-parse")]
+use std::cell::RefCell;
+#[cfg(feature = "css-parse")]
+use std::collections::HashMap;
+
+#[cfg(feature = "css-parse")]
+use servo_css_parser::cssparser::{ParseError, Parser, ParserInput, SourcePosition, Token};
+
+#[cfg(feature = "css-parse")]
+use styles::types::StyleDeclarations;
+use styles::types::{CustomProperty, InlineCustomProperties, UnparsedValue};
+
+// Elements with the same custom properties substitute a declaration to the
+// same text, so the parsed result is kept per property and substituted value
+// instead of parsing it again for every element.
+#[cfg(feature = "css-parse")]
+const MAX_PARSED_SUBSTITUTIONS: usize = 1024;
+
+#[cfg(feature = "css-parse")]
+thread_local! {
+    static PARSED_SUBSTITUTIONS: RefCell<HashMap<(String, String), StyleDeclarations>> = RefCell::new(HashMap::new());
+}
+
+// Replaces every `var(--name)` or `var(--name, fallback)` reference in `css`
+// with the value returned by `lookup`, or with the fallback when there's no such
+// custom property. Returns `None` when a reference can't be resolved at all,
+// which makes the whole value invalid at computed-value time. References are
+// found by tokenizing the value, so those in comments or escaped aren't.
+#[cfg(feature = "css-parse")]
+pub fn substitute_variables<F>(css: &str, lookup: &mut F) -> Option<String>
+where
+    F: FnMut(&str) -> Option<String>
+{
+    let mut input = ParserInput::new(css);
+    let mut parser = Parser::new(&mut input);
+    let mut result = String::with_capacity(css.len());
+    let mut copied = parser.position();
+
+    match substitute(&mut parser, lookup, &mut result, &mut copied) {
+        Ok(Some(())) => {}
+        _ => return None
+    }
+
+    result.push_str(parser.slice_from(copied));
+    Some(result)
+}
+
+// Substituting requires the CSS parser, so values are kept as declared.
+#[cfg(not(feature = "css-parse"))]
+pub fn substitute_variables<F>(css: &str, _: &mut F) -> Option<String>
+where
+    F: FnMut(&str) -> Option<String>
+{
+    Some(css.to_owned())
+}
+
+// Parses a declaration after `var()` substitution, reusing the result of a
+// previous parse of the same property and value.
+#[cfg(feature = "css-parse")]
+pub fn parse_substituted(property: &str, value: String) -> StyleDeclarations {
+    let key = (property.to_owned(), value);
+    PARSED_SUBSTITUTIONS.with(|cache| {
+        if let Some(declarations) = cache.borrow().get(&key) {
+            return declarations.clone();
+        }
+
+        let declarations = StyleDeclarations::parse_declaration(&key.0, &key.1);
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= MAX_PARSED_SUBSTITUTIONS {
+            cache.clear();
+        }
+        cache.insert(key, declarations.clone());
+        declarations
+    })
+}
+
+// Computes the custom properties of an element from the ones it inherited and
+// the ones declared on it. Declared values may reference each other in any
+// order; references which end up in a cycle, or can't be resolved, leave the
+// property unset.
+pub fn resolve_custom_properties(inherited: &InlineCustomProperties, declared: &[&CustomProperty]) -> InlineCustomProperties {
+    let mut result = inherited.clone();
+
+    for (i, property) in declared.iter().enumerate() {
+        let name = property.name.as_ref();
+        if declared[..i].iter().any(|p| p.name.as_ref() == name) {
+            continue;
+        }
+
+        let value = resolve_custom_property(name, inherited, declared, &mut Vec::new());
+        result.retain(|p| p.name.as_ref() != name);
+
+        if let Some(value) = value {
+            result.push(CustomProperty {
+                name: property.name.clone(),
+                value: UnparsedValue::from(value)
+            });
+        }
+    }
+
+    result
+}
+
+pub fn find_custom_property<'a>(properties: &'a InlineCustomProperties, name: &str) -> Option<&'a UnparsedValue> {
+    properties
+        .iter()
+        .find(|p| p.name.as_ref() == name)
+        .map(|p| &p.value)
+}
+
+fn resolve_custom_property(
+    name: &str,
+    inherited: &InlineCustomProperties,
+    declared: &[&CustomProperty],
+    resolving: &mut Vec<String>
+) -> Option<String> {
+    match declared.iter().rev().find(|p| p.name.as_ref() == name) {
+        None => find_custom_property(inherited, name).map(|v| v.as_ref().to_owned()),
+        Some(property) => {
+            if resolving.iter().any(|n| n == name) {
+                return None;
+            }
+
+            resolving.push(name.to_owned());
+            let value = substitute_variables(property.value.as_ref(), &mut |n: &str| {
+                resolve_custom_property(n, inherited, declared, resolving)
+            });
+            resolving.pop();
+
+            value
+        }
+    }
+}
+
+// Appends the text read since `copied` to `result`, with `var()` references
+// substituted, up to the end of the current block. Nested blocks may contain
+// references too, like in `calc(var(--size) * 2)`.
+#[cfg(feature = "css-parse")]
+fn substitute<'i, 't, F>(
+    parser: &mut Parser<'i, 't>,
+    lookup: &mut F,
+    result: &mut String,
+    copied: &mut SourcePosition
+) -> Result<Option<()>, ParseError<'i, ()>>
+where
+    F: FnMut(&str) -> Option<String>
+{
+    loop {
+        let start = parser.position();
+        let token = match parser.next_including_whitespace_and_comments() {
+            Ok(token) => token.clone(),
+            Err(_) => return Ok(Some(()))
+        };
+        match token {
+            Token::Function(ref name) if name.eq_ignore_ascii_case("var") => {
+                result.push_str(parser.slice(*copied..start));
+                match parser.parse_nested_block(|parser| var_reference(parser, lookup))? {
+                    Some(value) => result.push_str(&value),
+                    None => return Ok(None)
+                }
+                *copied = parser.position();
+            }
+            Token::Function(_) | Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock => {
+                if parser.parse_nested_block(|parser| substitute(parser, lookup, result, copied))?.is_none() {
+                    return Ok(None);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// The arguments of a `var()` reference, resolved to their value.
+#[cfg(feature = "css-parse")]
+fn var_reference<'i, 't, F>(parser: &mut Parser<'i, 't>, lookup: &mut F) -> Result<Option<String>, ParseError<'i, ()>>
+where
+    F: FnMut(&str) -> Option<String>
+{
+    let name = parser.expect_ident()?.to_string();
+    if parser.is_exhausted() {
+        return Ok(lookup(&name));
+    }
+
+    parser.expect_comma()?;
+    let start = parser.position();
+    while parser.next_including_whitespace_and_comments().is_ok() {}
+    let fallback = parser.slice_from(start).trim();
+
+    Ok(match lookup(&name) {
+        Some(value) => Some(value),
+        None => substitute_variables(fallback, &mut *lookup)
+    })
+}
@@ -9,57 +9,108 @@ CONDITIONS OF ANY KIND, either express or implied. See the License for the
 specific language governing permissions and limitations under the License.
 */
 
+#![feature(proc_macro, try_from)]
 #![recursion_limit = "128"]
 
 #[macro_use]
 extern crate quote;
 extern crate rsx_shared;
+#[macro_use]
 extern crate rsx_stylesheet;
+extern crate rsx_stylesheet_macros;
 extern crate self_tokenize_trait;
 extern crate serde_json;
+extern crate smallvec;
 extern crate syn;
+extern crate yoga;
 
+use std::convert::TryInto;
 use std::iter;
 use std::ops::Deref;
+use std::sync::Arc;
 
-use rsx_shared::traits::{TComputedStyles, TInheritedStyles};
+use rsx_shared::traits::{TComputedStyles, TInheritedStyles, TStyleDeclarations};
+use rsx_shared::types::{KnownElementName, SharedUnit};
+use rsx_stylesheet_macros::{checked_declaration, checked_style, css, css_module, include_css};
 use rsx_stylesheet::servo_css_parser::parse;
 use rsx_stylesheet::servo_css_parser::types::{MediaList, Origin, QuirksMode, Url};
 use rsx_stylesheet::types::{
     Align,
     BorderStyle,
     BoxShadow,
+    ClassNames,
     Color,
+    ColorValue,
+    ComputedLayoutStyles,
     ComputedStyles,
+    ConversionWarning,
+    Content,
+    ContentItem,
+    ContentString,
+    Counter,
+    CounterName,
+    CounterStyle,
+    CssFormat,
+    CssWideKeyword,
     Cursor,
+    CustomProperty,
+    CustomPropertyName,
+    ElementState,
     FlexDirection,
     FlexStyle,
     FontCaps,
     FontFamily,
     FontName,
     FontSize,
+    FontSizeKeyword,
+    FontSizeTable,
     FontStretch,
     FontStyle,
     FontWeight,
     GenericFontName,
+    Hsla,
+    Hsva,
     InlineBoxShadows,
     InlineDeclarations,
     InlineFontNames,
     InlineRules,
     InlineSelectors,
     InlineTextShadows,
+    Justify,
+    Overflow,
+    PositionType,
+    PropertyId,
+    PropertyName,
+    PseudoElement,
+    QuotePair,
+    Quotes,
+    SelectorKey,
+    SelectorUsage,
+    SourceLocation,
     SpecificFontName,
+    StyleChange,
+    StyleDamage,
     StyleDeclaration,
     StyleDeclarations,
+    StyleOrigin,
     StyleRule,
     StyleSelector,
     StyleSelectors,
+    StyleSharingCache,
     StyleUnit,
     Stylesheet,
+    SystemColor,
     TextShadow,
     ThemeStyle,
-    Visibility
+    ToCss,
+    UnparsedDeclaration,
+    UnparsedValue,
+    UsageReport,
+    Visibility,
+    WarningReason,
+    Wrap
 };
+use smallvec::SmallVec;
 
 use FlexStyle::*;
 use StyleDeclaration::*;
@@ -163,6 +214,8 @@ fn test_from_css_3() {
         ])
     );
 
+    // The rule is kept for its other selectors.
+    assert_eq!(stylesheet.take(".someClass").len(), 16);
     assert_eq!(
         stylesheet.take(".someClass"),
         StyleDeclarations(InlineDeclarations::new())
@@ -347,12 +400,12 @@ fn test_from_css_5() {
     assert_eq!(
         stylesheet.take(".root").deref(),
         &InlineDeclarations::from_vec(vec![
-            Theme(BackgroundColor(Color {
+            Theme(BackgroundColor(ColorValue::Rgba(Color {
                 red: 255,
                 green: 0,
                 blue: 0,
                 alpha: 255
-            })),
+            }))),
             Layout(Width(StyleUnit::Point(500.0.into()))),
             Layout(Height(StyleUnit::Point(120.0.into()))),
             Layout(FlexDirection(FlexDirection::Row)),
@@ -366,13 +419,13 @@ fn test_from_css_5() {
     assert_eq!(
         stylesheet.take(".image").deref(),
         &InlineDeclarations::from_vec(vec![
-            Theme(BackgroundColor(Color {
+            Theme(BackgroundColor(ColorValue::Rgba(Color {
                 red: 0,
                 green: 128,
                 blue: 0,
                 alpha: 255
-            })),
-            Theme(Opacity(50)),
+            }))),
+            Theme(Opacity(0.5)),
             Layout(Width(StyleUnit::Point(80.0.into()))),
             Layout(MarginRight(StyleUnit::Point(20.0.into()))),
         ])
@@ -381,18 +434,18 @@ fn test_from_css_5() {
     assert_eq!(
         stylesheet.take(".text").deref(),
         &InlineDeclarations::from_vec(vec![
-            Theme(BackgroundColor(Color {
+            Theme(BackgroundColor(ColorValue::Rgba(Color {
                 red: 0,
                 green: 0,
                 blue: 255,
                 alpha: 255
-            })),
-            Theme(Color(Color {
+            }))),
+            Theme(Color(ColorValue::Rgba(Color {
                 red: 255,
                 green: 255,
                 blue: 0,
                 alpha: 255
-            })),
+            }))),
             Layout(Height(StyleUnit::Point(25.0.into()))),
             Layout(AlignSelf(Align::Center)),
             Layout(FlexGrow(1.0.into())),
@@ -415,7 +468,7 @@ fn test_from_css_box_shadow_1() {
         &InlineDeclarations::from_vec(vec![
             Theme(BoxShadow(InlineBoxShadows::from_vec(vec![
                 BoxShadow {
-                    color: None,
+                    color: ColorValue::CurrentColor,
                     horizontal: StyleUnit::Point(10.0.into()),
                     vertical: StyleUnit::Point(5.0.into()),
                     blur: None,
@@ -442,7 +495,7 @@ fn test_from_css_box_shadow_2() {
         &InlineDeclarations::from_vec(vec![
             Theme(BoxShadow(InlineBoxShadows::from_vec(vec![
                 BoxShadow {
-                    color: Some(Color {
+                    color: ColorValue::Rgba(Color {
                         red: 255,
                         green: 0,
                         blue: 0,
@@ -474,7 +527,7 @@ fn test_from_css_box_shadow_3() {
         &InlineDeclarations::from_vec(vec![
             Theme(BoxShadow(InlineBoxShadows::from_vec(vec![
                 BoxShadow {
-                    color: Some(Color {
+                    color: ColorValue::Rgba(Color {
                         red: 255,
                         green: 0,
                         blue: 0,
@@ -506,7 +559,7 @@ fn test_from_css_box_shadow_4() {
         &InlineDeclarations::from_vec(vec![
             Theme(BoxShadow(InlineBoxShadows::from_vec(vec![
                 BoxShadow {
-                    color: Some(Color {
+                    color: ColorValue::Rgba(Color {
                         red: 255,
                         green: 0,
                         blue: 0,
@@ -538,7 +591,7 @@ fn test_from_css_box_shadow_5() {
         &InlineDeclarations::from_vec(vec![
             Theme(BoxShadow(InlineBoxShadows::from_vec(vec![
                 BoxShadow {
-                    color: Some(Color {
+                    color: ColorValue::Rgba(Color {
                         red: 255,
                         green: 0,
                         blue: 0,
@@ -570,7 +623,7 @@ fn test_from_css_text_shadow_1() {
         &InlineDeclarations::from_vec(vec![
             Theme(TextShadow(InlineTextShadows::from_vec(vec![
                 TextShadow {
-                    color: None,
+                    color: ColorValue::CurrentColor,
                     horizontal: StyleUnit::Point(10.0.into()),
                     vertical: StyleUnit::Point(5.0.into()),
                     blur: None
@@ -595,7 +648,7 @@ fn test_from_css_text_shadow_2() {
         &InlineDeclarations::from_vec(vec![
             Theme(TextShadow(InlineTextShadows::from_vec(vec![
                 TextShadow {
-                    color: Some(Color {
+                    color: ColorValue::Rgba(Color {
                         red: 255,
                         green: 0,
                         blue: 0,
@@ -625,7 +678,7 @@ fn test_from_css_text_shadow_3() {
         &InlineDeclarations::from_vec(vec![
             Theme(TextShadow(InlineTextShadows::from_vec(vec![
                 TextShadow {
-                    color: Some(Color {
+                    color: ColorValue::Rgba(Color {
                         red: 255,
                         green: 0,
                         blue: 0,
@@ -865,6 +918,24 @@ fn test_from_css_font_size_2() {
     );
 }
 
+#[test]
+fn test_from_css_font_size_3() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".someClass { font-size: x-large; }";
+    let mut stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    assert_eq!(
+        stylesheet.take(".someClass").deref(),
+        &InlineDeclarations::from_vec(vec![
+            Theme(FontSize(FontSize::Keyword(FontSizeKeyword::XLarge))),
+        ])
+    );
+}
+
 #[test]
 fn test_from_css_font_stretch_1() {
     let url = Url::parse("about::test").unwrap();
@@ -897,6 +968,119 @@ fn test_from_css_font_stretch_2() {
     );
 }
 
+#[test]
+fn test_from_css_custom_properties_1() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".someClass { --brand: #f00; color: var(--brand); }";
+    let mut stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    assert_eq!(
+        stylesheet.take(".someClass").deref(),
+        &InlineDeclarations::from_vec(vec![
+            Custom(CustomProperty {
+                name: CustomPropertyName::from("--brand"),
+                value: UnparsedValue::from("#f00")
+            }),
+            WithVariables(UnparsedDeclaration {
+                property: PropertyName::from("color"),
+                value: UnparsedValue::from("var(--brand)")
+            }),
+        ])
+    );
+}
+
+#[test]
+fn test_from_css_custom_properties_2() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".someClass { margin: var(--spacing) 0; }";
+    let mut stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    assert_eq!(
+        stylesheet.take(".someClass").deref(),
+        &InlineDeclarations::from_vec(vec![
+            WithVariables(UnparsedDeclaration {
+                property: PropertyName::from("margin"),
+                value: UnparsedValue::from("var(--spacing) 0")
+            }),
+        ])
+    );
+}
+
+#[test]
+fn test_from_css_wide_keywords() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".someClass { color: inherit; border-top-style: initial; cursor: unset; }";
+    let mut stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    assert_eq!(
+        stylesheet.take(".someClass").deref(),
+        &InlineDeclarations::from_vec(vec![
+            WideKeyword(PropertyId::Color, CssWideKeyword::Inherit),
+            WideKeyword(PropertyId::BorderTopStyle, CssWideKeyword::Initial),
+            WideKeyword(PropertyId::Cursor, CssWideKeyword::Unset),
+        ])
+    );
+}
+
+#[test]
+fn test_from_css_opacity() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".a { opacity: 0.125; } .b { opacity: 1.5; } .c { opacity: -1; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    assert_eq!(stylesheet.get_ref(".a").unwrap().deref(), &InlineDeclarations::from_vec(vec![Theme(Opacity(0.125))]));
+    assert_eq!(stylesheet.get_ref(".b").unwrap().deref(), &InlineDeclarations::from_vec(vec![Theme(Opacity(1.0))]));
+    assert_eq!(stylesheet.get_ref(".c").unwrap().deref(), &InlineDeclarations::from_vec(vec![Theme(Opacity(0.0))]));
+}
+
+#[test]
+fn test_from_css_current_color() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".someClass { border-top-color: currentColor; color: #f00; text-shadow: 1px 2px currentColor; }";
+    let mut stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    assert_eq!(
+        stylesheet.take(".someClass").deref(),
+        &InlineDeclarations::from_vec(vec![
+            Theme(BorderTopColor(ColorValue::CurrentColor)),
+            Theme(Color(ColorValue::Rgba(Color {
+                red: 255,
+                green: 0,
+                blue: 0,
+                alpha: 255
+            }))),
+            Theme(TextShadow(InlineTextShadows::from_vec(vec![
+                TextShadow {
+                    color: ColorValue::CurrentColor,
+                    horizontal: StyleUnit::Point(1.0.into()),
+                    vertical: StyleUnit::Point(2.0.into()),
+                    blur: None
+                },
+            ]))),
+        ])
+    );
+}
+
 #[test]
 pub fn test_tokens_1() {
     let url = Url::parse("about::test").unwrap();
@@ -960,12 +1144,12 @@ pub fn test_tokens_2() {
             StyleRule {
                 selectors: StyleSelectors(SmallVec::from_buf([StyleSelector::from(".root"),])),
                 declarations: StyleDeclarations(SmallVec::from_buf([
-                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(Color {
+                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(ColorValue::Rgba(Color {
                         red: 255u8,
                         green: 0u8,
                         blue: 0u8,
                         alpha: 255u8
-                    })),
+                    }))),
                     StyleDeclaration::Layout(FlexStyle::Width(StyleUnit::Point(500f32.into()))),
                     StyleDeclaration::Layout(FlexStyle::Height(StyleUnit::Point(120f32.into()))),
                     StyleDeclaration::Layout(FlexStyle::FlexDirection(FlexDirection::Row)),
@@ -978,13 +1162,13 @@ pub fn test_tokens_2() {
             StyleRule {
                 selectors: StyleSelectors(SmallVec::from_buf([StyleSelector::from(".image"),])),
                 declarations: StyleDeclarations(SmallVec::from_vec(vec![
-                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(Color {
+                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(ColorValue::Rgba(Color {
                         red: 0u8,
                         green: 128u8,
                         blue: 0u8,
                         alpha: 255u8
-                    })),
-                    StyleDeclaration::Theme(ThemeStyle::Opacity(50u32)),
+                    }))),
+                    StyleDeclaration::Theme(ThemeStyle::Opacity(0.5f32)),
                     StyleDeclaration::Layout(FlexStyle::Width(StyleUnit::Point(80f32.into()))),
                     StyleDeclaration::Layout(FlexStyle::MarginRight(StyleUnit::Point(20f32.into()))),
                 ]))
@@ -992,18 +1176,18 @@ pub fn test_tokens_2() {
             StyleRule {
                 selectors: StyleSelectors(SmallVec::from_buf([StyleSelector::from(".text"),])),
                 declarations: StyleDeclarations(SmallVec::from_vec(vec![
-                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(Color {
+                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(ColorValue::Rgba(Color {
                         red: 0u8,
                         green: 0u8,
                         blue: 255u8,
                         alpha: 255u8
-                    })),
-                    StyleDeclaration::Theme(ThemeStyle::Color(Color {
+                    }))),
+                    StyleDeclaration::Theme(ThemeStyle::Color(ColorValue::Rgba(Color {
                         red: 255u8,
                         green: 255u8,
                         blue: 0u8,
                         alpha: 255u8
-                    })),
+                    }))),
                     StyleDeclaration::Layout(FlexStyle::Height(StyleUnit::Point(25f32.into()))),
                     StyleDeclaration::Layout(FlexStyle::AlignSelf(Align::Center)),
                     StyleDeclaration::Layout(FlexStyle::FlexGrow(1f32.into())),
@@ -1040,12 +1224,12 @@ pub fn test_tokens_3() {
                             FontName::Generic(GenericFontName::SansSerif),
                         ])
                     ))),
-                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(Color {
+                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(ColorValue::Rgba(Color {
                         red: 255u8,
                         green: 0u8,
                         blue: 0u8,
                         alpha: 255u8
-                    })),
+                    }))),
                     StyleDeclaration::Layout(FlexStyle::Width(StyleUnit::Point(500f32.into()))),
                     StyleDeclaration::Layout(FlexStyle::Height(StyleUnit::Point(120f32.into()))),
                     StyleDeclaration::Layout(FlexStyle::FlexDirection(FlexDirection::Row)),
@@ -1058,13 +1242,13 @@ pub fn test_tokens_3() {
             StyleRule {
                 selectors: StyleSelectors(SmallVec::from_buf([StyleSelector::from(".image"),])),
                 declarations: StyleDeclarations(SmallVec::from_vec(vec![
-                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(Color {
+                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(ColorValue::Rgba(Color {
                         red: 0u8,
                         green: 128u8,
                         blue: 0u8,
                         alpha: 255u8
-                    })),
-                    StyleDeclaration::Theme(ThemeStyle::Opacity(50u32)),
+                    }))),
+                    StyleDeclaration::Theme(ThemeStyle::Opacity(0.5f32)),
                     StyleDeclaration::Layout(FlexStyle::Width(StyleUnit::Point(80f32.into()))),
                     StyleDeclaration::Layout(FlexStyle::MarginRight(StyleUnit::Point(20f32.into()))),
                 ]))
@@ -1079,18 +1263,18 @@ pub fn test_tokens_3() {
                             FontName::Generic(GenericFontName::Serif),
                         ])
                     ))),
-                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(Color {
+                    StyleDeclaration::Theme(ThemeStyle::BackgroundColor(ColorValue::Rgba(Color {
                         red: 0u8,
                         green: 0u8,
                         blue: 255u8,
                         alpha: 255u8
-                    })),
-                    StyleDeclaration::Theme(ThemeStyle::Color(Color {
+                    }))),
+                    StyleDeclaration::Theme(ThemeStyle::Color(ColorValue::Rgba(Color {
                         red: 255u8,
                         green: 255u8,
                         blue: 0u8,
                         alpha: 255u8
-                    })),
+                    }))),
                     StyleDeclaration::Layout(FlexStyle::Height(StyleUnit::Point(25f32.into()))),
                     StyleDeclaration::Layout(FlexStyle::AlignSelf(Align::Center)),
                     StyleDeclaration::Layout(FlexStyle::FlexGrow(1f32.into())),
@@ -1189,10 +1373,12 @@ pub fn test_serialize_2() {
   {
     "Theme": {
       "BackgroundColor": {
-        "red": 255,
-        "green": 0,
-        "blue": 0,
-        "alpha": 255
+        "Rgba": {
+          "red": 255,
+          "green": 0,
+          "blue": 0,
+          "alpha": 255
+        }
       }
     }
   },
@@ -1284,10 +1470,12 @@ pub fn test_serialize_3() {
   {
     "Theme": {
       "BackgroundColor": {
-        "red": 255,
-        "green": 0,
-        "blue": 0,
-        "alpha": 255
+        "Rgba": {
+          "red": 255,
+          "green": 0,
+          "blue": 0,
+          "alpha": 255
+        }
       }
     }
   },
@@ -1348,7 +1536,7 @@ pub fn test_serialize_3() {
 
 #[test]
 fn test_computed_styles_1() {
-    let computed = ComputedStyles::default();
+    let (computed, layout_styles) = ComputedStyles::compute(None, &[], UnknownElement);
 
     assert_eq!(computed.cursor(), Cursor::Default);
     assert_eq!(computed.color().red, 0);
@@ -1359,9 +1547,14 @@ fn test_computed_styles_1() {
     assert_eq!(computed.font_names_copy(), vec![]);
     assert_eq!(computed.font_caps(), FontCaps::Normal);
     assert_eq!(computed.font_weight(), FontWeight::Normal);
-    assert_eq!(computed.font_size(), FontSize::System);
+    assert_eq!(computed.font_size(), FontSize::Length(StyleUnit::Point(16.0.into())));
+    assert!(TryInto::<SharedUnit>::try_into(computed.font_size()).is_ok());
     assert_eq!(computed.font_stretch(), FontStretch::Normal);
     assert_eq!(computed.visibility(), Visibility::Visible);
+    assert_eq!(computed.opacity(), 1.0);
+    assert_eq!(computed.effective_opacity(), 1.0);
+    assert_eq!(layout_styles, vec![]);
+    assert_eq!(ComputedStyles::default(), ComputedStyles::initial_values());
 }
 
 #[test]
@@ -1493,3 +1686,1394 @@ fn test_computed_styles_4() {
     assert_eq!(computed.font_stretch(), FontStretch::Normal);
     assert_eq!(computed.visibility(), Visibility::Visible);
 }
+
+#[test]
+fn test_computed_styles_custom_properties_1() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".parent { color: var(--brand); --brand: #f00; } .child { --brand: #00f; --accent: var(--brand); border-top-color: var(--accent); }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let mut parent = ComputedStyles::default();
+    parent.apply_styles(stylesheet.get_ref(".parent").unwrap());
+    assert_eq!(parent.color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(parent.custom_property("--brand"), Some(&UnparsedValue::from("#f00")));
+
+    let mut child = ComputedStyles::default();
+    child.inherit_styles(&parent);
+    assert_eq!(child.color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.custom_property("--brand"), Some(&UnparsedValue::from("#f00")));
+
+    child.apply_styles(stylesheet.get_ref(".child").unwrap());
+    assert_eq!(child.color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.border_top_color(), Color::new([0, 0, 255, 255]));
+    assert_eq!(child.custom_property("--accent"), Some(&UnparsedValue::from("#00f")));
+}
+
+#[test]
+fn test_computed_styles_custom_properties_2() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".someClass { --a: var(--b); --b: var(--a); color: var(--missing, #0f0); background-color: var(--a); }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let mut computed = ComputedStyles::default();
+    computed.apply_styles(stylesheet.get_ref(".someClass").unwrap());
+    assert_eq!(computed.color(), Color::new([0, 255, 0, 255]));
+    assert_eq!(computed.background_color(), ComputedStyles::default().background_color());
+    assert_eq!(computed.custom_property("--a"), None);
+    assert_eq!(computed.custom_property("--b"), None);
+
+    // Unresolvable references make the property behave as `unset`, rather
+    // than letting an earlier declaration win.
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let css = ".parent { color: #00f; } .red { color: #f00; background-color: #f00; width: 10px; } \
+               .missing { color: var(--missing); background-color: var(--missing); width: var(--missing); }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+    let parent = Arc::new(ComputedStyles::compute(None, &[stylesheet.get_ref(".parent").unwrap()], UnknownElement).0);
+    let declarations = [stylesheet.get_ref(".red").unwrap(), stylesheet.get_ref(".missing").unwrap()];
+    let (computed, layout_styles) = ComputedStyles::compute(Some(&parent), &declarations, UnknownElement);
+    assert_eq!(computed.color(), Color::new([0, 0, 255, 255]));
+    assert_eq!(computed.background_color(), ComputedStyles::default().background_color());
+    assert_eq!(layout_styles, vec![Width(StyleUnit::Point(10.0.into())), Width(StyleUnit::Auto)]);
+
+    // References in comments, or escaped, aren't substituted.
+    let mut computed = ComputedStyles::default();
+    computed.apply_styles(&StyleDeclarations(SmallVec::from_vec(vec![
+        Custom(CustomProperty { name: CustomPropertyName::from("--a"), value: UnparsedValue::from("1px") }),
+        Custom(CustomProperty { name: CustomPropertyName::from("--b"), value: UnparsedValue::from("/* var(--a) */ 2px") }),
+        Custom(CustomProperty { name: CustomPropertyName::from("--c"), value: UnparsedValue::from("var\\(--a) calc(var(--a) * 2)") })
+    ])));
+    assert_eq!(computed.custom_property("--b"), Some(&UnparsedValue::from("/* var(--a) */ 2px")));
+    assert_eq!(computed.custom_property("--c"), Some(&UnparsedValue::from("var\\(--a) calc(1px * 2)")));
+}
+
+#[test]
+fn test_computed_styles_wide_keywords() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".parent { color: #f00; background-color: #00f; cursor: pointer; border-top-color: #0f0; } \
+               .child { color: #0f0; color: inherit; background-color: inherit; cursor: initial; border-top-color: unset; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let parent = Arc::new(ComputedStyles::compute(None, &[stylesheet.get_ref(".parent").unwrap()], UnknownElement).0);
+    let (child, _) = ComputedStyles::compute(Some(&parent), &[stylesheet.get_ref(".child").unwrap()], UnknownElement);
+
+    assert_eq!(child.color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.background_color(), Color::new([0, 0, 255, 255]));
+    assert_eq!(child.cursor(), Cursor::default());
+    assert_eq!(child.border_top_color(), Color::transparent());
+
+    // Inheriting from a reference shares the inherited styles of the parent,
+    // whose non-inherited properties aren't known.
+    let mut child = ComputedStyles::default();
+    child.inherit_styles(&parent);
+    assert!(Arc::ptr_eq(&child.inherited, &parent.inherited));
+
+    child.apply_styles(stylesheet.get_ref(".child").unwrap());
+    assert_eq!(child.color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.background_color(), Color::transparent());
+    assert_eq!(child.cursor(), Cursor::default());
+}
+
+#[test]
+fn test_computed_styles_current_color() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".parent { color: #00f; text-shadow: 1px 2px currentColor; } \
+               .child { border-top-color: currentColor; color: #f00; background-color: currentColor; } \
+               .other { color: currentColor; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let mut parent = ComputedStyles::default();
+    parent.apply_styles(stylesheet.get_ref(".parent").unwrap());
+    assert_eq!(parent.text_shadows_copy()[0].color, ColorValue::Rgba(Color::new([0, 0, 255, 255])));
+
+    let mut child = ComputedStyles::default();
+    child.inherit_styles(&parent);
+    child.apply_styles(stylesheet.get_ref(".child").unwrap());
+
+    assert_eq!(child.color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.border_top_color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.background_color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.text_shadows_copy()[0].color, ColorValue::Rgba(Color::new([255, 0, 0, 255])));
+
+    let mut other = ComputedStyles::default();
+    other.inherit_styles(&parent);
+    other.apply_styles(stylesheet.get_ref(".other").unwrap());
+
+    assert_eq!(other.color(), Color::new([0, 0, 255, 255]));
+}
+
+#[test]
+fn test_color_parse() {
+    assert_eq!("#f00".parse::<Color>(), Ok(Color::new([255, 0, 0, 255])));
+    assert_eq!("#f008".parse::<Color>(), Ok(Color::new([255, 0, 0, 136])));
+    assert_eq!("#00ff0080".parse::<Color>(), Ok(Color::new([0, 255, 0, 128])));
+    assert_eq!("rgb(0, 128, 255)".parse::<Color>(), Ok(Color::new([0, 128, 255, 255])));
+    assert_eq!("rgba(0, 0, 0, 0.5)".parse::<Color>(), Ok(Color::new([0, 0, 0, 128])));
+    assert_eq!("rgb(100% 0% 0% / 50%)".parse::<Color>(), Ok(Color::new([255, 0, 0, 128])));
+    assert_eq!("hsl(120, 100%, 50%)".parse::<Color>(), Ok(Color::new([0, 255, 0, 255])));
+    assert_eq!("hsla(240deg, 100%, 50%, 0.5)".parse::<Color>(), Ok(Color::new([0, 0, 255, 128])));
+    assert_eq!("RebeccaPurple".parse::<Color>(), Ok(Color::new([102, 51, 153, 255])));
+
+    assert_eq!("#ff".parse::<Color>(), Err(()));
+    assert_eq!("#ggg".parse::<Color>(), Err(()));
+    assert_eq!("rgb(1, 2)".parse::<Color>(), Err(()));
+    assert_eq!("hsl(0, 50, 50%)".parse::<Color>(), Err(()));
+}
+
+#[test]
+fn test_color_conversions() {
+    let red = Color::new([255, 0, 0, 255]);
+    let blue = Color::new([0, 0, 255, 255]);
+
+    assert_eq!(
+        red.to_hsla(),
+        Hsla {
+            hue: 0.0,
+            saturation: 1.0,
+            lightness: 0.5,
+            alpha: 1.0
+        }
+    );
+    assert_eq!(
+        blue.to_hsva(),
+        Hsva {
+            hue: 240.0,
+            saturation: 1.0,
+            value: 1.0,
+            alpha: 1.0
+        }
+    );
+    assert_eq!(Color::from_hsla(red.to_hsla()), red);
+    assert_eq!(Color::from_hsva(blue.to_hsva()), blue);
+}
+
+#[test]
+fn test_color_manipulation() {
+    let red = Color::new([255, 0, 0, 255]);
+
+    assert_eq!(Color::black().lighten(0.5), Color::new([128, 128, 128, 255]));
+    assert_eq!(Color::white().darken(1.0), Color::black());
+    assert_eq!(red.lighten(0.25), Color::new([255, 128, 128, 255]));
+    assert_eq!(Color::black().mix(Color::white(), 0.5), Color::new([128, 128, 128, 255]));
+    assert_eq!(red.with_alpha(0.5), Color::new([255, 0, 0, 128]));
+
+    assert_eq!(red.with_alpha(0.5).over(Color::white()), Color::new([255, 127, 127, 255]));
+    assert_eq!(Color::transparent().over(Color::transparent()), Color::transparent());
+    assert_eq!(Color::new([255, 128, 0, 128]).premultiplied(), [128, 64, 0, 128]);
+
+    assert!((Color::black().contrast_ratio(Color::white()) - 21.0).abs() < 0.01);
+    assert!((red.contrast_ratio(Color::white()) - 4.0).abs() < 0.01);
+    assert!((red.contrast_ratio(red) - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn test_color_serialize() {
+    assert_eq!(Color::new([255, 0, 0, 255]).to_hex_string(), "#ff0000");
+    assert_eq!(Color::new([0, 255, 0, 128]).to_hex_string(), "#00ff0080");
+    assert_eq!(Color::new([255, 0, 0, 255]).to_rgba_string(), "rgb(255, 0, 0)");
+    assert_eq!(Color::new([0, 0, 0, 128]).to_rgba_string(), "rgba(0, 0, 0, 0.502)");
+    assert_eq!(Color::new([0, 0, 0, 0]).to_string(), "rgba(0, 0, 0, 0)");
+    assert_eq!(Color::new([0, 0, 0, 128]).to_string().parse::<Color>(), Ok(Color::new([0, 0, 0, 128])));
+}
+
+#[test]
+fn test_color_keywords() {
+    assert_eq!(value!(color: red), ColorValue::Rgba(Color::new([255, 0, 0, 255])));
+    assert_eq!(value!(color: transparent), ColorValue::Rgba(Color::new([0, 0, 0, 0])));
+    assert_eq!(value!(color: ButtonText), ColorValue::System(SystemColor::ButtonText));
+    assert_eq!(ColorValue::from_keyword("buttontext"), Some(ColorValue::System(SystemColor::ButtonText)));
+    assert_eq!(ColorValue::from_keyword("RED"), Some(value!(color: red)));
+    assert_eq!(ColorValue::from_keyword("redd"), None);
+
+    let my_color = Color::new([1, 2, 3, 255]);
+    assert_eq!(value!(color: my_color), ColorValue::Rgba(my_color));
+
+    let styles = style! {
+        background-color: { my_color };
+    };
+
+    assert_eq!(styles.deref(), &InlineDeclarations::from_vec(vec![Theme(BackgroundColor(ColorValue::Rgba(my_color)))]));
+}
+
+#[test]
+fn test_computed_styles_opacity() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".parent { opacity: 0.5; } .child { opacity: 0.25; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let root = ComputedStyles::initial_values();
+    assert_eq!(root.opacity(), 1.0);
+    assert_eq!(root.effective_opacity(), 1.0);
+
+    let mut parent = ComputedStyles::initial_values();
+    parent.inherit_styles(&root);
+    parent.apply_styles(stylesheet.get_ref(".parent").unwrap());
+    assert_eq!(parent.opacity(), 0.5);
+    assert_eq!(parent.effective_opacity(), 0.5);
+
+    let mut child = ComputedStyles::initial_values();
+    child.inherit_styles(&parent);
+    child.apply_styles(stylesheet.get_ref(".child").unwrap());
+    assert_eq!(child.opacity(), 0.25);
+    assert_eq!(child.effective_opacity(), 0.125);
+
+    let mut grandchild = ComputedStyles::initial_values();
+    grandchild.inherit_styles(&child);
+    assert_eq!(grandchild.opacity(), 1.0);
+    assert_eq!(grandchild.effective_opacity(), 0.125);
+}
+
+#[test]
+fn test_computed_styles_relative_fonts() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".parent { font-size: 20px; font-weight: bold; } \
+               .child { font-size: 150%; font-weight: bolder; } \
+               .smaller { font-size: smaller; font-weight: lighter; } \
+               .larger { font-size: larger; font-weight: lighter; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let root = ComputedStyles::initial_values();
+
+    let mut parent = ComputedStyles::initial_values();
+    parent.inherit_styles(&root);
+    parent.apply_styles(stylesheet.get_ref(".parent").unwrap());
+    assert_eq!(parent.font_size(), FontSize::Length(StyleUnit::Point(20.0.into())));
+    assert_eq!(parent.font_weight(), FontWeight::Bold);
+
+    let mut child = ComputedStyles::initial_values();
+    child.inherit_styles(&parent);
+    child.apply_styles(stylesheet.get_ref(".child").unwrap());
+    assert_eq!(child.font_size(), FontSize::Length(StyleUnit::Point(30.0.into())));
+    assert_eq!(child.font_weight(), FontWeight::Weight(900));
+
+    // The medium size is one of the absolute-size keywords, so `smaller`
+    // steps down to `small`.
+    let mut smaller = ComputedStyles::initial_values();
+    smaller.inherit_styles(&root);
+    smaller.apply_styles(stylesheet.get_ref(".smaller").unwrap());
+    assert!((smaller.font_size().to_points() - 16.0 * 8.0 / 9.0).abs() < 0.01);
+    assert_eq!(smaller.font_weight(), FontWeight::Weight(100));
+
+    // 20px isn't one of them, so `larger` scales it instead.
+    let mut larger = ComputedStyles::initial_values();
+    larger.inherit_styles(&parent);
+    larger.apply_styles(stylesheet.get_ref(".larger").unwrap());
+    assert!((larger.font_size().to_points() - 24.0).abs() < 0.01);
+    assert_eq!(larger.font_weight(), FontWeight::Weight(400));
+}
+
+#[test]
+fn test_computed_styles_font_size_keywords() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".large { font-size: large; } .small { font-size: x-small; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let mut large = ComputedStyles::initial_values();
+    large.apply_styles(stylesheet.get_ref(".large").unwrap());
+    assert!((large.font_size().to_points() - 19.2).abs() < 0.01);
+
+    let root = Arc::new(ComputedStyles::compute_root(FontSizeTable::from_medium(20.0), &[], UnknownElement).0);
+    assert_eq!(root.font_size(), FontSize::Length(StyleUnit::Point(20.0.into())));
+
+    let (small, _) = ComputedStyles::compute(Some(&root), &[stylesheet.get_ref(".small").unwrap()], UnknownElement);
+    assert!((small.font_size().to_points() - 15.0).abs() < 0.01);
+
+    let system_styles = StyleDeclarations(InlineDeclarations::from_vec(vec![Theme(FontSize(FontSize::System))]));
+    let (system, _) = ComputedStyles::compute(Some(&root), &[&system_styles], UnknownElement);
+    assert_eq!(system.font_size(), FontSize::Length(StyleUnit::Point(20.0.into())));
+
+    let initial_styles = StyleDeclarations(InlineDeclarations::from_vec(vec![WideKeyword(PropertyId::FontSize, CssWideKeyword::Initial)]));
+    let (initial, _) = ComputedStyles::compute(Some(&Arc::new(small)), &[&initial_styles], UnknownElement);
+    assert_eq!(initial.font_size(), FontSize::Length(StyleUnit::Point(20.0.into())));
+}
+
+struct UnknownElement;
+
+impl TryInto<KnownElementName> for UnknownElement {
+    type Error = ();
+
+    fn try_into(self) -> Result<KnownElementName, Self::Error> {
+        Err(())
+    }
+}
+
+#[test]
+fn test_computed_styles_compute() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".parent { color: #00f; --size: 50px; } \
+               .child { background-color: currentColor; width: var(--size); color: #f00; flex-grow: 2; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let parent = Arc::new(ComputedStyles::compute(None, &[stylesheet.get_ref(".parent").unwrap()], UnknownElement).0);
+    assert_eq!(parent.color(), Color::new([0, 0, 255, 255]));
+
+    let user_agent = StyleDeclarations::make_user_agent_styles(UnknownElement);
+    let author = stylesheet.get_ref(".child").unwrap();
+    let (child, layout_styles) = ComputedStyles::compute(Some(&parent), &[&user_agent, author], UnknownElement);
+
+    assert_eq!(child.color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.background_color(), Color::new([255, 0, 0, 255]));
+    assert_eq!(child.custom_property("--size"), Some(&UnparsedValue::from("50px")));
+    assert_eq!(
+        layout_styles,
+        vec![
+            FlexDirection(FlexDirection::Row),
+            FlexWrap(Wrap::NoWrap),
+            JustifyContent(Justify::FlexStart),
+            AlignItems(Align::Stretch),
+            AlignContent(Align::Stretch),
+            FlexGrow(0.0.into()),
+            FlexShrink(1.0.into()),
+            FlexBasis(StyleUnit::Auto),
+            Width(StyleUnit::Point(50.0.into())),
+            FlexGrow(2.0.into()),
+        ]
+    );
+}
+
+#[test]
+fn test_computed_styles_layout() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".a { width: 100px; margin: 4px 8px; padding-left: 10%; position: absolute; top: 5px; \
+               overflow: hidden; flex-grow: 2; flex-direction: column; } \
+               .b { width: initial; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let user_agent = StyleDeclarations::make_user_agent_styles(UnknownElement);
+    let a = stylesheet.get_ref(".a").unwrap();
+    let (computed, _) = ComputedStyles::compute(None, &[&user_agent, a], UnknownElement);
+
+    assert_eq!(
+        computed.layout,
+        ComputedLayoutStyles {
+            position: PositionType::Absolute,
+            overflow: Overflow::Hidden,
+            width: StyleUnit::Point(100.0.into()),
+            top: StyleUnit::Point(5.0.into()),
+            margin_top: StyleUnit::Point(4.0.into()),
+            margin_right: StyleUnit::Point(8.0.into()),
+            margin_bottom: StyleUnit::Point(4.0.into()),
+            margin_left: StyleUnit::Point(8.0.into()),
+            padding_left: StyleUnit::Percent(10.0.into()),
+            flex_direction: FlexDirection::Column,
+            flex_wrap: Wrap::NoWrap,
+            flex_grow: 2.0,
+            flex_shrink: 1.0,
+            flex_basis: StyleUnit::Auto,
+            justify_content: Justify::FlexStart,
+            align_items: Align::Stretch,
+            align_content: Align::Stretch,
+            ..ComputedLayoutStyles::default()
+        }
+    );
+
+    let b = stylesheet.get_ref(".b").unwrap();
+    let (computed, _) = ComputedStyles::compute(None, &[&user_agent, a, b], UnknownElement);
+    assert_eq!(computed.layout.width, StyleUnit::Auto);
+    assert_eq!(computed.layout.margin_top, StyleUnit::Point(4.0.into()));
+}
+
+#[test]
+fn test_computed_styles_layout_wide_keywords() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".parent { width: 100px; border-top-width: 3px; } \
+               .child { width: inherit; border-top-width: inherit; flex-grow: initial; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let parent = Arc::new(ComputedStyles::compute(None, &[stylesheet.get_ref(".parent").unwrap()], UnknownElement).0);
+    let (child, layout_styles) = ComputedStyles::compute(Some(&parent), &[stylesheet.get_ref(".child").unwrap()], UnknownElement);
+
+    assert_eq!(child.layout.width, StyleUnit::Point(100.0.into()));
+    assert_eq!(child.layout.flex_grow, 0.0);
+    assert_eq!(
+        layout_styles,
+        vec![Width(StyleUnit::Point(100.0.into())), BorderTop(3.0.into()), FlexGrow(0.0.into())]
+    );
+}
+
+#[test]
+fn test_computed_styles_diff() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".base { color: #f00; width: 10px; } \
+               .cursor { cursor: pointer; } \
+               .color { color: #00f; background-color: #0f0; } \
+               .opacity { opacity: 0.5; color: #00f; } \
+               .width { width: 20px; font-size: 20px; color: #00f; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let compute = |selector: &str| {
+        let base = stylesheet.get_ref(".base").unwrap();
+        let styles = stylesheet.get_ref(selector).unwrap();
+        ComputedStyles::compute(None, &[base, styles], UnknownElement).0
+    };
+
+    let base = ComputedStyles::compute(None, &[stylesheet.get_ref(".base").unwrap()], UnknownElement).0;
+    assert_eq!(base.diff(&base.clone()), StyleChange::default());
+    assert!(base.diff(&base.clone()).is_empty());
+
+    let change = base.diff(&compute(".cursor"));
+    assert_eq!(change.damage, StyleDamage::None);
+    assert_eq!(change.properties, vec![PropertyId::Cursor]);
+
+    let change = base.diff(&compute(".color"));
+    assert_eq!(change.damage, StyleDamage::Repaint);
+    assert_eq!(change.properties, vec![PropertyId::Color, PropertyId::BackgroundColor]);
+
+    let change = base.diff(&compute(".opacity"));
+    assert_eq!(change.damage, StyleDamage::Restack);
+    assert!(change.contains(PropertyId::Opacity));
+
+    let change = base.diff(&compute(".width"));
+    assert_eq!(change.damage, StyleDamage::Relayout);
+    assert_eq!(change.properties, vec![PropertyId::Color, PropertyId::FontSize, PropertyId::Width]);
+}
+
+#[test]
+fn test_computed_styles_sharing() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".root { color: #f00; font-size: 20px; } \
+               .child { width: 10px; } \
+               .blue { color: #00f; } \
+               .same { width: 10px; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+    let root = stylesheet.get_ref(".root").unwrap();
+    let child = stylesheet.get_ref(".child").unwrap();
+    let blue = stylesheet.get_ref(".blue").unwrap();
+
+    // Children only setting non-inherited styles share the inherited ones.
+    let parent = Arc::new(ComputedStyles::compute(None, &[root], UnknownElement).0);
+    let (styles, _) = ComputedStyles::compute(Some(&parent), &[child], UnknownElement);
+    assert!(Arc::ptr_eq(&parent.inherited, &styles.inherited));
+    assert!(Arc::ptr_eq(styles.parent.styles().unwrap(), &parent));
+
+    let (styles, _) = ComputedStyles::compute(Some(&parent), &[blue], UnknownElement);
+    assert!(!Arc::ptr_eq(&parent.inherited, &styles.inherited));
+    assert_eq!(styles.inherited.color, Color::new([0, 0, 255, 255]));
+    assert_eq!(parent.inherited.color, Color::new([255, 0, 0, 255]));
+    assert_eq!(styles.font_size(), parent.font_size());
+
+    // Siblings matching the same rules share their styles.
+    let root = stylesheet.get_rule(".root").unwrap();
+    let child = stylesheet.get_rule(".child").unwrap();
+    let blue = stylesheet.get_rule(".blue").unwrap();
+    assert_eq!(child.0, stylesheet.rule_id(stylesheet.index_of(".child").unwrap()));
+
+    let mut cache = StyleSharingCache::default();
+    let (parent, _) = cache.compute(None, &[root], UnknownElement);
+    let (first, first_layout) = cache.compute(Some(&parent), &[child], UnknownElement);
+    let (second, second_layout) = cache.compute(Some(&parent), &[child], UnknownElement);
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first_layout, vec![Width(StyleUnit::Point(10.0.into()))]);
+    assert_eq!(first_layout, second_layout);
+    assert_eq!(cache.len(), 2);
+
+    let (third, _) = cache.compute(Some(&parent), &[child, blue], UnknownElement);
+    assert!(!Arc::ptr_eq(&first, &third));
+    assert_eq!(cache.len(), 3);
+
+    // Rules are compared by identity, not by their declarations.
+    let (same, _) = cache.compute(Some(&parent), &[stylesheet.get_rule(".same").unwrap()], UnknownElement);
+    assert!(!Arc::ptr_eq(&first, &same));
+    assert_eq!(first, same);
+
+    // Different parent allocations don't share, even with equal values.
+    let other_parent = Arc::new((*parent).clone());
+    let (fourth, _) = cache.compute(Some(&other_parent), &[child], UnknownElement);
+    assert!(!Arc::ptr_eq(&first, &fourth));
+    assert_eq!(first, fourth);
+
+    let mut cache = StyleSharingCache::with_capacity(1);
+    let (first, _) = cache.compute(Some(&parent), &[child], UnknownElement);
+    cache.compute(Some(&parent), &[blue], UnknownElement);
+    let (second, _) = cache.compute(Some(&parent), &[child], UnknownElement);
+    assert!(!Arc::ptr_eq(&first, &second));
+    assert_eq!(cache.len(), 1);
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_style_macro_shorthands() {
+    let styles = style! {
+        border: { 1 px solid rgb(0, 0, 255) };
+        padding: { 4 px 8 px };
+        margin: { 1 px 2 px 3 px };
+        flex: { 2 };
+        font: { italic bold 12 px serif };
+    };
+
+    assert_eq!(styles.len(), 4 * 3 + 4 + 4 + 3 + 6);
+    assert_eq!(
+        styles[12..16].to_vec(),
+        vec![
+            Layout(PaddingTop(StyleUnit::Point(4.0.into()))),
+            Layout(PaddingRight(StyleUnit::Point(8.0.into()))),
+            Layout(PaddingBottom(StyleUnit::Point(4.0.into()))),
+            Layout(PaddingLeft(StyleUnit::Point(8.0.into())))
+        ]
+    );
+    assert_eq!(
+        styles[20..23].to_vec(),
+        vec![
+            Layout(FlexGrow(2.0.into())),
+            Layout(FlexShrink(1.0.into())),
+            Layout(FlexBasis(StyleUnit::Percent(0.0.into())))
+        ]
+    );
+
+    // Border values can be given in any order, and the width is medium when
+    // omitted. Sides can be undefined.
+    let styles = style! {
+        border: { 1 px red };
+        padding: { undefined 8 px };
+    };
+
+    assert_eq!(styles[0], Layout(BorderTop(1.0.into())));
+    assert_eq!(styles[4], Theme(BorderTopStyle(BorderStyle::None)));
+    assert_eq!(styles[8], Theme(BorderTopColor(value!(color: red))));
+    assert_eq!(styles[12], Layout(PaddingTop(StyleUnit::UndefinedValue)));
+    assert_eq!(styles[13], Layout(PaddingRight(StyleUnit::Point(8.0.into()))));
+
+    let styles = style! {
+        border: { solid };
+    };
+
+    assert_eq!(styles[0], Layout(BorderTop(3.0.into())));
+    assert_eq!(styles[4], Theme(BorderTopStyle(BorderStyle::Solid)));
+    assert_eq!(styles[8], Theme(BorderTopColor(ColorValue::CurrentColor)));
+
+    // Shorthands expand like they do when parsed from CSS.
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".foo { border: 1px solid #00f; padding: 4px 8px; margin: 1px 2px 3px; flex: 2; font: italic bold 12px serif; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+    let parsed = stylesheet.get_ref(".foo").unwrap();
+
+    let (from_macro, _) = ComputedStyles::compute(None, &[&styles], UnknownElement);
+    let (from_css, _) = ComputedStyles::compute(None, &[parsed], UnknownElement);
+    assert_eq!(from_macro, from_css);
+    assert_eq!(from_macro.layout.margin_left, StyleUnit::Point(2.0.into()));
+    assert_eq!(from_macro.inherited.font_weight, FontWeight::Bold);
+    assert_eq!(from_macro.border_top_style, BorderStyle::Solid);
+}
+
+#[test]
+fn test_style_macro_theme_styles() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".someClass { \
+               cursor: not-allowed; \
+               font-family: Times, \"Times New Roman\", serif; \
+               font-style: italic; \
+               font-variant: small-caps; \
+               font-weight: 300; \
+               font-stretch: semi-condensed; \
+               visibility: hidden; \
+               box-shadow: 10px 5px 5px 1px red, inset 1px 2px; \
+               text-shadow: 10px 5px rgb(0, 0, 255), 1px 1px 2px; \
+               }";
+    let mut stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let styles = style! {
+        cursor: { not-allowed };
+        font-family: { Times, "Times New Roman", serif };
+        font-style: { italic };
+        font-caps: { small-caps };
+        font-weight: { 300 };
+        font-stretch: { semi-condensed };
+        visibility: { hidden };
+        box-shadow: { 10 px 5 px 5 px 1 px red, inset 1 px 2 px };
+        text-shadow: { 10 px 5 px rgb(0, 0, 255), 1 px 1 px 2 px };
+    };
+
+    assert_eq!(stylesheet.take(".someClass"), styles);
+
+    let styles = style! {
+        box-shadow: { none };
+        text-shadow: { red 1 px 2 px };
+    };
+
+    assert_eq!(
+        styles.deref(),
+        &InlineDeclarations::from_vec(vec![
+            Theme(BoxShadow(InlineBoxShadows::new())),
+            Theme(TextShadow(InlineTextShadows::from_vec(vec![
+                TextShadow {
+                    color: ColorValue::Rgba(Color::new([255, 0, 0, 255])),
+                    horizontal: StyleUnit::Point(1.0.into()),
+                    vertical: StyleUnit::Point(2.0.into()),
+                    blur: None
+                },
+            ]))),
+        ])
+    );
+}
+
+#[test]
+fn test_checked_style_macros() {
+    let checked = checked_style! {
+        border: { 1 px solid rgb(0, 0, 255) };
+        flex: { 1 1 auto };
+        cursor: { not-allowed };
+        font-family: { Times, "Times New Roman", serif };
+        box-shadow: { 10 px 5 px 5 px 1 px red, inset 1 px 2 px };
+        opacity: { 0.5 };
+    };
+    let unchecked = style! {
+        border: { 1 px solid rgb(0, 0, 255) };
+        flex: { 1 1 auto };
+        cursor: { not-allowed };
+        font-family: { Times, "Times New Roman", serif };
+        box-shadow: { 10 px 5 px 5 px 1 px red, inset 1 px 2 px };
+        opacity: { 0.5 };
+    };
+    assert_eq!(checked, unchecked);
+
+    assert_eq!(checked_declaration!(flex-direction: row), Layout(FlexDirection(FlexDirection::Row)));
+    assert_eq!(checked_declaration!(margin: 1 px), Layout(Margin(StyleUnit::Point(1.0.into()))));
+}
+
+#[test]
+fn test_css_macros() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::Author;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let stylesheet = css!(".foo, #bar > baz { border: 1px solid #00f; font-family: \"Fira Sans\", serif; --x: 1px; }");
+    let css = ".foo, #bar > baz { border: 1px solid #00f; font-family: \"Fira Sans\", serif; --x: 1px; }";
+    let expected: Stylesheet = parse(css, url, origin, qm, media).into();
+    assert_eq!(stylesheet, expected);
+
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+
+    let stylesheet = include_css!("tests/fixtures/test_2.css");
+    let css = include_str!("fixtures/test_2.css");
+    let expected: Stylesheet = parse(css, url, origin, qm, media).into();
+    assert_eq!(stylesheet, expected);
+}
+
+#[test]
+fn test_to_css() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".foo, .bar { width: 10px; color: #ff0; opacity: 0.5; } #baz { box-shadow: inset 1px 2px red; font-family: \"Fira Sans\", serif; }";
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    assert_eq!(
+        stylesheet.to_css_string(CssFormat::Pretty),
+        ".foo, .bar {\n    width: 10px;\n    color: #ffff00;\n    opacity: 0.5;\n}\n\n\
+         #baz {\n    box-shadow: inset 1px 2px #ff0000;\n    font-family: \"Fira Sans\", serif;\n}\n"
+    );
+    assert_eq!(
+        stylesheet.to_css_string(CssFormat::Minified),
+        ".foo,.bar{width:10px;color:#ffff00;opacity:0.5}#baz{box-shadow:inset 1px 2px #ff0000;font-family:\"Fira Sans\",serif}"
+    );
+
+    // Serialized stylesheets parse back into the same values.
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let css = include_str!("fixtures/test_3.css");
+    let stylesheet: Stylesheet = parse(css, url, origin, qm, media).into();
+
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let css = stylesheet.to_css_string(CssFormat::Minified);
+    let reparsed: Stylesheet = parse(&css, url, origin, qm, media).into();
+    assert_eq!(reparsed, stylesheet);
+
+    // Yoga styles without a CSS equivalent expand to the longhands they set.
+    let declaration = Layout(PaddingHorizontal(StyleUnit::Point(4.0.into())));
+    assert_eq!(declaration.to_css_string(CssFormat::Minified), "padding-left:4px;padding-right:4px");
+    assert_eq!(Theme(FontWeight(FontWeight::System)).to_css_string(CssFormat::Pretty), "");
+}
+
+#[test]
+fn test_to_css_layout_styles() {
+    let reparse = |style: &FlexStyle| {
+        let url = Url::parse("about::test").unwrap();
+        let media = MediaList::empty();
+        let css = format!(".a {{ {} }}", Layout(style.clone()).to_css_string(CssFormat::Minified));
+        let stylesheet: Stylesheet = parse(&css, url, Origin::UserAgent, QuirksMode::NoQuirks, media).into();
+        stylesheet.get(".a").unwrap()
+    };
+    let declarations = |styles: Vec<FlexStyle>| StyleDeclarations(InlineDeclarations::from_vec(styles.into_iter().map(Layout).collect()));
+
+    // Styles with a CSS property of their own parse back into themselves.
+    let same = vec![
+        AlignContent(Align::SpaceBetween),
+        AlignItems(Align::Center),
+        AlignSelf(Align::FlexEnd),
+        BorderBottom(1.0.into()),
+        BorderLeft(2.0.into()),
+        BorderRight(3.0.into()),
+        BorderTop(4.0.into()),
+        Bottom(StyleUnit::Point(5.0.into())),
+        Display(yoga::Display::None),
+        FlexBasis(StyleUnit::Point(6.0.into())),
+        FlexBasis(StyleUnit::Auto),
+        FlexDirection(FlexDirection::ColumnReverse),
+        FlexGrow(2.0.into()),
+        FlexShrink(0.5.into()),
+        FlexWrap(Wrap::WrapReverse),
+        Height(StyleUnit::Percent(50.0.into())),
+        JustifyContent(Justify::SpaceAround),
+        Left(StyleUnit::Percent(10.0.into())),
+        MarginBottom(StyleUnit::Point(7.0.into())),
+        MarginLeft(StyleUnit::Auto),
+        MarginRight(StyleUnit::Percent(5.0.into())),
+        MarginTop(StyleUnit::Point(8.0.into())),
+        MaxHeight(StyleUnit::Point(100.0.into())),
+        MaxHeight(StyleUnit::UndefinedValue),
+        MaxWidth(StyleUnit::Percent(75.0.into())),
+        MaxWidth(StyleUnit::UndefinedValue),
+        MinHeight(StyleUnit::Point(9.0.into())),
+        MinWidth(StyleUnit::Percent(20.0.into())),
+        PaddingBottom(StyleUnit::Point(10.0.into())),
+        PaddingLeft(StyleUnit::Percent(25.0.into())),
+        PaddingRight(StyleUnit::Point(11.0.into())),
+        PaddingTop(StyleUnit::Point(12.0.into())),
+        Position(PositionType::Absolute),
+        Right(StyleUnit::Auto),
+        Top(StyleUnit::Point(13.0.into())),
+        Width(StyleUnit::Point(14.0.into()))
+    ];
+    for style in &same {
+        assert_eq!(reparse(style), declarations(vec![style.clone()]), "{:?}", style);
+    }
+
+    // Other styles parse back into the longhands they set.
+    let unit = StyleUnit::Point(4.0.into());
+    let expanded = vec![
+        (Border(2.0.into()), vec![BorderTop(2.0.into()), BorderRight(2.0.into()), BorderBottom(2.0.into()), BorderLeft(2.0.into())]),
+        (BorderEnd(3.0.into()), vec![BorderRight(3.0.into())]),
+        (BorderStart(3.0.into()), vec![BorderLeft(3.0.into())]),
+        (End(unit), vec![Right(unit)]),
+        (Start(unit), vec![Left(unit)]),
+        (Flex(2.0.into()), vec![FlexGrow(2.0.into()), FlexShrink(0.0.into())]),
+        (Flex((-1.0).into()), vec![FlexGrow(0.0.into()), FlexShrink(1.0.into())]),
+        (Margin(unit), vec![MarginTop(unit), MarginRight(unit), MarginBottom(unit), MarginLeft(unit)]),
+        (MarginEnd(unit), vec![MarginRight(unit)]),
+        (MarginHorizontal(unit), vec![MarginLeft(unit), MarginRight(unit)]),
+        (MarginStart(unit), vec![MarginLeft(unit)]),
+        (MarginVertical(unit), vec![MarginTop(unit), MarginBottom(unit)]),
+        (Overflow(Overflow::Hidden), vec![Overflow(Overflow::Hidden), Overflow(Overflow::Hidden)]),
+        (Padding(unit), vec![PaddingTop(unit), PaddingRight(unit), PaddingBottom(unit), PaddingLeft(unit)]),
+        (PaddingEnd(unit), vec![PaddingRight(unit)]),
+        (PaddingHorizontal(unit), vec![PaddingLeft(unit), PaddingRight(unit)]),
+        (PaddingStart(unit), vec![PaddingLeft(unit)]),
+        (PaddingVertical(unit), vec![PaddingTop(unit), PaddingBottom(unit)]),
+        // Not a property Servo knows about.
+        (AspectRatio(1.5.into()), vec![])
+    ];
+    for (style, expected) in expanded {
+        assert_eq!(reparse(&style), declarations(expected), "{:?}", style);
+    }
+
+    // Undefined sizes are laid out as `auto` ones, and other undefined lengths
+    // are the initial values, which leave their properties out.
+    let undefined = StyleUnit::UndefinedValue;
+    assert_eq!(Layout(Width(undefined)).to_css_string(CssFormat::Minified), "width:auto");
+    assert_eq!(Layout(MaxWidth(undefined)).to_css_string(CssFormat::Minified), "max-width:none");
+    assert_eq!(reparse(&Height(undefined)), declarations(vec![Height(StyleUnit::Auto)]));
+    assert_eq!(reparse(&FlexBasis(undefined)), declarations(vec![FlexBasis(StyleUnit::Auto)]));
+    for style in &[Top(undefined), MarginHorizontal(undefined), Padding(undefined), MinHeight(undefined)] {
+        assert_eq!(Layout(style.clone()).to_css_string(CssFormat::Minified), "", "{:?}", style);
+        assert_eq!(reparse(style), declarations(vec![]), "{:?}", style);
+    }
+}
+
+#[test]
+fn test_conversion_warnings() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".foo, .bar { z-index: 1; width: 2em; height: 10px; } .baz { color: red; }";
+    let (stylesheet, warnings) = Stylesheet::from_servo_with_warnings(parse(css, url, origin, qm, media));
+
+    let selectors = StyleSelectors(InlineSelectors::from_vec(vec![StyleSelector::from(".foo"), StyleSelector::from(".bar")]));
+    assert_eq!(
+        warnings,
+        vec![
+            ConversionWarning {
+                selectors: selectors.clone(),
+                location: SourceLocation { line: 1, column: 1 },
+                property: "z-index".to_owned(),
+                value: "1".to_owned(),
+                reason: WarningReason::UnsupportedProperty
+            },
+            ConversionWarning {
+                selectors,
+                location: SourceLocation { line: 1, column: 1 },
+                property: "width".to_owned(),
+                value: "2em".to_owned(),
+                reason: WarningReason::UnsupportedValue
+            },
+        ]
+    );
+    assert_eq!(warnings[1].to_string(), "1:1: .foo, .bar: unsupported value in `width: 2em`");
+
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let expected: Stylesheet = parse(css, url, origin, qm, media).into();
+    assert_eq!(stylesheet, expected);
+}
+
+#[test]
+fn test_source_map() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".foo { width: 10px; }\n\n  .bar, .baz {\n    color: red;\n  }\n.foo { height: 10px; }\n";
+    let (mut stylesheet, source_map) = Stylesheet::from_servo_with_source_map(parse(css, url, origin, qm, media));
+
+    // Rules with the same selectors each have their own location.
+    assert_eq!(source_map.len(), 3);
+    assert_eq!(source_map.rule_location(0), Some(SourceLocation { line: 1, column: 1 }));
+    assert_eq!(source_map.rule_location(1), Some(SourceLocation { line: 3, column: 3 }));
+    assert_eq!(source_map.rule_location(2), Some(SourceLocation { line: 6, column: 1 }));
+    assert_eq!(source_map.rule_location(3), None);
+
+    // Locations are still found once rules are taken out of the stylesheet.
+    stylesheet.take(".foo");
+    assert_eq!(source_map.rule_location(1), Some(SourceLocation { line: 3, column: 3 }));
+
+    // Warnings and locations can be had from a single conversion.
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let css = ".foo { z-index: 1; }";
+    let (stylesheet, warnings, source_map) = Stylesheet::from_servo_with_warnings_and_source_map(parse(css, url, origin, qm, media));
+    assert_eq!(stylesheet.len(), 1);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].location, source_map.rule_location(0).unwrap());
+}
+
+#[test]
+fn test_usage_report() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".foo { width: 10px; } .bar, .baz { color: red; } .qux { height: 5px; }";
+    let mut stylesheet = Stylesheet::from(parse(css, url, origin, qm, media));
+
+    // Lookups aren't counted until usage is tracked.
+    stylesheet.get_ref(".qux");
+    assert_eq!(stylesheet.usage_report().used, vec![]);
+
+    stylesheet.track_usage(true);
+    stylesheet.get_ref(".bar");
+    stylesheet.get_copy(".bar");
+    stylesheet.take(".foo");
+    stylesheet.get_ref(".missing");
+
+    let report = stylesheet.usage_report();
+    assert_eq!(
+        report,
+        UsageReport {
+            used: vec![
+                SelectorUsage {
+                    selector: StyleSelector::from(".bar"),
+                    hits: 2
+                },
+                SelectorUsage {
+                    selector: StyleSelector::from(".foo"),
+                    hits: 1
+                }
+            ],
+            unused: vec![StyleSelector::from(".baz"), StyleSelector::from(".qux")]
+        }
+    );
+
+    // Reports can be exported for tooling.
+    let stringified = serde_json::to_string(&report).unwrap();
+    assert_eq!(serde_json::from_str::<UsageReport>(&stringified).unwrap(), report);
+
+    stylesheet.reset_usage();
+    assert_eq!(stylesheet.usage_report().used, vec![]);
+    assert_eq!(stylesheet.usage_report().unused.len(), 3);
+
+    stylesheet.track_usage(false);
+    stylesheet.get_ref(".qux");
+    assert_eq!(stylesheet.usage_report().used, vec![]);
+}
+
+#[test]
+fn test_selector_index() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".foo { width: 1px; } div > .foo, #bar { width: 2px; } div { width: 3px; } .baz .foo:hover { width: 4px; } * { width: 5px; }";
+    let mut stylesheet = Stylesheet::from(parse(css, url, origin, qm, media));
+
+    assert_eq!(SelectorKey::of("ul li.foo.bar:hover"), SelectorKey::Class("foo"));
+    assert_eq!(SelectorKey::of(".foo #bar"), SelectorKey::Id("bar"));
+    assert_eq!(SelectorKey::of("div[data-x='a b']"), SelectorKey::Tag("div"));
+    assert_eq!(SelectorKey::of(":not(.foo)"), SelectorKey::Universal);
+
+    // Strings, escapes and functional pseudo-classes don't end the compound
+    // selector, nor start another simple selector.
+    assert_eq!(SelectorKey::of("[title=\"a]b .c\"].foo"), SelectorKey::Class("foo"));
+    assert_eq!(SelectorKey::of("a[href='#top']"), SelectorKey::Tag("a"));
+    assert_eq!(SelectorKey::of("div > .a\\.b"), SelectorKey::Class("a\\.b"));
+    assert_eq!(SelectorKey::of("#a\\#b.c"), SelectorKey::Id("a\\#b"));
+    assert_eq!(SelectorKey::of("li:not(#bar > .baz)"), SelectorKey::Tag("li"));
+    assert_eq!(SelectorKey::of("li:nth-child(2n + 1)"), SelectorKey::Tag("li"));
+
+    assert_eq!(stylesheet.index_of("#bar"), Some(1));
+    assert_eq!(stylesheet.rules_for(SelectorKey::Class("foo")).len(), 3);
+    assert_eq!(stylesheet.rules_for(SelectorKey::Tag("div")).len(), 1);
+    assert_eq!(stylesheet.rules_for(SelectorKey::Universal).len(), 1);
+    assert_eq!(stylesheet.rules_for(SelectorKey::Id("qux")).len(), 0);
+
+    // Taking a rule moves the following ones down, which the index follows.
+    stylesheet.take(".foo");
+    assert_eq!(stylesheet.index_of(".foo"), None);
+    assert_eq!(stylesheet.index_of("#bar"), Some(0));
+    assert_eq!(stylesheet.index_of("*"), Some(3));
+    assert_eq!(stylesheet.get_ref("*"), Some(&StyleDeclarations(SmallVec::from_vec(vec![Layout(Width(StyleUnit::Point(5.0.into())))]))));
+    assert_eq!(stylesheet.rules_for(SelectorKey::Class("foo")).len(), 2);
+
+    stylesheet.push(StyleRule {
+        selectors: StyleSelectors(SmallVec::from_vec(vec![StyleSelector::from(".qux")])),
+        declarations: StyleDeclarations(InlineDeclarations::default())
+    });
+    assert_eq!(stylesheet.index_of(".qux"), Some(4));
+
+    // Rules pushed after a removal are found again after another one.
+    stylesheet.take("div");
+    assert_eq!(stylesheet.index_of("#bar"), Some(0));
+    assert_eq!(stylesheet.index_of("*"), Some(2));
+    assert_eq!(stylesheet.index_of(".qux"), Some(3));
+    assert_eq!(stylesheet.rules_for(SelectorKey::Tag("div")).len(), 0);
+    assert_eq!(stylesheet.rules_for(SelectorKey::Class("qux")).len(), 1);
+    assert_eq!(stylesheet.cascade_order()[3].1.selectors[0].as_ref(), ".qux");
+
+    // The index is rebuilt when deserializing.
+    let stringified = serde_json::to_string(&stylesheet).unwrap();
+    let deserialized: Stylesheet = serde_json::from_str(&stringified).unwrap();
+    assert_eq!(deserialized.index_of("#bar"), Some(0));
+    assert_eq!(deserialized.rules_for(SelectorKey::Class("foo")).len(), 2);
+
+    // Neither are pseudo-elements within strings or escapes split off.
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let css = "a[title='::before'] { width: 1px; } .b\\:after { width: 2px; } a::before { width: 3px; }";
+    let stylesheet = Stylesheet::from(parse(css, url, origin, qm, media));
+    assert_eq!(stylesheet.rules_for(SelectorKey::Tag("a")).len(), 1);
+    assert_eq!(stylesheet.rules_for(SelectorKey::Class("b\\:after")).len(), 1);
+    assert_eq!(stylesheet.pseudo_rules_for(SelectorKey::Tag("a"), PseudoElement::Before).len(), 1);
+    assert_eq!(stylesheet.pseudo_rules_for(SelectorKey::Class("b"), PseudoElement::After).len(), 0);
+}
+
+#[test]
+fn test_take_and_remove_selectors() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::UserAgent;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".a, .b { width: 1px; } .c { width: 2px; } .a { height: 3px; } .d { width: 4px; } .a, .e { width: 5px; }";
+    let mut stylesheet = Stylesheet::from(parse(css, url, origin, qm, media));
+
+    // Taking `.a` keeps the rule for `.b`, and the other rules in order.
+    assert_eq!(
+        stylesheet.take(".a"),
+        StyleDeclarations(SmallVec::from_vec(vec![Layout(Width(StyleUnit::Point(1.0.into())))]))
+    );
+    assert_eq!(
+        stylesheet.get_ref(".b"),
+        Some(&StyleDeclarations(SmallVec::from_vec(vec![Layout(Width(StyleUnit::Point(1.0.into())))])))
+    );
+    assert_eq!(stylesheet.index_of(".b"), Some(0));
+    assert_eq!(stylesheet.index_of(".c"), Some(1));
+
+    // The remaining rules for `.a` are merged in source order.
+    assert_eq!(
+        stylesheet.take_all(".a"),
+        StyleDeclarations(SmallVec::from_vec(vec![
+            Layout(Height(StyleUnit::Point(3.0.into()))),
+            Layout(Width(StyleUnit::Point(5.0.into())))
+        ]))
+    );
+    assert_eq!(stylesheet.index_of(".a"), None);
+    assert_eq!(stylesheet.index_of(".d"), Some(2));
+    assert_eq!(stylesheet.index_of(".e"), Some(3));
+
+    assert!(stylesheet.remove_selector(".b"));
+    assert!(!stylesheet.remove_selector(".b"));
+    assert_eq!(stylesheet.get_ref(".b"), None);
+    assert_eq!(stylesheet.index_of(".c"), Some(0));
+    assert_eq!(
+        stylesheet.take_all(".e"),
+        StyleDeclarations(SmallVec::from_vec(vec![Layout(Width(StyleUnit::Point(5.0.into())))]))
+    );
+}
+
+#[test]
+fn test_stylesheet_composition() {
+    let sheet = |css: &str| {
+        let url = Url::parse("about::test").unwrap();
+        let origin = Origin::UserAgent;
+        let qm = QuirksMode::NoQuirks;
+        let media = MediaList::empty();
+        Stylesheet::from(parse(css, url, origin, qm, media))
+    };
+
+    let component = sheet(".title { width: 3px; }").scoped(".component-x");
+    let theme = sheet(".component-x .title { width: 2px; height: 2px; }").with_origin(StyleOrigin::User);
+    let base = sheet(".component-x .title { height: 1px; } .body { width: 1px; }").with_origin(StyleOrigin::UserAgent);
+
+    // Author rules come first in the combined sheet, but last in the cascade.
+    let mut stylesheet: Stylesheet = vec![component, theme, base].into_iter().collect();
+
+    let cascade: Vec<_> = stylesheet
+        .cascade_order()
+        .into_iter()
+        .map(|(origin, rule)| (origin, rule.selectors[0].as_ref().to_string()))
+        .collect();
+    assert_eq!(
+        cascade,
+        vec![
+            (StyleOrigin::UserAgent, ".component-x .title".to_string()),
+            (StyleOrigin::UserAgent, ".body".to_string()),
+            (StyleOrigin::User, ".component-x .title".to_string()),
+            (StyleOrigin::Author, ".component-x .title".to_string())
+        ]
+    );
+
+    assert_eq!(stylesheet.index_of(".title"), None);
+    assert_eq!(
+        stylesheet.take_all(".component-x .title"),
+        StyleDeclarations(SmallVec::from_vec(vec![
+            Layout(Height(StyleUnit::Point(1.0.into()))),
+            Layout(Width(StyleUnit::Point(2.0.into()))),
+            Layout(Height(StyleUnit::Point(2.0.into()))),
+            Layout(Width(StyleUnit::Point(3.0.into())))
+        ]))
+    );
+    assert_eq!(stylesheet.cascade_order().len(), 1);
+
+    // Origins survive serialization.
+    let stringified = serde_json::to_string(&stylesheet).unwrap();
+    let deserialized: Stylesheet = serde_json::from_str(&stringified).unwrap();
+    assert_eq!(deserialized.cascade_order()[0].0, StyleOrigin::UserAgent);
+
+    // Pseudo-elements on their own apply to the scope, and each selector of
+    // a list is scoped, without splitting at commas within strings.
+    let scoped = sheet("::before, .title::after, a[title=\"a, b\"] { width: 1px; }").scoped(".component-x");
+    let selectors: Vec<&str> = scoped.cascade_order()[0].1.selectors.iter().map(|selector| selector.as_ref()).collect();
+    assert_eq!(selectors, vec![".component-x::before", ".component-x .title::after", ".component-x a[title=\"a, b\"]"]);
+
+    let list = Stylesheet::from(InlineRules::from_vec(vec![StyleRule {
+        selectors: StyleSelectors(InlineSelectors::from_vec(vec![StyleSelector::from(".a, :after,.b > .c")])),
+        declarations: StyleDeclarations(InlineDeclarations::new())
+    }])).scoped(".s");
+    let selectors: Vec<&str> = list.cascade_order()[0].1.selectors.iter().map(|selector| selector.as_ref()).collect();
+    assert_eq!(selectors, vec![".s .a", ".s:after", ".s .b > .c"]);
+    assert_eq!(list.index_of(".s .b > .c"), Some(0));
+}
+
+#[test]
+fn test_class_name_hashing() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::Author;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".button, .nav .button:hover { width: 1px; } a[title=\".button\"].link { width: 2px; }";
+    let (mut stylesheet, class_names) = Stylesheet::from_servo_with_class_names(parse(css, url, origin, qm, media));
+
+    assert_eq!(class_names.len(), 3);
+    let button = class_names.get("button").unwrap().to_string();
+    let nav = class_names.get("nav").unwrap().to_string();
+    let link = class_names.get("link").unwrap().to_string();
+    assert!(button.starts_with("button_") && button.len() == "button_".len() + 8);
+    assert_eq!(class_names.get("title"), None);
+
+    assert_eq!(stylesheet.index_of(".button"), None);
+    assert_eq!(stylesheet.index_of(format!(".{}", button)), Some(0));
+    assert_eq!(stylesheet.index_of(format!(".{} .{}:hover", nav, button)), Some(0));
+    assert_eq!(stylesheet.index_of(format!("a[title=\".button\"].{}", link)), Some(1));
+    assert_eq!(stylesheet.take(format!(".{}", button)).len(), 1);
+
+    // The same CSS always hashes to the same names, including at compile time.
+    let (stylesheet, expected_class_names) = css_module!(".button, .nav .button:hover { width: 1px; } a[title=\".button\"].link { width: 2px; }");
+    assert_eq!(expected_class_names, class_names);
+    assert_eq!(stylesheet.index_of(format!(".{}", button)), Some(0));
+    assert_eq!(ClassNames::from(vec![("button", "button_00000000")]).get("button"), Some("button_00000000"));
+}
+
+#[test]
+fn test_element_state_styles() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::Author;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = ".btn:hover:active { width: 3px; } .btn:hover { width: 2px; } .btn { width: 1px; height: 1px; } \
+               .btn:disabled { opacity: 0.5; } .nav:focus .btn { height: 4px; }";
+    let stylesheet = Stylesheet::from(parse(css, url, origin, qm, media));
+
+    let width = |value: f32| Layout(Width(StyleUnit::Point(value.into())));
+    let height = |value: f32| Layout(Height(StyleUnit::Point(value.into())));
+
+    assert_eq!(
+        stylesheet.get_for_state(".btn", ElementState::empty()),
+        StyleDeclarations(SmallVec::from_vec(vec![width(1.0), height(1.0)]))
+    );
+    assert_eq!(
+        stylesheet.get_for_state(".btn", ElementState::HOVER),
+        StyleDeclarations(SmallVec::from_vec(vec![width(1.0), height(1.0), width(2.0)]))
+    );
+    assert_eq!(
+        stylesheet.get_for_state(".btn", ElementState::HOVER | ElementState::ACTIVE),
+        StyleDeclarations(SmallVec::from_vec(vec![width(1.0), height(1.0), width(2.0), width(3.0)]))
+    );
+    assert_eq!(stylesheet.get_for_state(".btn", ElementState::ACTIVE).len(), 2);
+    assert_eq!(stylesheet.get_for_state(".other", ElementState::all()).len(), 0);
+
+    // Ancestor states, like the focused `.nav`, aren't the element's own.
+    let states = stylesheet.affecting_states(".btn");
+    assert_eq!(states, ElementState::HOVER | ElementState::ACTIVE | ElementState::DISABLED);
+    assert!(!states.intersects(ElementState::FOCUS));
+    assert_eq!(stylesheet.affecting_states(".nav:focus .btn"), ElementState::empty());
+
+    let dependencies = stylesheet.state_dependencies();
+    assert_eq!(dependencies.len(), 1);
+    assert_eq!(dependencies[".btn"], states);
+
+    // States within `:not(...)` or attribute values aren't the element's own.
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let css = ".btn:not(:hover) { width: 5px; } .btn:hover { width: 6px; } \
+               a[href=\":focus\"] { width: 7px; } a[href=\":focus\"]:focus { width: 8px; }";
+    let stylesheet = Stylesheet::from(parse(css, url, origin, qm, media));
+
+    assert_eq!(stylesheet.get_for_state(".btn", ElementState::all()), StyleDeclarations(SmallVec::from_vec(vec![width(6.0)])));
+    assert_eq!(
+        stylesheet.get_for_state(".btn:not(:hover)", ElementState::empty()),
+        StyleDeclarations(SmallVec::from_vec(vec![width(5.0)]))
+    );
+    assert_eq!(stylesheet.affecting_states(".btn:not(:hover)"), ElementState::empty());
+    assert_eq!(
+        stylesheet.get_for_state("a[href=\":focus\"]", ElementState::FOCUS),
+        StyleDeclarations(SmallVec::from_vec(vec![width(7.0), width(8.0)]))
+    );
+    assert_eq!(stylesheet.affecting_states("a[href=\":focus\"]"), ElementState::FOCUS);
+
+    let dependencies = stylesheet.state_dependencies();
+    assert_eq!(dependencies.len(), 2);
+    assert_eq!(dependencies[".btn"], ElementState::HOVER);
+    assert_eq!(dependencies["a[href=\":focus\"]"], ElementState::FOCUS);
+}
+
+#[test]
+fn test_generated_content() {
+    let url = Url::parse("about::test").unwrap();
+    let origin = Origin::Author;
+    let qm = QuirksMode::NoQuirks;
+    let media = MediaList::empty();
+
+    let css = "ol { counter-reset: item; } li { counter-increment: item; } \
+               li::before { content: counter(item, upper-roman) \". \" open-quote; } li::after { content: close-quote; } \
+               blockquote { quotes: \"<\" \">\" \"{\" \"}\"; }";
+    let stylesheet = Stylesheet::from(parse(css, url, origin, qm, media));
+
+    // Pseudo-element rules are kept apart from the rules for their elements.
+    assert_eq!(stylesheet.rules_for(SelectorKey::Tag("li")).len(), 1);
+    assert_eq!(stylesheet.pseudo_rules_for(SelectorKey::Tag("li"), PseudoElement::Before).len(), 1);
+    assert_eq!(stylesheet.pseudo_rules_for(SelectorKey::Tag("li"), PseudoElement::After).len(), 1);
+    assert!(stylesheet.get_pseudo("ol", PseudoElement::Before).is_none());
+
+    let ol_styles = stylesheet.get_ref("ol").unwrap();
+    let li_styles = stylesheet.get_ref("li").unwrap();
+    let before_styles = stylesheet.get_pseudo("li", PseudoElement::Before).unwrap();
+    assert_eq!(
+        ol_styles,
+        &StyleDeclarations(SmallVec::from_vec(vec![
+            Theme(CounterReset(SmallVec::from_vec(vec![Counter { name: CounterName::from("item"), value: 0 }])))
+        ]))
+    );
+    assert_eq!(
+        li_styles,
+        &StyleDeclarations(SmallVec::from_vec(vec![
+            Theme(CounterIncrement(SmallVec::from_vec(vec![Counter { name: CounterName::from("item"), value: 1 }])))
+        ]))
+    );
+    assert_eq!(
+        before_styles,
+        &StyleDeclarations(SmallVec::from_vec(vec![
+            Theme(ThemeStyle::Content(Content::Items(SmallVec::from_vec(vec![
+                ContentItem::Counter(CounterName::from("item"), CounterStyle::UpperRoman),
+                ContentItem::String(ContentString::from(". ")),
+                ContentItem::OpenQuote
+            ]))))
+        ]))
+    );
+    assert_eq!(
+        stylesheet.get_ref("blockquote").unwrap(),
+        &StyleDeclarations(SmallVec::from_vec(vec![
+            Theme(ThemeStyle::Quotes(Quotes::Pairs(SmallVec::from_vec(vec![
+                QuotePair { open: "<".into(), close: ">".into() },
+                QuotePair { open: "{".into(), close: "}".into() }
+            ]))))
+        ]))
+    );
+
+    // Siblings continue the counters reset by their parent.
+    let ol = Arc::new(ComputedStyles::compute(None, &[ol_styles], UnknownElement).0);
+    let (first, _) = ComputedStyles::compute(Some(&ol), &[li_styles], UnknownElement);
+    let second = Arc::new(ComputedStyles::compute_following(Some(&ol), Some(&first), None, &[li_styles], UnknownElement).0);
+    assert_eq!(first.counter_value("item"), 1);
+    assert_eq!(second.counter_value("item"), 2);
+    assert_eq!(ol.counter_value("other"), 0);
+
+    // Counters don't keep siblings from sharing their inherited styles.
+    assert!(Arc::ptr_eq(&ol.inherited, &first.inherited));
+    assert!(Arc::ptr_eq(&first.inherited, &second.inherited));
+
+    // Siblings computed through the sharing cache are numbered too, while
+    // those that don't change the counters still share their styles.
+    let mut cache = StyleSharingCache::default();
+    let li_rule = stylesheet.get_rule("li").unwrap();
+    let (cached_first, _) = cache.compute(Some(&ol), &[li_rule], UnknownElement);
+    let (cached_second, _) = cache.compute_following(Some(&ol), Some(&cached_first), None, &[li_rule], UnknownElement);
+    let (cached_third, _) = cache.compute_following(Some(&ol), Some(&cached_second), None, &[li_rule], UnknownElement);
+    assert_eq!(cached_first.counter_value("item"), 1);
+    assert_eq!(cached_second.counter_value("item"), 2);
+    assert_eq!(cached_third.counter_value("item"), 3);
+    let (unnumbered, _) = cache.compute_following(Some(&ol), Some(&cached_third), None, &[], UnknownElement);
+    let (next_unnumbered, _) = cache.compute_following(Some(&ol), Some(&unnumbered), None, &[], UnknownElement);
+    assert!(Arc::ptr_eq(&unnumbered, &next_unnumbered));
+    assert_eq!(next_unnumbered.counter_value("item"), 3);
+
+    let (before, _) = ComputedStyles::compute(Some(&second), &[before_styles], UnknownElement);
+    assert_eq!(before.generated_text(|_| None), Some("II. \u{201c}".to_owned()));
+    assert_eq!(before.quote_depth_after(), 1);
+    assert_eq!(second.generated_text(|_| None), None);
+
+    // Counters follow document order: siblings continue from the previous
+    // sibling's last descendant, keeping its increments, but not the counters
+    // reset within it.
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let css = "body { counter-reset: section; } div { counter-reset: div; } h2 { counter-increment: section; counter-reset: h2; }";
+    let sections = Stylesheet::from(parse(css, url, origin, qm, media));
+    let body_styles = sections.get_ref("body").unwrap();
+    let div_styles = sections.get_ref("div").unwrap();
+    let h2_styles = sections.get_ref("h2").unwrap();
+
+    let body = Arc::new(ComputedStyles::compute(None, &[body_styles], UnknownElement).0);
+    let first_div = Arc::new(ComputedStyles::compute(Some(&body), &[div_styles], UnknownElement).0);
+    let (first_h2, _) = ComputedStyles::compute(Some(&first_div), &[h2_styles], UnknownElement);
+    let (second_div, _) = ComputedStyles::compute_following(Some(&body), Some(&first_div), Some(&first_h2), &[div_styles], UnknownElement);
+    let second_div = Arc::new(second_div);
+    let (second_h2, _) = ComputedStyles::compute(Some(&second_div), &[h2_styles], UnknownElement);
+    assert_eq!(first_h2.counter_value("section"), 1);
+    assert_eq!(first_h2.counters.len(), 3);
+    assert_eq!(second_div.counter_value("section"), 1);
+    assert_eq!(second_div.counters.len(), 2);
+    assert_eq!(second_h2.counter_value("section"), 2);
+
+    let mut cache = StyleSharingCache::default();
+    let div_rule = sections.get_rule("div").unwrap();
+    let (cached_div, _) = cache.compute_following(Some(&body), Some(&first_div), Some(&first_h2), &[div_rule], UnknownElement);
+    let (uncounted_div, _) = cache.compute_following(Some(&body), Some(&first_div), None, &[div_rule], UnknownElement);
+    assert_eq!(cached_div.counter_value("section"), 1);
+    assert_eq!(uncounted_div.counter_value("section"), 0);
+
+    // Nested lists reset a nested counter of the same name.
+    let nested_ol = Arc::new(ComputedStyles::compute(Some(&second), &[ol_styles], UnknownElement).0);
+    let nested_li = Arc::new(ComputedStyles::compute(Some(&nested_ol), &[li_styles], UnknownElement).0);
+    assert_eq!(nested_li.counters.len(), 2);
+    assert_eq!(nested_li.counter_value("item"), 1);
+
+    let (mut nested_before, _) = ComputedStyles::compute(Some(&nested_li), &[], UnknownElement);
+    nested_before.content = Content::parse("counters(item, \".\") \" \" attr(title)").unwrap();
+    let title = |name: &str| if name == "title" { Some("Intro".to_owned()) } else { None };
+    assert_eq!(nested_before.generated_text(title), Some("2.1 Intro".to_owned()));
+
+    // Typed values parse and format on their own too.
+    assert_eq!(Content::parse("none"), Some(Content::None));
+    assert_eq!(Content::parse("url(image.png)"), None);
+    assert_eq!(Quotes::parse("\"<\""), None);
+    assert_eq!(CounterStyle::LowerAlpha.format(28), "ab");
+    assert_eq!(CounterStyle::UpperRoman.format(1994), "MCMXCIV");
+    assert_eq!(CounterStyle::DecimalLeadingZero.format(7), "07");
+    assert_eq!(CounterStyle::LowerRoman.format(0), "0");
+
+    // Serialized generated content parses back into the same values.
+    let url = Url::parse("about::test").unwrap();
+    let media = MediaList::empty();
+    let css = stylesheet.to_css_string(CssFormat::Minified);
+    assert!(css.contains("li::before{content:counter(item, upper-roman) \". \" open-quote}"));
+    let reparsed = Stylesheet::from(parse(&css, url, origin, qm, media));
+    assert_eq!(reparsed, stylesheet);
+}